@@ -0,0 +1,162 @@
+/*
+ * C API for robot_rs.
+ *
+ * Every function that can fail returns a RobotError; ROBOT_ERROR_OK is zero. After a
+ * failure, robot_last_error_message() describes it until the next call on the same thread.
+ *
+ * Handles come from the *_new functions and must be released with the matching *_free
+ * function. A handle may be used from any thread, but not from two threads at once.
+ * Strings are NUL-terminated UTF-8. Durations are in milliseconds and scroll amounts in
+ * wheel units, 120 per notch. Buttons and events are the ROBOT_BUTTON_* and ROBOT_EVENT_*
+ * constants.
+ *
+ * Event callbacks run on the thread that triggered the event, with the user_data
+ * pointer given at registration. They must stay valid until the mouse is freed.
+ */
+
+#ifndef ROBOT_RS_H
+#define ROBOT_RS_H
+
+/* Generated by cbindgen from src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define ROBOT_BUTTON_LEFT 0
+
+#define ROBOT_BUTTON_RIGHT 1
+
+#define ROBOT_BUTTON_MIDDLE 2
+
+#define ROBOT_BUTTON_BACK 3
+
+#define ROBOT_BUTTON_FORWARD 4
+
+#define ROBOT_EVENT_CLICK 0
+
+typedef enum RobotError {
+  ROBOT_ERROR_OK = 0,
+  ROBOT_ERROR_INVALID_INPUT = 1,
+  ROBOT_ERROR_CONVERSION_ERROR = 2,
+  ROBOT_ERROR_IO_ERROR = 3,
+  ROBOT_ERROR_OUT_OF_BOUNDS = 4,
+  ROBOT_ERROR_WINDOW_ERROR = 5,
+  ROBOT_ERROR_CLIPBOARD_ERROR = 6,
+  ROBOT_ERROR_ABORTED = 7,
+  ROBOT_ERROR_INJECTION_BLOCKED = 8,
+  ROBOT_ERROR_BACKEND_UNAVAILABLE = 9,
+  ROBOT_ERROR_PERMISSION_DENIED = 10,
+  ROBOT_ERROR_OS = 11,
+  ROBOT_ERROR_UNSUPPORTED_BUTTON = 12,
+  ROBOT_ERROR_INVALID_ACTION = 13,
+  ROBOT_ERROR_CANCELLED = 14,
+  ROBOT_ERROR_EXTERNAL_MOVEMENT = 15,
+  ROBOT_ERROR_NULL_POINTER = 100,
+  ROBOT_ERROR_INVALID_STRING = 101,
+  ROBOT_ERROR_SCREEN = 102,
+  ROBOT_ERROR_PANIC = 103,
+} RobotError;
+
+typedef struct Keyboard Keyboard;
+
+typedef struct Mouse Mouse;
+
+typedef void (*RobotCallback)(void *user_data);
+
+typedef struct RobotImage {
+  size_t width;
+  size_t height;
+  uint8_t *rgba;
+  size_t len;
+} RobotImage;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+const char *robot_last_error_message(void);
+
+struct Mouse *robot_mouse_new(void);
+
+void robot_mouse_free(struct Mouse *handle);
+
+enum RobotError robot_mouse_position(struct Mouse *handle, int32_t *x, int32_t *y);
+
+enum RobotError robot_mouse_screen_size(struct Mouse *handle, int32_t *width, int32_t *height);
+
+enum RobotError robot_mouse_move_to(struct Mouse *handle, int32_t x, int32_t y);
+
+enum RobotError robot_mouse_move_relative(struct Mouse *handle, int32_t dx, int32_t dy);
+
+enum RobotError robot_mouse_hover(struct Mouse *handle, int32_t x, int32_t y, uint64_t duration_ms);
+
+enum RobotError robot_mouse_move_in_circle(struct Mouse *handle,
+                                           int32_t center_x,
+                                           int32_t center_y,
+                                           int32_t radius,
+                                           uint64_t duration_ms);
+
+enum RobotError robot_mouse_click(struct Mouse *handle, int32_t button, size_t count);
+
+enum RobotError robot_mouse_press(struct Mouse *handle, int32_t button);
+
+enum RobotError robot_mouse_release(struct Mouse *handle, int32_t button);
+
+enum RobotError robot_mouse_release_all(struct Mouse *handle);
+
+enum RobotError robot_mouse_is_pressed(struct Mouse *handle, int32_t button, bool *pressed);
+
+enum RobotError robot_mouse_scroll(struct Mouse *handle, int32_t amount);
+
+enum RobotError robot_mouse_scroll_horizontal(struct Mouse *handle, int32_t amount);
+
+enum RobotError robot_mouse_drag(struct Mouse *handle, int32_t dx, int32_t dy);
+
+enum RobotError robot_mouse_drag_with_duration(struct Mouse *handle,
+                                               int32_t dx,
+                                               int32_t dy,
+                                               uint64_t duration_ms);
+
+enum RobotError robot_mouse_on(struct Mouse *handle,
+                               int32_t event,
+                               RobotCallback callback,
+                               void *user_data);
+
+struct Keyboard *robot_keyboard_new(void);
+
+void robot_keyboard_free(struct Keyboard *handle);
+
+enum RobotError robot_keyboard_type_text(struct Keyboard *handle, const char *text);
+
+enum RobotError robot_keyboard_paste_text(struct Keyboard *handle, const char *text);
+
+enum RobotError robot_keyboard_press_combo(struct Keyboard *handle, const char *combo);
+
+enum RobotError robot_keyboard_press_key(struct Keyboard *handle, const char *name);
+
+enum RobotError robot_keyboard_release_key(struct Keyboard *handle, const char *name);
+
+enum RobotError robot_keyboard_tap_key(struct Keyboard *handle, const char *name);
+
+enum RobotError robot_screen_pixel(int32_t x,
+                                   int32_t y,
+                                   uint8_t *red,
+                                   uint8_t *green,
+                                   uint8_t *blue);
+
+enum RobotError robot_screen_capture(int32_t left,
+                                     int32_t top,
+                                     int32_t right,
+                                     int32_t bottom,
+                                     struct RobotImage *image);
+
+void robot_image_free(struct RobotImage *image);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ROBOT_RS_H */
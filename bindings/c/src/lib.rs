@@ -0,0 +1,611 @@
+// Every exported function takes raw pointers from C; the safety rules are spelled out
+// once in the generated header instead of on each function.
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    time::Duration,
+};
+
+use robot_rs::{ButtonAction, ClickOptions, EventType, Key, MouseButton, MouseError, Rect, Screen};
+
+pub const ROBOT_BUTTON_LEFT: i32 = 0;
+pub const ROBOT_BUTTON_RIGHT: i32 = 1;
+pub const ROBOT_BUTTON_MIDDLE: i32 = 2;
+pub const ROBOT_BUTTON_BACK: i32 = 3;
+pub const ROBOT_BUTTON_FORWARD: i32 = 4;
+
+pub const ROBOT_EVENT_CLICK: i32 = 0;
+
+// The first codes mirror MouseError variant for variant; the ones from 100 up are raised
+// by the C API itself. Values are part of the ABI and must never be renumbered.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobotError {
+    Ok = 0,
+    InvalidInput = 1,
+    ConversionError = 2,
+    IoError = 3,
+    OutOfBounds = 4,
+    WindowError = 5,
+    ClipboardError = 6,
+    Aborted = 7,
+    InjectionBlocked = 8,
+    BackendUnavailable = 9,
+    PermissionDenied = 10,
+    Os = 11,
+    UnsupportedButton = 12,
+    InvalidAction = 13,
+    Cancelled = 14,
+    ExternalMovement = 15,
+    NullPointer = 100,
+    InvalidString = 101,
+    Screen = 102,
+    Panic = 103,
+}
+
+pub struct Mouse(robot_rs::Mouse);
+
+pub struct Keyboard(robot_rs::Keyboard);
+
+// Pixels are tightly packed RGBA rows; free them with robot_image_free.
+#[repr(C)]
+pub struct RobotImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: *mut u8,
+    pub len: usize,
+}
+
+pub type RobotCallback = Option<extern "C" fn(user_data: *mut c_void)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(code: RobotError, message: impl Into<String>) -> RobotError {
+    // Interior NULs can't cross into C, so they are dropped from the message.
+    let message = CString::new(message.into().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    code
+}
+
+fn mouse_error(err: MouseError) -> RobotError {
+    let code = match err {
+        MouseError::InvalidInput => RobotError::InvalidInput,
+        MouseError::ConversionError(_) => RobotError::ConversionError,
+        MouseError::IoError(_) => RobotError::IoError,
+        MouseError::OutOfBounds => RobotError::OutOfBounds,
+        MouseError::WindowError(_) => RobotError::WindowError,
+        MouseError::ClipboardError(_) => RobotError::ClipboardError,
+        MouseError::Aborted => RobotError::Aborted,
+        MouseError::InjectionBlocked => RobotError::InjectionBlocked,
+        MouseError::BackendUnavailable(_) => RobotError::BackendUnavailable,
+        MouseError::PermissionDenied => RobotError::PermissionDenied,
+        MouseError::Os { .. } => RobotError::Os,
+        MouseError::UnsupportedButton(_) => RobotError::UnsupportedButton,
+        MouseError::InvalidAction { .. } => RobotError::InvalidAction,
+        MouseError::Cancelled => RobotError::Cancelled,
+        MouseError::ExternalMovement { .. } => RobotError::ExternalMovement,
+    };
+    fail(code, err.to_string())
+}
+
+// Runs one API call, turning errors and panics into codes so nothing unwinds into C.
+fn guard<F>(f: F) -> RobotError
+where
+    F: FnOnce() -> Result<(), RobotError>,
+{
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => RobotError::Ok,
+        Ok(Err(code)) => code,
+        Err(_) => fail(RobotError::Panic, "robot_rs panicked"),
+    }
+}
+
+unsafe fn mouse<'a>(handle: *mut Mouse) -> Result<&'a mut robot_rs::Mouse, RobotError> {
+    match handle.as_mut() {
+        Some(handle) => Ok(&mut handle.0),
+        None => Err(fail(RobotError::NullPointer, "mouse handle is null")),
+    }
+}
+
+unsafe fn keyboard<'a>(handle: *mut Keyboard) -> Result<&'a mut robot_rs::Keyboard, RobotError> {
+    match handle.as_mut() {
+        Some(handle) => Ok(&mut handle.0),
+        None => Err(fail(RobotError::NullPointer, "keyboard handle is null")),
+    }
+}
+
+unsafe fn string<'a>(text: *const c_char) -> Result<&'a str, RobotError> {
+    if text.is_null() {
+        return Err(fail(RobotError::NullPointer, "string is null"));
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|_| fail(RobotError::InvalidString, "string is not valid UTF-8"))
+}
+
+unsafe fn write<T>(out: *mut T, value: T) -> Result<(), RobotError> {
+    if out.is_null() {
+        return Err(fail(RobotError::NullPointer, "output pointer is null"));
+    }
+    out.write(value);
+    Ok(())
+}
+
+fn button(code: i32) -> Result<MouseButton, RobotError> {
+    match code {
+        ROBOT_BUTTON_LEFT => Ok(MouseButton::Left),
+        ROBOT_BUTTON_RIGHT => Ok(MouseButton::Right),
+        ROBOT_BUTTON_MIDDLE => Ok(MouseButton::Middle),
+        ROBOT_BUTTON_BACK => Ok(MouseButton::Back),
+        ROBOT_BUTTON_FORWARD => Ok(MouseButton::Forward),
+        _ => Err(fail(
+            RobotError::UnsupportedButton,
+            format!("unknown button code {}", code),
+        )),
+    }
+}
+
+fn key(name: &str) -> Result<Key, RobotError> {
+    name.parse().map_err(mouse_error)
+}
+
+// Returns the message for the last failed call on this thread, or NULL. The pointer stays
+// valid until the next API call on the same thread.
+#[no_mangle]
+pub extern "C" fn robot_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| match &*last.borrow() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn robot_mouse_new() -> *mut Mouse {
+    match panic::catch_unwind(robot_rs::Mouse::new) {
+        Ok(mouse) => Box::into_raw(Box::new(Mouse(mouse))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_free(handle: *mut Mouse) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_position(
+    handle: *mut Mouse,
+    x: *mut i32,
+    y: *mut i32,
+) -> RobotError {
+    guard(|| {
+        let (current_x, current_y) = mouse(handle)?.get_current_position().map_err(mouse_error)?;
+        write(x, current_x)?;
+        write(y, current_y)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_screen_size(
+    handle: *mut Mouse,
+    width: *mut i32,
+    height: *mut i32,
+) -> RobotError {
+    guard(|| {
+        let (screen_width, screen_height) = mouse(handle)?.get_screen_size();
+        write(width, screen_width)?;
+        write(height, screen_height)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_move_to(handle: *mut Mouse, x: i32, y: i32) -> RobotError {
+    guard(|| mouse(handle)?.move_to(x, y).map_err(mouse_error))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_move_relative(
+    handle: *mut Mouse,
+    dx: i32,
+    dy: i32,
+) -> RobotError {
+    guard(|| mouse(handle)?.move_relative(dx, dy).map_err(mouse_error))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_hover(
+    handle: *mut Mouse,
+    x: i32,
+    y: i32,
+    duration_ms: u64,
+) -> RobotError {
+    guard(|| {
+        mouse(handle)?
+            .hover(x, y, Duration::from_millis(duration_ms))
+            .map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_move_in_circle(
+    handle: *mut Mouse,
+    center_x: i32,
+    center_y: i32,
+    radius: i32,
+    duration_ms: u64,
+) -> RobotError {
+    guard(|| {
+        mouse(handle)?
+            .move_in_circle(
+                center_x,
+                center_y,
+                radius,
+                Duration::from_millis(duration_ms),
+            )
+            .map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_click(
+    handle: *mut Mouse,
+    button: i32,
+    count: usize,
+) -> RobotError {
+    guard(|| {
+        let options = ClickOptions::new(self::button(button)?).count(count);
+        mouse(handle)?.click_with(options).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_press(handle: *mut Mouse, button: i32) -> RobotError {
+    guard(|| {
+        let button = self::button(button)?;
+        mouse(handle)?
+            .simulate_mouse_button(button, ButtonAction::Press)
+            .map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_release(handle: *mut Mouse, button: i32) -> RobotError {
+    guard(|| {
+        let button = self::button(button)?;
+        mouse(handle)?
+            .simulate_mouse_button(button, ButtonAction::Release)
+            .map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_release_all(handle: *mut Mouse) -> RobotError {
+    guard(|| mouse(handle)?.release_all().map_err(mouse_error))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_is_pressed(
+    handle: *mut Mouse,
+    button: i32,
+    pressed: *mut bool,
+) -> RobotError {
+    guard(|| {
+        let button = self::button(button)?;
+        write(pressed, mouse(handle)?.button_state(button))
+    })
+}
+
+// Amounts are in wheel units, 120 per notch, positive scrolling up or right.
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_scroll(handle: *mut Mouse, amount: i32) -> RobotError {
+    guard(|| mouse(handle)?.scroll(amount).map_err(mouse_error))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_scroll_horizontal(
+    handle: *mut Mouse,
+    amount: i32,
+) -> RobotError {
+    guard(|| {
+        mouse(handle)?
+            .scroll_horizontal(amount)
+            .map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_drag(handle: *mut Mouse, dx: i32, dy: i32) -> RobotError {
+    guard(|| mouse(handle)?.drag(dx, dy).map_err(mouse_error))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_drag_with_duration(
+    handle: *mut Mouse,
+    dx: i32,
+    dy: i32,
+    duration_ms: u64,
+) -> RobotError {
+    guard(|| {
+        mouse(handle)?
+            .drag_with_duration(dx, dy, Duration::from_millis(duration_ms))
+            .map_err(mouse_error)
+    })
+}
+
+// Wraps the caller's pointer so the listener can be shared with the mouse's emitter.
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_mouse_on(
+    handle: *mut Mouse,
+    event: i32,
+    callback: RobotCallback,
+    user_data: *mut c_void,
+) -> RobotError {
+    guard(|| {
+        let mouse = mouse(handle)?;
+        let event = match event {
+            ROBOT_EVENT_CLICK => EventType::Click,
+            _ => {
+                return Err(fail(
+                    RobotError::InvalidInput,
+                    format!("unknown event code {}", event),
+                ))
+            }
+        };
+        let callback = callback.ok_or_else(|| fail(RobotError::NullPointer, "callback is null"))?;
+        let user_data = UserData(user_data);
+        mouse.on(event, move || callback(user_data.get()));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn robot_keyboard_new() -> *mut Keyboard {
+    match panic::catch_unwind(robot_rs::Keyboard::new) {
+        Ok(keyboard) => Box::into_raw(Box::new(Keyboard(keyboard))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_free(handle: *mut Keyboard) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_type_text(
+    handle: *mut Keyboard,
+    text: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let text = string(text)?;
+        keyboard(handle)?.type_text(text).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_paste_text(
+    handle: *mut Keyboard,
+    text: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let text = string(text)?;
+        keyboard(handle)?.paste_text(text).map_err(mouse_error)
+    })
+}
+
+// Combos are written like "ctrl+shift+t".
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_press_combo(
+    handle: *mut Keyboard,
+    combo: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let keys = Key::parse_combo(string(combo)?).map_err(mouse_error)?;
+        keyboard(handle)?.press_combo(&keys).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_press_key(
+    handle: *mut Keyboard,
+    name: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let key = key(string(name)?)?;
+        keyboard(handle)?.press_key(key).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_release_key(
+    handle: *mut Keyboard,
+    name: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let key = key(string(name)?)?;
+        keyboard(handle)?.release_key(key).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_keyboard_tap_key(
+    handle: *mut Keyboard,
+    name: *const c_char,
+) -> RobotError {
+    guard(|| {
+        let key = key(string(name)?)?;
+        keyboard(handle)?.tap_key(key).map_err(mouse_error)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_screen_pixel(
+    x: i32,
+    y: i32,
+    red: *mut u8,
+    green: *mut u8,
+    blue: *mut u8,
+) -> RobotError {
+    guard(|| {
+        let (r, g, b) =
+            Screen::pixel(x, y).map_err(|err| fail(RobotError::Screen, err.to_string()))?;
+        write(red, r)?;
+        write(green, g)?;
+        write(blue, b)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_screen_capture(
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+    image: *mut RobotImage,
+) -> RobotError {
+    guard(|| {
+        if image.is_null() {
+            return Err(fail(RobotError::NullPointer, "output pointer is null"));
+        }
+        let captured = Screen::capture(Rect::new(left, top, right, bottom))
+            .map_err(|err| fail(RobotError::Screen, err.to_string()))?;
+        let rgba = captured.rgba.into_boxed_slice();
+        let len = rgba.len();
+        image.write(RobotImage {
+            width: captured.width,
+            height: captured.height,
+            rgba: Box::into_raw(rgba) as *mut u8,
+            len,
+        });
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn robot_image_free(image: *mut RobotImage) {
+    if let Some(image) = image.as_mut() {
+        if !image.rgba.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                image.rgba, image.len,
+            )));
+        }
+        *image = RobotImage {
+            width: 0,
+            height: 0,
+            rgba: ptr::null_mut(),
+            len: 0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let message = robot_last_error_message();
+        if message.is_null() {
+            None
+        } else {
+            Some(
+                unsafe { CStr::from_ptr(message) }
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_null_handle_is_reported() {
+        let code = unsafe { robot_mouse_move_to(ptr::null_mut(), 10, 10) };
+        assert_eq!(code, RobotError::NullPointer);
+        assert_eq!(last_error().as_deref(), Some("mouse handle is null"));
+    }
+
+    #[test]
+    fn test_unknown_button_code() {
+        let code = unsafe { robot_mouse_press(ptr::null_mut(), 42) };
+        assert_eq!(code, RobotError::UnsupportedButton);
+        assert_eq!(last_error().as_deref(), Some("unknown button code 42"));
+    }
+
+    #[test]
+    fn test_invalid_utf8_string() {
+        let text = b"\xff\xfe\0";
+        let code = unsafe { robot_keyboard_type_text(ptr::null_mut(), text.as_ptr().cast()) };
+        // The handle is checked after the string, so the string error wins.
+        assert_eq!(code, RobotError::InvalidString);
+    }
+
+    #[test]
+    fn test_success_clears_last_error() {
+        unsafe { robot_mouse_move_to(ptr::null_mut(), 0, 0) };
+        assert!(last_error().is_some());
+        let code = guard(|| Ok(()));
+        assert_eq!(code, RobotError::Ok);
+        assert!(last_error().is_none());
+    }
+
+    // The header is checked in so C and C# users don't need a Rust toolchain to read it.
+    // Set ROBOT_RS_UPDATE_HEADER=1 to rewrite it after changing the API.
+    #[test]
+    fn test_header_is_up_to_date() {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+        let mut generated = Vec::new();
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(crate_dir.join("src/lib.rs"))
+            .generate()
+            .unwrap()
+            .write(&mut generated);
+        let header = crate_dir.join("include/robot_rs.h");
+        if env::var_os("ROBOT_RS_UPDATE_HEADER").is_some() {
+            fs::write(&header, &generated).unwrap();
+        }
+        assert!(
+            fs::read(&header).unwrap() == generated,
+            "include/robot_rs.h is out of date; rerun with ROBOT_RS_UPDATE_HEADER=1"
+        );
+    }
+
+    #[test]
+    fn test_mouse_error_codes() {
+        assert_eq!(
+            mouse_error(MouseError::OutOfBounds),
+            RobotError::OutOfBounds
+        );
+        assert_eq!(
+            mouse_error(MouseError::UnsupportedButton(9)),
+            RobotError::UnsupportedButton
+        );
+        assert_eq!(
+            last_error().as_deref(),
+            Some("Mouse button 9 is not supported")
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard, TryLockError},
+    time::Duration,
+};
+
+use napi::{
+    bindgen_prelude::{AsyncTask, Buffer},
+    threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+    Env, Error, JsFunction, Result, Status, Task,
+};
+use napi_derive::napi;
+use robot_rs::{ButtonAction, ClickOptions, EventType, Key, MouseButton, MouseError, Rect};
+
+fn mouse_error(err: MouseError) -> Error {
+    let status = match err {
+        MouseError::InvalidInput
+        | MouseError::ConversionError(_)
+        | MouseError::UnsupportedButton(_)
+        | MouseError::InvalidAction { .. } => Status::InvalidArg,
+        _ => Status::GenericFailure,
+    };
+    Error::new(status, err.to_string())
+}
+
+fn screen_error(err: impl ToString) -> Error {
+    Error::new(Status::GenericFailure, err.to_string())
+}
+
+fn button(name: Option<String>) -> Result<MouseButton> {
+    match name {
+        Some(name) => name.parse().map_err(mouse_error),
+        None => Ok(MouseButton::Left),
+    }
+}
+
+fn button_name(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left".to_string(),
+        MouseButton::Right => "right".to_string(),
+        MouseButton::Middle => "middle".to_string(),
+        MouseButton::Back => "back".to_string(),
+        MouseButton::Forward => "forward".to_string(),
+        MouseButton::Other(n) => n.to_string(),
+    }
+}
+
+fn key(name: String) -> Result<Key> {
+    name.parse().map_err(mouse_error)
+}
+
+#[napi(object)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[napi(object)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+#[napi(object)]
+pub struct Region {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl From<Rect> for Region {
+    fn from(rect: Rect) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct Monitor {
+    pub name: String,
+    pub rect: Region,
+    pub work_area: Region,
+    pub primary: bool,
+    pub scale_factor: f64,
+}
+
+#[napi(object)]
+pub struct Pixel {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+#[napi(object)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Buffer,
+}
+
+type SharedMouse = Arc<Mutex<robot_rs::Mouse>>;
+
+fn lock(mouse: &SharedMouse) -> MutexGuard<'_, robot_rs::Mouse> {
+    // A panic mid-operation leaves the mouse itself usable, so poisoning is ignored.
+    mouse.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+// Synchronous methods run on the JS thread, which must not wait for an async operation on
+// the thread pool to finish, so they fail instead while one has the mouse.
+fn try_lock(mouse: &SharedMouse) -> Result<MutexGuard<'_, robot_rs::Mouse>> {
+    match mouse.try_lock() {
+        Ok(mouse) => Ok(mouse),
+        Err(TryLockError::Poisoned(err)) => Ok(err.into_inner()),
+        Err(TryLockError::WouldBlock) => Err(Error::new(
+            Status::GenericFailure,
+            "Mouse is busy with an asynchronous operation".to_string(),
+        )),
+    }
+}
+
+type Operation =
+    Box<dyn FnOnce(&mut robot_rs::Mouse) -> std::result::Result<(), MouseError> + Send>;
+
+// Runs a long mouse operation on the libuv thread pool and settles a Promise with it.
+pub struct MouseTask {
+    mouse: SharedMouse,
+    operation: Option<Operation>,
+}
+
+impl MouseTask {
+    fn new<F>(mouse: &SharedMouse, operation: F) -> AsyncTask<Self>
+    where
+        F: FnOnce(&mut robot_rs::Mouse) -> std::result::Result<(), MouseError> + Send + 'static,
+    {
+        AsyncTask::new(Self {
+            mouse: mouse.clone(),
+            operation: Some(Box::new(operation)),
+        })
+    }
+}
+
+impl Task for MouseTask {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<()> {
+        match self.operation.take() {
+            Some(operation) => operation(&mut lock(&self.mouse)).map_err(mouse_error),
+            None => Ok(()),
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, _output: ()) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[napi(js_name = "Mouse")]
+pub struct JsMouse {
+    inner: SharedMouse,
+}
+
+#[napi]
+impl JsMouse {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(robot_rs::Mouse::new())),
+        }
+    }
+
+    #[napi]
+    pub fn position(&self) -> Result<Point> {
+        let (x, y) = try_lock(&self.inner)?
+            .get_current_position()
+            .map_err(mouse_error)?;
+        Ok(Point { x, y })
+    }
+
+    #[napi]
+    pub fn screen_size(&self) -> Result<Size> {
+        let (width, height) = try_lock(&self.inner)?.get_screen_size();
+        Ok(Size { width, height })
+    }
+
+    #[napi]
+    pub fn move_to(&self, x: i32, y: i32) -> Result<()> {
+        try_lock(&self.inner)?.move_to(x, y).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn move_relative(&self, dx: i32, dy: i32) -> Result<()> {
+        try_lock(&self.inner)?
+            .move_relative(dx, dy)
+            .map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn hover(&self, x: i32, y: i32, duration_ms: u32) -> Result<()> {
+        let duration = Duration::from_millis(duration_ms.into());
+        try_lock(&self.inner)?
+            .hover(x, y, duration)
+            .map_err(mouse_error)
+    }
+
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn hover_async(&self, x: i32, y: i32, duration_ms: u32) -> AsyncTask<MouseTask> {
+        let duration = Duration::from_millis(duration_ms.into());
+        MouseTask::new(&self.inner, move |mouse| mouse.hover(x, y, duration))
+    }
+
+    #[napi]
+    pub fn move_in_circle(
+        &self,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        duration_ms: u32,
+    ) -> Result<()> {
+        let duration = Duration::from_millis(duration_ms.into());
+        try_lock(&self.inner)?
+            .move_in_circle(center_x, center_y, radius, duration)
+            .map_err(mouse_error)
+    }
+
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn move_in_circle_async(
+        &self,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        duration_ms: u32,
+    ) -> AsyncTask<MouseTask> {
+        let duration = Duration::from_millis(duration_ms.into());
+        MouseTask::new(&self.inner, move |mouse| {
+            mouse.move_in_circle(center_x, center_y, radius, duration)
+        })
+    }
+
+    #[napi]
+    pub fn click(&self, button: Option<String>, count: Option<u32>) -> Result<()> {
+        let options = ClickOptions::new(self::button(button)?).count(count.unwrap_or(1) as usize);
+        try_lock(&self.inner)?
+            .click_with(options)
+            .map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn double_click(&self) -> Result<()> {
+        try_lock(&self.inner)?.double_click().map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn right_click(&self) -> Result<()> {
+        try_lock(&self.inner)?.right_click().map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn press(&self, button: Option<String>) -> Result<()> {
+        let button = self::button(button)?;
+        try_lock(&self.inner)?
+            .simulate_mouse_button(button, ButtonAction::Press)
+            .map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn release(&self, button: Option<String>) -> Result<()> {
+        let button = self::button(button)?;
+        try_lock(&self.inner)?
+            .simulate_mouse_button(button, ButtonAction::Release)
+            .map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn release_all(&self) -> Result<()> {
+        try_lock(&self.inner)?.release_all().map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn is_pressed(&self, button: String) -> Result<bool> {
+        let button = self::button(Some(button))?;
+        Ok(try_lock(&self.inner)?.button_state(button))
+    }
+
+    #[napi]
+    pub fn pressed_buttons(&self) -> Result<Vec<String>> {
+        Ok(try_lock(&self.inner)?
+            .pressed_buttons()
+            .into_iter()
+            .map(button_name)
+            .collect())
+    }
+
+    // Amounts are in wheel units, 120 per notch, positive scrolling up or right.
+    #[napi]
+    pub fn scroll(&self, amount: i32) -> Result<()> {
+        try_lock(&self.inner)?.scroll(amount).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn scroll_horizontal(&self, amount: i32) -> Result<()> {
+        try_lock(&self.inner)?
+            .scroll_horizontal(amount)
+            .map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn drag(&self, dx: i32, dy: i32) -> Result<()> {
+        try_lock(&self.inner)?.drag(dx, dy).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn drag_with_duration(&self, dx: i32, dy: i32, duration_ms: u32) -> Result<()> {
+        let duration = Duration::from_millis(duration_ms.into());
+        try_lock(&self.inner)?
+            .drag_with_duration(dx, dy, duration)
+            .map_err(mouse_error)
+    }
+
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn drag_with_duration_async(
+        &self,
+        dx: i32,
+        dy: i32,
+        duration_ms: u32,
+    ) -> AsyncTask<MouseTask> {
+        let duration = Duration::from_millis(duration_ms.into());
+        MouseTask::new(&self.inner, move |mouse| {
+            mouse.drag_with_duration(dx, dy, duration)
+        })
+    }
+
+    // Listeners are queued onto the JS thread, so they also fire for clicks made by the
+    // Promise-returning methods. They don't keep the process alive on their own.
+    #[napi(ts_args_type = "event: 'click', listener: () => void")]
+    pub fn on(&self, env: Env, event: String, listener: JsFunction) -> Result<()> {
+        let event = match event.as_str() {
+            "click" => EventType::Click,
+            _ => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("unknown event {:?}", event),
+                ))
+            }
+        };
+        let mut listener: ThreadsafeFunction<(), ErrorStrategy::Fatal> =
+            listener.create_threadsafe_function(0, |_| Ok(Vec::<()>::new()))?;
+        listener.unref(&env)?;
+        try_lock(&self.inner)?.on(event, move || {
+            listener.call((), ThreadsafeFunctionCallMode::NonBlocking);
+        });
+        Ok(())
+    }
+}
+
+#[napi(js_name = "Keyboard")]
+pub struct JsKeyboard {
+    inner: robot_rs::Keyboard,
+}
+
+#[napi]
+impl JsKeyboard {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: robot_rs::Keyboard::new(),
+        }
+    }
+
+    #[napi]
+    pub fn type_text(&mut self, text: String) -> Result<()> {
+        self.inner.type_text(&text).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn paste_text(&mut self, text: String) -> Result<()> {
+        self.inner.paste_text(&text).map_err(mouse_error)
+    }
+
+    // Combos are written like "ctrl+shift+t".
+    #[napi]
+    pub fn press_combo(&mut self, combo: String) -> Result<()> {
+        let keys = Key::parse_combo(&combo).map_err(mouse_error)?;
+        self.inner.press_combo(&keys).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn press_key(&mut self, name: String) -> Result<()> {
+        self.inner.press_key(key(name)?).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn release_key(&mut self, name: String) -> Result<()> {
+        self.inner.release_key(key(name)?).map_err(mouse_error)
+    }
+
+    #[napi]
+    pub fn tap_key(&mut self, name: String) -> Result<()> {
+        self.inner.tap_key(key(name)?).map_err(mouse_error)
+    }
+}
+
+#[napi(js_name = "Screen")]
+pub struct JsScreen;
+
+#[napi]
+impl JsScreen {
+    #[napi]
+    pub fn monitors() -> Vec<Monitor> {
+        robot_rs::Screen::monitors()
+            .into_iter()
+            .map(|monitor| Monitor {
+                scale_factor: monitor.scale_factor(),
+                name: monitor.name,
+                rect: monitor.rect.into(),
+                work_area: monitor.work_area.into(),
+                primary: monitor.primary,
+            })
+            .collect()
+    }
+
+    #[napi]
+    pub fn pixel(x: i32, y: i32) -> Result<Pixel> {
+        let (red, green, blue) = robot_rs::Screen::pixel(x, y).map_err(screen_error)?;
+        Ok(Pixel { red, green, blue })
+    }
+
+    // Without a region the whole virtual screen is captured.
+    #[napi]
+    pub fn capture(region: Option<Region>) -> Result<Image> {
+        let rect = match region {
+            Some(region) => Rect::new(region.left, region.top, region.right, region.bottom),
+            None => robot_rs::Screen::virtual_rect(),
+        };
+        let image = robot_rs::Screen::capture(rect).map_err(screen_error)?;
+        Ok(Image {
+            width: image.width as u32,
+            height: image.height as u32,
+            rgba: image.rgba.into(),
+        })
+    }
+}
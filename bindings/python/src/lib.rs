@@ -0,0 +1,403 @@
+use std::time::Duration;
+
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+
+create_exception!(robot_rs, MouseError, PyException);
+create_exception!(robot_rs, OutOfBoundsError, MouseError);
+create_exception!(robot_rs, InvalidInputError, MouseError);
+create_exception!(robot_rs, AbortedError, MouseError);
+create_exception!(robot_rs, CancelledError, MouseError);
+create_exception!(robot_rs, ExternalMovementError, MouseError);
+create_exception!(robot_rs, InjectionBlockedError, MouseError);
+create_exception!(robot_rs, PermissionDeniedError, MouseError);
+create_exception!(robot_rs, BackendUnavailableError, MouseError);
+create_exception!(robot_rs, ScreenError, PyException);
+
+fn mouse_error(err: robot_rs::MouseError) -> PyErr {
+    let message = err.to_string();
+    match err {
+        robot_rs::MouseError::OutOfBounds => OutOfBoundsError::new_err(message),
+        robot_rs::MouseError::InvalidInput
+        | robot_rs::MouseError::ConversionError(_)
+        | robot_rs::MouseError::UnsupportedButton(_)
+        | robot_rs::MouseError::InvalidAction { .. } => InvalidInputError::new_err(message),
+        robot_rs::MouseError::Aborted => AbortedError::new_err(message),
+        robot_rs::MouseError::Cancelled => CancelledError::new_err(message),
+        robot_rs::MouseError::ExternalMovement { .. } => ExternalMovementError::new_err(message),
+        robot_rs::MouseError::InjectionBlocked => InjectionBlockedError::new_err(message),
+        robot_rs::MouseError::PermissionDenied => PermissionDeniedError::new_err(message),
+        robot_rs::MouseError::BackendUnavailable(_) => BackendUnavailableError::new_err(message),
+        _ => MouseError::new_err(message),
+    }
+}
+
+fn screen_error(err: impl ToString) -> PyErr {
+    ScreenError::new_err(err.to_string())
+}
+
+// Python passes durations as seconds.
+fn seconds(value: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(value)
+        .map_err(|_| PyValueError::new_err("durations must be non-negative seconds"))
+}
+
+fn button(name: &str) -> PyResult<robot_rs::MouseButton> {
+    name.parse().map_err(mouse_error)
+}
+
+fn key(name: &str) -> PyResult<robot_rs::Key> {
+    name.parse().map_err(mouse_error)
+}
+
+fn button_name(button: robot_rs::MouseButton) -> String {
+    match button {
+        robot_rs::MouseButton::Left => "left".to_string(),
+        robot_rs::MouseButton::Right => "right".to_string(),
+        robot_rs::MouseButton::Middle => "middle".to_string(),
+        robot_rs::MouseButton::Back => "back".to_string(),
+        robot_rs::MouseButton::Forward => "forward".to_string(),
+        robot_rs::MouseButton::Other(n) => n.to_string(),
+    }
+}
+
+#[pyclass(name = "Mouse")]
+struct PyMouse {
+    inner: robot_rs::Mouse,
+}
+
+#[pymethods]
+impl PyMouse {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: robot_rs::Mouse::new(),
+        }
+    }
+
+    fn position(&mut self) -> PyResult<(i32, i32)> {
+        self.inner.get_current_position().map_err(mouse_error)
+    }
+
+    fn screen_size(&self) -> (i32, i32) {
+        self.inner.get_screen_size()
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> PyResult<()> {
+        self.inner.move_to(x, y).map_err(mouse_error)
+    }
+
+    fn move_relative(&mut self, dx: i32, dy: i32) -> PyResult<()> {
+        self.inner.move_relative(dx, dy).map_err(mouse_error)
+    }
+
+    // Long movements release the GIL so other Python threads keep running.
+    fn hover(&mut self, py: Python<'_>, x: i32, y: i32, duration: f64) -> PyResult<()> {
+        let duration = seconds(duration)?;
+        py.allow_threads(|| self.inner.hover(x, y, duration))
+            .map_err(mouse_error)
+    }
+
+    fn move_in_circle(
+        &mut self,
+        py: Python<'_>,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        duration: f64,
+    ) -> PyResult<()> {
+        let duration = seconds(duration)?;
+        py.allow_threads(|| {
+            self.inner
+                .move_in_circle(center_x, center_y, radius, duration)
+        })
+        .map_err(mouse_error)
+    }
+
+    #[pyo3(signature = (button = "left", count = 1, interval = None, hold = 0.0, at = None))]
+    fn click(
+        &mut self,
+        button: &str,
+        count: usize,
+        interval: Option<f64>,
+        hold: f64,
+        at: Option<(i32, i32)>,
+    ) -> PyResult<()> {
+        let mut options = robot_rs::ClickOptions::new(self::button(button)?)
+            .count(count)
+            .hold_duration(seconds(hold)?);
+        if let Some(interval) = interval {
+            options = options.interval(seconds(interval)?);
+        }
+        if let Some((x, y)) = at {
+            options = options.position(x, y);
+        }
+        self.inner.click_with(options).map_err(mouse_error)
+    }
+
+    fn double_click(&mut self) -> PyResult<()> {
+        self.inner.double_click().map_err(mouse_error)
+    }
+
+    fn right_click(&mut self) -> PyResult<()> {
+        self.inner.right_click().map_err(mouse_error)
+    }
+
+    #[pyo3(signature = (button = "left"))]
+    fn press(&mut self, button: &str) -> PyResult<()> {
+        let button = self::button(button)?;
+        self.inner
+            .simulate_mouse_button(button, robot_rs::ButtonAction::Press)
+            .map_err(mouse_error)
+    }
+
+    #[pyo3(signature = (button = "left"))]
+    fn release(&mut self, button: &str) -> PyResult<()> {
+        let button = self::button(button)?;
+        self.inner
+            .simulate_mouse_button(button, robot_rs::ButtonAction::Release)
+            .map_err(mouse_error)
+    }
+
+    fn release_all(&mut self) -> PyResult<()> {
+        self.inner.release_all().map_err(mouse_error)
+    }
+
+    fn is_pressed(&self, button: &str) -> PyResult<bool> {
+        Ok(self.inner.button_state(self::button(button)?))
+    }
+
+    fn pressed_buttons(&self) -> Vec<String> {
+        self.inner
+            .pressed_buttons()
+            .into_iter()
+            .map(button_name)
+            .collect()
+    }
+
+    #[pyo3(signature = (amount, unit = "notches", horizontal = false, duration = 0.0))]
+    fn scroll(
+        &mut self,
+        py: Python<'_>,
+        amount: f64,
+        unit: &str,
+        horizontal: bool,
+        duration: f64,
+    ) -> PyResult<()> {
+        let amount = match unit {
+            "notches" => robot_rs::ScrollAmount::Notches(amount),
+            "lines" => robot_rs::ScrollAmount::Lines(amount),
+            "pixels" => robot_rs::ScrollAmount::Pixels(amount),
+            _ => return Err(PyValueError::new_err(format!("unknown unit {:?}", unit))),
+        };
+        let axis = if horizontal {
+            robot_rs::ScrollAxis::Horizontal
+        } else {
+            robot_rs::ScrollAxis::Vertical
+        };
+        let options = robot_rs::ScrollOptions::new(amount)
+            .axis(axis)
+            .duration(seconds(duration)?);
+        py.allow_threads(|| self.inner.scroll_by(options))
+            .map_err(mouse_error)
+    }
+
+    fn drag(&mut self, dx: i32, dy: i32) -> PyResult<()> {
+        self.inner.drag(dx, dy).map_err(mouse_error)
+    }
+
+    fn drag_with_duration(
+        &mut self,
+        py: Python<'_>,
+        dx: i32,
+        dy: i32,
+        duration: f64,
+    ) -> PyResult<()> {
+        let duration = seconds(duration)?;
+        py.allow_threads(|| self.inner.drag_with_duration(dx, dy, duration))
+            .map_err(mouse_error)
+    }
+
+    // Callbacks run on the thread that caused the event; an exception they raise is
+    // reported through sys.unraisablehook rather than failing the mouse operation.
+    fn on(&mut self, event: &str, callback: PyObject) -> PyResult<()> {
+        let event = match event {
+            "click" => robot_rs::EventType::Click,
+            _ => return Err(PyValueError::new_err(format!("unknown event {:?}", event))),
+        };
+        self.inner.on(event, move || {
+            Python::with_gil(|py| {
+                if let Err(err) = callback.call0(py) {
+                    err.write_unraisable(py, Some(callback.bind(py)));
+                }
+            })
+        });
+        Ok(())
+    }
+}
+
+#[pyclass(name = "Keyboard")]
+struct PyKeyboard {
+    inner: robot_rs::Keyboard,
+}
+
+#[pymethods]
+impl PyKeyboard {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: robot_rs::Keyboard::new(),
+        }
+    }
+
+    fn type_text(&mut self, py: Python<'_>, text: &str) -> PyResult<()> {
+        py.allow_threads(|| self.inner.type_text(text))
+            .map_err(mouse_error)
+    }
+
+    fn paste_text(&mut self, py: Python<'_>, text: &str) -> PyResult<()> {
+        py.allow_threads(|| self.inner.paste_text(text))
+            .map_err(mouse_error)
+    }
+
+    fn press_combo(&mut self, combo: &str) -> PyResult<()> {
+        let keys = robot_rs::Key::parse_combo(combo).map_err(mouse_error)?;
+        self.inner.press_combo(&keys).map_err(mouse_error)
+    }
+
+    fn press_key(&mut self, name: &str) -> PyResult<()> {
+        self.inner.press_key(key(name)?).map_err(mouse_error)
+    }
+
+    fn release_key(&mut self, name: &str) -> PyResult<()> {
+        self.inner.release_key(key(name)?).map_err(mouse_error)
+    }
+
+    fn tap_key(&mut self, name: &str) -> PyResult<()> {
+        self.inner.tap_key(key(name)?).map_err(mouse_error)
+    }
+}
+
+#[pyclass(name = "Image")]
+struct PyImage {
+    inner: robot_rs::ClipboardImage,
+}
+
+#[pymethods]
+impl PyImage {
+    #[getter]
+    fn width(&self) -> usize {
+        self.inner.width
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.inner.height
+    }
+
+    #[getter]
+    fn rgba<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.rgba)
+    }
+
+    fn to_bmp<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bmp = self.inner.to_bmp().map_err(screen_error)?;
+        Ok(PyBytes::new(py, &bmp))
+    }
+
+    fn save_bmp(&self, path: std::path::PathBuf) -> PyResult<()> {
+        self.inner.save_bmp(path).map_err(screen_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Image({}x{})", self.inner.width, self.inner.height)
+    }
+}
+
+#[pyclass(name = "Screen")]
+struct PyScreen;
+
+#[pymethods]
+impl PyScreen {
+    #[staticmethod]
+    fn monitors<'py>(py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        robot_rs::Screen::monitors()
+            .into_iter()
+            .map(|monitor| {
+                let rect = &monitor.rect;
+                let work_area = &monitor.work_area;
+                let dict = PyDict::new(py);
+                dict.set_item("name", &monitor.name)?;
+                dict.set_item("rect", (rect.left, rect.top, rect.right, rect.bottom))?;
+                dict.set_item(
+                    "work_area",
+                    (
+                        work_area.left,
+                        work_area.top,
+                        work_area.right,
+                        work_area.bottom,
+                    ),
+                )?;
+                dict.set_item("primary", monitor.primary)?;
+                dict.set_item("scale_factor", monitor.scale_factor())?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    #[staticmethod]
+    fn pixel(x: i32, y: i32) -> PyResult<(u8, u8, u8)> {
+        robot_rs::Screen::pixel(x, y).map_err(screen_error)
+    }
+
+    // Captures left, top, right, bottom; without a region, all monitors.
+    #[staticmethod]
+    #[pyo3(signature = (region = None))]
+    fn capture(py: Python<'_>, region: Option<(i32, i32, i32, i32)>) -> PyResult<PyImage> {
+        let rect = match region {
+            Some((left, top, right, bottom)) => robot_rs::Rect::new(left, top, right, bottom),
+            None => robot_rs::Screen::virtual_rect(),
+        };
+        let image = py
+            .allow_threads(|| robot_rs::Screen::capture(rect))
+            .map_err(screen_error)?;
+        Ok(PyImage { inner: image })
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "robot_rs")]
+fn robot_rs_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<PyMouse>()?;
+    m.add_class::<PyKeyboard>()?;
+    m.add_class::<PyScreen>()?;
+    m.add_class::<PyImage>()?;
+    m.add("MouseError", py.get_type::<MouseError>())?;
+    m.add("OutOfBoundsError", py.get_type::<OutOfBoundsError>())?;
+    m.add("InvalidInputError", py.get_type::<InvalidInputError>())?;
+    m.add("AbortedError", py.get_type::<AbortedError>())?;
+    m.add("CancelledError", py.get_type::<CancelledError>())?;
+    m.add(
+        "ExternalMovementError",
+        py.get_type::<ExternalMovementError>(),
+    )?;
+    m.add(
+        "InjectionBlockedError",
+        py.get_type::<InjectionBlockedError>(),
+    )?;
+    m.add(
+        "PermissionDeniedError",
+        py.get_type::<PermissionDeniedError>(),
+    )?;
+    m.add(
+        "BackendUnavailableError",
+        py.get_type::<BackendUnavailableError>(),
+    )?;
+    m.add("ScreenError", py.get_type::<ScreenError>())?;
+    Ok(())
+}
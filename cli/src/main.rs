@@ -0,0 +1,446 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read},
+    iter,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use robot_rs::{
+    ClickOptions, Easing, Inspector, Key, Keyboard, Mouse, MouseButton, Rect, Screen, Script,
+    ScriptLimits, ScrollAmount, ScrollAxis, ScrollOptions, Server, WebDriver, Window,
+};
+use serde_json::{json, Value};
+
+#[derive(Parser)]
+#[command(
+    name = "robot_rs",
+    about = "Drive the mouse and keyboard from the command line"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Move the cursor to X Y
+    Move { x: i32, y: i32 },
+    /// Click at the current cursor position
+    Click {
+        /// left, right, middle, back, forward or a button number
+        #[arg(long, default_value = "left")]
+        button: MouseButton,
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Milliseconds between clicks [default: half the system double-click time]
+        #[arg(long)]
+        interval: Option<u64>,
+        /// Milliseconds to hold each click
+        #[arg(long, default_value_t = 0)]
+        hold: u64,
+    },
+    /// Drag with the left button from the cursor to X Y
+    Drag {
+        x: i32,
+        y: i32,
+        /// Milliseconds the drag should take
+        #[arg(long, default_value_t = 0)]
+        duration: u64,
+    },
+    /// Scroll; positive amounts scroll up or right
+    Scroll {
+        #[arg(allow_negative_numbers = true)]
+        amount: f64,
+        #[arg(long, value_enum, default_value_t = Unit::Notches)]
+        unit: Unit,
+        #[arg(long)]
+        horizontal: bool,
+        /// Milliseconds to animate the scroll over
+        #[arg(long, default_value_t = 0)]
+        duration: u64,
+        #[arg(long, value_enum, default_value_t = EasingArg::Linear)]
+        easing: EasingArg,
+    },
+    /// Type TEXT into the focused window
+    Type { text: String },
+    /// Press a key combination such as ctrl+shift+t
+    Key { combo: String },
+    /// Print the cursor position as JSON
+    Pos,
+    /// Show live details of what is under the cursor
+    Inspect {
+        /// Milliseconds between updates
+        #[arg(long, default_value_t = 50)]
+        interval: u64,
+        /// Key combination that copies a move_to/click snippet to the clipboard
+        #[arg(long, default_value = "ctrl+shift+c")]
+        copy_hotkey: String,
+    },
+    /// Print the connected monitors as JSON
+    Screens,
+    /// Print the visible top-level windows as JSON
+    Windows,
+    /// Save a BMP screenshot of all monitors, or of a region
+    Screenshot {
+        file: PathBuf,
+        #[arg(
+            long,
+            num_args = 4,
+            value_names = ["X", "Y", "WIDTH", "HEIGHT"],
+            allow_negative_numbers = true
+        )]
+        region: Option<Vec<i32>>,
+    },
+    /// Run a Rhai script (*.rhai), or a file of commands, one per line; "-" reads stdin
+    Run {
+        script: PathBuf,
+        /// Seconds a Rhai script may run for; 0 removes the limit
+        #[arg(long, default_value_t = 60)]
+        max_runtime: u64,
+        /// Number of input actions a Rhai script may perform; 0 removes the limit
+        #[arg(long, default_value_t = 10_000)]
+        max_actions: u64,
+    },
+    /// Wait for MS milliseconds
+    Sleep { ms: u64 },
+    /// Serve JSON-RPC remote control over TCP and/or WebSocket
+    Serve {
+        /// Address for newline-delimited JSON-RPC over TCP, e.g. 127.0.0.1:7878
+        #[arg(long)]
+        tcp: Option<String>,
+        /// Address for JSON-RPC over WebSocket, e.g. 127.0.0.1:7879
+        #[arg(long)]
+        websocket: Option<String>,
+        /// Token clients must pass to auth before anything else; required unless both
+        /// addresses are loopback
+        #[arg(long)]
+        token: Option<String>,
+        /// Most connections served at once, across both listeners
+        #[arg(long, default_value_t = 8)]
+        max_sessions: usize,
+    },
+    /// Serve the WebDriver Perform Actions and Release Actions endpoints over HTTP
+    Webdriver {
+        #[arg(long, default_value = "127.0.0.1:4444")]
+        addr: String,
+        /// Token clients must put first in the URL path; required unless the address is
+        /// loopback
+        #[arg(long)]
+        token: Option<String>,
+        /// Most connections served at once
+        #[arg(long, default_value_t = 8)]
+        max_connections: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Unit {
+    Notches,
+    Lines,
+    Pixels,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EasingArg {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut mouse = Mouse::new();
+    let mut keyboard = Keyboard::new();
+    match execute(cli.command, &mut mouse, &mut keyboard) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execute(command: Command, mouse: &mut Mouse, keyboard: &mut Keyboard) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Move { x, y } => mouse.move_to(x, y)?,
+        Command::Click {
+            button,
+            count,
+            interval,
+            hold,
+        } => {
+            let mut options = ClickOptions::new(button)
+                .count(count)
+                .hold_duration(Duration::from_millis(hold));
+            if let Some(interval) = interval {
+                options = options.interval(Duration::from_millis(interval));
+            }
+            mouse.click_with(options)?;
+        }
+        Command::Drag { x, y, duration } => {
+            let (start_x, start_y) = mouse.get_current_position()?;
+            if duration == 0 {
+                mouse.drag(x - start_x, y - start_y)?;
+            } else {
+                let duration = Duration::from_millis(duration);
+                mouse.drag_with_duration(x - start_x, y - start_y, duration)?;
+            }
+        }
+        Command::Scroll {
+            amount,
+            unit,
+            horizontal,
+            duration,
+            easing,
+        } => {
+            let amount = match unit {
+                Unit::Notches => ScrollAmount::Notches(amount),
+                Unit::Lines => ScrollAmount::Lines(amount),
+                Unit::Pixels => ScrollAmount::Pixels(amount),
+            };
+            let axis = if horizontal {
+                ScrollAxis::Horizontal
+            } else {
+                ScrollAxis::Vertical
+            };
+            let easing = match easing {
+                EasingArg::Linear => Easing::Linear,
+                EasingArg::EaseIn => Easing::EaseIn,
+                EasingArg::EaseOut => Easing::EaseOut,
+                EasingArg::EaseInOut => Easing::EaseInOut,
+            };
+            mouse.scroll_by(
+                ScrollOptions::new(amount)
+                    .axis(axis)
+                    .duration(Duration::from_millis(duration))
+                    .easing(easing),
+            )?;
+        }
+        Command::Type { text } => keyboard.type_text(&text)?,
+        Command::Key { combo } => keyboard.press_combo(&Key::parse_combo(&combo)?)?,
+        Command::Pos => {
+            let (x, y) = mouse.get_current_position()?;
+            print_json(json!({ "x": x, "y": y }));
+        }
+        Command::Inspect {
+            interval,
+            copy_hotkey,
+        } => {
+            let inspector = Inspector {
+                interval: Duration::from_millis(interval),
+                copy_hotkey: Key::parse_combo(&copy_hotkey)?,
+            };
+            inspector.run(mouse)?;
+        }
+        Command::Screens => {
+            let monitors: Vec<Value> = Screen::monitors()
+                .iter()
+                .map(|monitor| {
+                    json!({
+                        "name": monitor.name,
+                        "rect": rect_json(&monitor.rect),
+                        "work_area": rect_json(&monitor.work_area),
+                        "primary": monitor.primary,
+                    })
+                })
+                .collect();
+            print_json(Value::Array(monitors));
+        }
+        Command::Windows => {
+            // Windows can close while we enumerate them; skip any that have gone.
+            let windows: Vec<Value> = Window::all()
+                .iter()
+                .filter_map(|window| {
+                    let rect = window.rect().ok()?;
+                    Some(json!({
+                        "title": window.title(),
+                        "class": window.class_name(),
+                        "pid": window.process_id(),
+                        "rect": rect_json(&rect),
+                        "focused": window.is_focused(),
+                        "minimized": window.is_minimized(),
+                        "maximized": window.is_maximized(),
+                    }))
+                })
+                .collect();
+            print_json(Value::Array(windows));
+        }
+        Command::Screenshot { file, region } => {
+            let rect = match region.as_deref() {
+                Some(&[x, y, width, height]) => Rect::new(x, y, x + width, y + height),
+                _ => Screen::virtual_rect(),
+            };
+            Screen::capture(rect)?.save_bmp(file)?;
+        }
+        Command::Run {
+            script,
+            max_runtime,
+            max_actions,
+        } if script
+            .extension()
+            .is_some_and(|extension| extension == "rhai") =>
+        {
+            let limits = ScriptLimits {
+                max_runtime: (max_runtime > 0).then(|| Duration::from_secs(max_runtime)),
+                max_actions: (max_actions > 0).then_some(max_actions),
+                ..ScriptLimits::default()
+            };
+            Script::with_limits(limits).run(&fs::read_to_string(script)?)?;
+        }
+        Command::Run { script, .. } => run_script(&script, mouse, keyboard)?,
+        Command::Sleep { ms } => std::thread::sleep(Duration::from_millis(ms)),
+        Command::Serve {
+            tcp,
+            websocket,
+            token,
+            max_sessions,
+        } => {
+            let mut server = Server::new().max_sessions(max_sessions);
+            if let Some(token) = token {
+                server = server.token(&token);
+            }
+            match (tcp, websocket) {
+                (Some(tcp), Some(websocket)) => {
+                    let tcp_server = server.clone();
+                    let tcp = thread::spawn(move || tcp_server.serve_tcp(tcp));
+                    server.serve_websocket(websocket)?;
+                    tcp.join().map_err(|_| "TCP server panicked")??;
+                }
+                (Some(tcp), None) => server.serve_tcp(tcp)?,
+                (None, Some(websocket)) => server.serve_websocket(websocket)?,
+                (None, None) => return Err("pass --tcp, --websocket or both".into()),
+            }
+        }
+        Command::Webdriver {
+            addr,
+            token,
+            max_connections,
+        } => {
+            let mut webdriver = WebDriver::new().max_connections(max_connections);
+            if let Some(token) = token {
+                webdriver = webdriver.token(&token);
+            }
+            webdriver.serve(addr)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_script(script: &Path, mouse: &mut Mouse, keyboard: &mut Keyboard) -> Result<(), Box<dyn Error>> {
+    let source = if script.as_os_str() == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        source
+    } else {
+        fs::read_to_string(script)?
+    };
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at_line = |err: String| format!("line {}: {}", index + 1, err);
+        let args = split_args(line).map_err(at_line)?;
+        let command = Cli::try_parse_from(iter::once("robot_rs".to_string()).chain(args))
+            .map_err(|err| at_line(err.to_string()))?
+            .command;
+        if let Command::Run { .. } = command {
+            return Err(at_line("scripts cannot run other scripts".to_string()).into());
+        }
+        execute(command, mouse, keyboard).map_err(|err| at_line(err.to_string()))?;
+    }
+    Ok(())
+}
+
+// Splits a script line into arguments the way a shell would for the simple cases:
+// whitespace separates arguments, and quotes group them.
+fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => match chars.next() {
+                Some(escaped) => current.push(escaped),
+                None => return Err("unterminated escape".to_string()),
+            },
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn rect_json(rect: &Rect) -> Value {
+    json!({
+        "left": rect.left,
+        "top": rect.top,
+        "right": rect.right,
+        "bottom": rect.bottom,
+    })
+}
+
+fn print_json(value: Value) {
+    println!("{}", value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args("move 10  20").unwrap(), vec!["move", "10", "20"]);
+        assert_eq!(
+            split_args(r#"type "hello \"world\"""#).unwrap(),
+            vec!["type", "hello \"world\""]
+        );
+        assert_eq!(split_args("type ''").unwrap(), vec!["type", ""]);
+        assert!(split_args("type \"open").is_err());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let cli = Cli::try_parse_from(["robot_rs", "click", "--button", "right", "--count", "2"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Click {
+                button: MouseButton::Right,
+                count: 2,
+                ..
+            }
+        ));
+        let cli = Cli::try_parse_from(["robot_rs", "scroll", "-3", "--unit", "lines"]).unwrap();
+        assert!(matches!(cli.command, Command::Scroll { amount, .. } if amount == -3.0));
+        assert!(Cli::try_parse_from(["robot_rs", "click", "--button", "thumb"]).is_err());
+    }
+}
@@ -0,0 +1,488 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+#[cfg(feature = "local")]
+mod local;
+
+#[cfg(feature = "local")]
+pub use local::Local;
+
+#[derive(Debug)]
+pub enum ClientError {
+    IoError(io::Error),
+    WebSocket(Box<tungstenite::Error>),
+    Protocol(String),
+    Rpc { code: i64, message: String },
+}
+
+impl Error for ClientError {}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::IoError(err) => write!(f, "IO error: {}", err),
+            ClientError::WebSocket(err) => write!(f, "WebSocket error: {}", err),
+            ClientError::Protocol(msg) => write!(f, "Protocol error: {}", msg),
+            ClientError::Rpc { code, message } => write!(f, "Server error {}: {}", code, message),
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> Self {
+        ClientError::IoError(err)
+    }
+}
+
+impl From<tungstenite::Error> for ClientError {
+    fn from(err: tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(err))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u8),
+}
+
+impl Button {
+    // The form robot_rs::MouseButton takes on the wire.
+    fn to_json(self) -> Value {
+        match self {
+            Button::Left => json!("left"),
+            Button::Right => json!("right"),
+            Button::Middle => json!("middle"),
+            Button::Back => json!("back"),
+            Button::Forward => json!("forward"),
+            Button::Other(number) => json!({ "other": number }),
+        }
+    }
+}
+
+// The mouse and keyboard calls that Client and Local share, so that code generic over
+// this trait can drive either a remote machine or this one. Keys and combos are named as
+// robot_rs::Key parses them, e.g. "enter" or "ctrl+shift+t".
+pub trait Automation {
+    type Error: Error;
+
+    fn get_current_position(&mut self) -> Result<(i32, i32), Self::Error>;
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), Self::Error>;
+    fn move_relative(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error>;
+    fn hover(&mut self, x: i32, y: i32, duration: Duration) -> Result<(), Self::Error>;
+    fn click(&mut self) -> Result<(), Self::Error>;
+    fn double_click(&mut self) -> Result<(), Self::Error>;
+    fn right_click(&mut self) -> Result<(), Self::Error>;
+    fn click_button(&mut self, button: Button) -> Result<(), Self::Error>;
+    fn press(&mut self, button: Button) -> Result<(), Self::Error>;
+    fn release(&mut self, button: Button) -> Result<(), Self::Error>;
+    fn release_all(&mut self) -> Result<(), Self::Error>;
+    fn scroll(&mut self, notches: f64) -> Result<(), Self::Error>;
+    fn drag(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error>;
+    fn drag_with_duration(
+        &mut self,
+        distance_x: i32,
+        distance_y: i32,
+        duration: Duration,
+    ) -> Result<(), Self::Error>;
+    fn type_text(&mut self, text: &str) -> Result<(), Self::Error>;
+    fn paste_text(&mut self, text: &str) -> Result<(), Self::Error>;
+    fn press_combo(&mut self, combo: &str) -> Result<(), Self::Error>;
+    fn press_key(&mut self, key: &str) -> Result<(), Self::Error>;
+    fn release_key(&mut self, key: &str) -> Result<(), Self::Error>;
+    fn get_screen_size(&mut self) -> Result<(i32, i32), Self::Error>;
+}
+
+enum Connection {
+    Tcp {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    },
+    WebSocket(Box<WebSocket<MaybeTlsStream<TcpStream>>>),
+}
+
+// Every call runs on the remote machine.
+pub struct Client {
+    connection: Connection,
+    next_id: u64,
+    events: VecDeque<Value>,
+}
+
+impl Client {
+    pub fn connect_tcp<A: ToSocketAddrs>(
+        addr: A,
+        token: Option<&str>,
+    ) -> Result<Self, ClientError> {
+        let writer = TcpStream::connect(addr)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Self::authenticate(Connection::Tcp { reader, writer }, token)
+    }
+
+    pub fn connect_websocket(url: &str, token: Option<&str>) -> Result<Self, ClientError> {
+        let (socket, _) = tungstenite::connect(url)?;
+        Self::authenticate(Connection::WebSocket(Box::new(socket)), token)
+    }
+
+    fn authenticate(connection: Connection, token: Option<&str>) -> Result<Self, ClientError> {
+        let mut client = Self {
+            connection,
+            next_id: 1,
+            events: VecDeque::new(),
+        };
+        if let Some(token) = token {
+            client.call("auth", json!({ "token": token }))?;
+        }
+        Ok(client)
+    }
+
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.send(&request.to_string())?;
+        loop {
+            let mut message = self.receive()?;
+            // Events that arrive while we wait are kept for next_event.
+            if message.get("id").is_none() {
+                self.events.push_back(message["params"].take());
+                continue;
+            }
+            if message["id"] != id {
+                return Err(ClientError::Protocol(format!(
+                    "Expected a response to {}, got {}",
+                    id, message["id"]
+                )));
+            }
+            if let Some(error) = message.get("error") {
+                return Err(ClientError::Rpc {
+                    code: error["code"].as_i64().unwrap_or(0),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+            return Ok(message["result"].take());
+        }
+    }
+
+    // Blocks until the server sends an event the client subscribed to.
+    pub fn next_event(&mut self) -> Result<Value, ClientError> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+        let mut message = self.receive()?;
+        if message.get("id").is_some() {
+            return Err(ClientError::Protocol("Unexpected response".to_string()));
+        }
+        Ok(message["params"].take())
+    }
+
+    pub fn take_events(&mut self) -> Vec<Value> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn subscribe(&mut self, events: &[&str]) -> Result<(), ClientError> {
+        self.call("events.subscribe", json!({ "events": events }))?;
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, events: &[&str]) -> Result<(), ClientError> {
+        self.call("events.unsubscribe", json!({ "events": events }))?;
+        Ok(())
+    }
+
+    // Takes the same JSON that robot_rs::Actions serializes to.
+    pub fn perform(&mut self, actions: Value) -> Result<(), ClientError> {
+        self.call_unit("actions.perform", json!({ "actions": actions }))
+    }
+
+    pub fn monitors(&mut self) -> Result<Value, ClientError> {
+        self.call("screen.monitors", Value::Null)
+    }
+
+    pub fn pixel(&mut self, x: i32, y: i32) -> Result<(u8, u8, u8), ClientError> {
+        let color = self.call("screen.pixel", json!({ "x": x, "y": y }))?;
+        match (color[0].as_u64(), color[1].as_u64(), color[2].as_u64()) {
+            (Some(r), Some(g), Some(b)) => Ok((r as u8, g as u8, b as u8)),
+            _ => Err(ClientError::Protocol(format!("Bad color {}", color))),
+        }
+    }
+
+    // Captures left, top, right, bottom; None captures all monitors.
+    pub fn capture(&mut self, rect: Option<[i32; 4]>) -> Result<Image, ClientError> {
+        let image = self.call("screen.capture", json!({ "rect": rect }))?;
+        let bad_image = || ClientError::Protocol("Bad image".to_string());
+        let rgba = STANDARD
+            .decode(image["rgba"].as_str().ok_or_else(bad_image)?)
+            .map_err(|_| bad_image())?;
+        Ok(Image {
+            width: image["width"].as_u64().ok_or_else(bad_image)? as usize,
+            height: image["height"].as_u64().ok_or_else(bad_image)? as usize,
+            rgba,
+        })
+    }
+
+    fn call_unit(&mut self, method: &str, params: Value) -> Result<(), ClientError> {
+        self.call(method, params)?;
+        Ok(())
+    }
+
+    fn send(&mut self, message: &str) -> Result<(), ClientError> {
+        match &mut self.connection {
+            Connection::Tcp { writer, .. } => {
+                writeln!(writer, "{}", message)?;
+                writer.flush()?;
+            }
+            Connection::WebSocket(socket) => socket.send(Message::Text(message.to_string()))?,
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Value, ClientError> {
+        let text = match &mut self.connection {
+            Connection::Tcp { reader, .. } => {
+                let mut line = String::new();
+                if reader.read_line(&mut line)? == 0 {
+                    return Err(ClientError::Protocol("Connection closed".to_string()));
+                }
+                line
+            }
+            Connection::WebSocket(socket) => loop {
+                match socket.read()? {
+                    Message::Text(text) => break text,
+                    Message::Close(_) => {
+                        return Err(ClientError::Protocol("Connection closed".to_string()))
+                    }
+                    _ => {}
+                }
+            },
+        };
+        serde_json::from_str(&text).map_err(|err| ClientError::Protocol(err.to_string()))
+    }
+}
+
+impl Automation for Client {
+    type Error = ClientError;
+
+    fn get_current_position(&mut self) -> Result<(i32, i32), Self::Error> {
+        let position = self.call("mouse.position", Value::Null)?;
+        match (position["x"].as_i64(), position["y"].as_i64()) {
+            (Some(x), Some(y)) => Ok((x as i32, y as i32)),
+            _ => Err(ClientError::Protocol(format!("Bad position {}", position))),
+        }
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), Self::Error> {
+        self.call_unit("mouse.move_to", json!({ "x": x, "y": y }))
+    }
+
+    fn move_relative(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error> {
+        self.call_unit(
+            "mouse.move_relative",
+            json!({ "dx": distance_x, "dy": distance_y }),
+        )
+    }
+
+    fn hover(&mut self, x: i32, y: i32, duration: Duration) -> Result<(), Self::Error> {
+        let duration_ms = duration.as_millis() as u64;
+        self.call_unit(
+            "mouse.hover",
+            json!({ "x": x, "y": y, "duration_ms": duration_ms }),
+        )
+    }
+
+    fn click(&mut self) -> Result<(), Self::Error> {
+        self.click_button(Button::Left)
+    }
+
+    fn double_click(&mut self) -> Result<(), Self::Error> {
+        self.call_unit("mouse.click", json!({ "button": "left", "count": 2 }))
+    }
+
+    fn right_click(&mut self) -> Result<(), Self::Error> {
+        self.click_button(Button::Right)
+    }
+
+    fn click_button(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.call_unit("mouse.click", json!({ "button": button.to_json() }))
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.call_unit("mouse.press", json!({ "button": button.to_json() }))
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.call_unit("mouse.release", json!({ "button": button.to_json() }))
+    }
+
+    fn release_all(&mut self) -> Result<(), Self::Error> {
+        self.call_unit("mouse.release_all", Value::Null)
+    }
+
+    fn scroll(&mut self, notches: f64) -> Result<(), Self::Error> {
+        self.call_unit("mouse.scroll", json!({ "amount": notches }))
+    }
+
+    fn drag(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error> {
+        self.call_unit("mouse.drag", json!({ "dx": distance_x, "dy": distance_y }))
+    }
+
+    fn drag_with_duration(
+        &mut self,
+        distance_x: i32,
+        distance_y: i32,
+        duration: Duration,
+    ) -> Result<(), Self::Error> {
+        let duration_ms = duration.as_millis() as u64;
+        self.call_unit(
+            "mouse.drag",
+            json!({ "dx": distance_x, "dy": distance_y, "duration_ms": duration_ms }),
+        )
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.call_unit("keyboard.type_text", json!({ "text": text }))
+    }
+
+    fn paste_text(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.call_unit("keyboard.paste_text", json!({ "text": text }))
+    }
+
+    fn press_combo(&mut self, combo: &str) -> Result<(), Self::Error> {
+        self.call_unit("keyboard.key", json!({ "combo": combo }))
+    }
+
+    fn press_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.call_unit("keyboard.key_down", json!({ "key": key }))
+    }
+
+    fn release_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.call_unit("keyboard.key_up", json!({ "key": key }))
+    }
+
+    fn get_screen_size(&mut self) -> Result<(i32, i32), Self::Error> {
+        let size = self.call("screen.size", Value::Null)?;
+        match (size["width"].as_i64(), size["height"].as_i64()) {
+            (Some(width), Some(height)) => Ok((width as i32, height as i32)),
+            _ => Err(ClientError::Protocol(format!("Bad screen size {}", size))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    // Serves one connection, answering each request with `reply(request)`.
+    fn fake_server<F>(reply: F) -> String
+    where
+        F: Fn(Value) -> Vec<Value> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines() {
+                let request: Value = serde_json::from_str(&line.unwrap()).unwrap();
+                for message in reply(request) {
+                    writeln!(writer, "{}", message).unwrap();
+                }
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn test_call() {
+        let addr = fake_server(|request| {
+            assert_eq!(request["method"], "mouse.position");
+            vec![json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "x": 3, "y": 4 } })]
+        });
+        let mut client = Client::connect_tcp(addr, None).unwrap();
+        assert_eq!(client.get_current_position().unwrap(), (3, 4));
+    }
+
+    #[test]
+    fn test_auth_and_errors() {
+        let addr = fake_server(|request| {
+            let response = match request["method"].as_str() {
+                Some("auth") => json!({ "id": request["id"], "result": true }),
+                _ => json!({ "id": request["id"], "error": { "code": -32000, "message": "no" } }),
+            };
+            vec![response]
+        });
+        let mut client = Client::connect_tcp(addr, Some("secret")).unwrap();
+        assert!(matches!(
+            client.move_to(1, 2),
+            Err(ClientError::Rpc { code: -32000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_events_during_call() {
+        let addr = fake_server(|request| {
+            vec![
+                json!({ "jsonrpc": "2.0", "method": "event", "params": { "type": "click" } }),
+                json!({ "jsonrpc": "2.0", "id": request["id"], "result": null }),
+            ]
+        });
+        let mut client = Client::connect_tcp(addr, None).unwrap();
+        client.click().unwrap();
+        assert_eq!(client.next_event().unwrap(), json!({ "type": "click" }));
+        assert!(client.take_events().is_empty());
+    }
+
+    // Written once for Client and Local alike.
+    fn drag_with<A: Automation>(automation: &mut A, button: Button) -> Result<(), A::Error> {
+        automation.move_to(10, 20)?;
+        automation.press(button)?;
+        automation.move_relative(5, 0)?;
+        automation.release(button)
+    }
+
+    #[test]
+    fn test_generic_over_automation() {
+        let addr = fake_server(|request| {
+            if request["method"] == "mouse.press" || request["method"] == "mouse.release" {
+                assert_eq!(request["params"]["button"], json!({ "other": 6 }));
+            }
+            vec![json!({ "jsonrpc": "2.0", "id": request["id"], "result": null })]
+        });
+        let mut client = Client::connect_tcp(addr, None).unwrap();
+        drag_with(&mut client, Button::Other(6)).unwrap();
+        assert_eq!(Button::Middle.to_json(), json!("middle"));
+    }
+
+    #[test]
+    fn test_capture() {
+        let addr = fake_server(|request| {
+            let image = json!({ "width": 1, "height": 1, "rgba": STANDARD.encode([1, 2, 3, 255]) });
+            vec![json!({ "jsonrpc": "2.0", "id": request["id"], "result": image })]
+        });
+        let mut client = Client::connect_tcp(addr, None).unwrap();
+        let image = client.capture(Some([0, 0, 1, 1])).unwrap();
+        assert_eq!(image.rgba, vec![1, 2, 3, 255]);
+    }
+}
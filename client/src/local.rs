@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use robot_rs::{
+    ButtonAction, Key, Keyboard, Mouse, MouseButton, MouseError, ScrollAmount, ScrollOptions,
+};
+
+use crate::{Automation, Button};
+
+// This machine's own mouse and keyboard, driven through the same trait as Client.
+pub struct Local {
+    pub mouse: Mouse,
+    pub keyboard: Keyboard,
+}
+
+impl Local {
+    pub fn new() -> Self {
+        Self {
+            mouse: Mouse::new(),
+            keyboard: Keyboard::new(),
+        }
+    }
+}
+
+impl Default for Local {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Button> for MouseButton {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Left => MouseButton::Left,
+            Button::Right => MouseButton::Right,
+            Button::Middle => MouseButton::Middle,
+            Button::Back => MouseButton::Back,
+            Button::Forward => MouseButton::Forward,
+            Button::Other(number) => MouseButton::Other(number),
+        }
+    }
+}
+
+impl Automation for Local {
+    type Error = MouseError;
+
+    fn get_current_position(&mut self) -> Result<(i32, i32), Self::Error> {
+        self.mouse.get_current_position()
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), Self::Error> {
+        self.mouse.move_to(x, y)
+    }
+
+    fn move_relative(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error> {
+        self.mouse.move_relative(distance_x, distance_y)
+    }
+
+    fn hover(&mut self, x: i32, y: i32, duration: Duration) -> Result<(), Self::Error> {
+        self.mouse.hover(x, y, duration)
+    }
+
+    fn click(&mut self) -> Result<(), Self::Error> {
+        self.mouse.click()
+    }
+
+    fn double_click(&mut self) -> Result<(), Self::Error> {
+        self.mouse.double_click()
+    }
+
+    fn right_click(&mut self) -> Result<(), Self::Error> {
+        self.mouse.right_click()
+    }
+
+    fn click_button(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.mouse.click_button(button.into())
+    }
+
+    fn press(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.mouse
+            .simulate_mouse_button(button.into(), ButtonAction::Press)
+    }
+
+    fn release(&mut self, button: Button) -> Result<(), Self::Error> {
+        self.mouse
+            .simulate_mouse_button(button.into(), ButtonAction::Release)
+    }
+
+    fn release_all(&mut self) -> Result<(), Self::Error> {
+        self.mouse.release_all()
+    }
+
+    fn scroll(&mut self, notches: f64) -> Result<(), Self::Error> {
+        self.mouse
+            .scroll_by(ScrollOptions::new(ScrollAmount::Notches(notches)))
+    }
+
+    fn drag(&mut self, distance_x: i32, distance_y: i32) -> Result<(), Self::Error> {
+        self.mouse.drag(distance_x, distance_y)
+    }
+
+    fn drag_with_duration(
+        &mut self,
+        distance_x: i32,
+        distance_y: i32,
+        duration: Duration,
+    ) -> Result<(), Self::Error> {
+        self.mouse
+            .drag_with_duration(distance_x, distance_y, duration)
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.keyboard.type_text(text)
+    }
+
+    fn paste_text(&mut self, text: &str) -> Result<(), Self::Error> {
+        self.keyboard.paste_text(text)
+    }
+
+    fn press_combo(&mut self, combo: &str) -> Result<(), Self::Error> {
+        self.keyboard.press_combo(&Key::parse_combo(combo)?)
+    }
+
+    fn press_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.keyboard.press_key(key.parse()?)
+    }
+
+    fn release_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.keyboard.release_key(key.parse()?)
+    }
+
+    fn get_screen_size(&mut self) -> Result<(i32, i32), Self::Error> {
+        Ok(self.mouse.get_screen_size())
+    }
+}
@@ -0,0 +1,422 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    batch::InputBatch,
+    input,
+    keyboard::Key,
+    mouse::{Mouse, MouseButton, MouseError, MousePosition},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum Action {
+    MoveTo { x: i32, y: i32 },
+    MoveBy { dx: i32, dy: i32 },
+    Press { button: MouseButton },
+    Release { button: MouseButton },
+    Click { button: MouseButton },
+    Scroll { delta: i32 },
+    KeyDown { key: Key },
+    KeyUp { key: Key },
+    TypeText { text: String },
+    Pause { ms: u64 },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Actions {
+    actions: Vec<Action>,
+}
+
+// Buttons and keys held down at some point of a chain.
+#[derive(Default)]
+struct Held {
+    buttons: Vec<MouseButton>,
+    keys: Vec<Key>,
+}
+
+impl Actions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    pub fn then(mut self, action: Action) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    pub fn move_to(self, x: i32, y: i32) -> Self {
+        self.then(Action::MoveTo { x, y })
+    }
+
+    pub fn move_by(self, dx: i32, dy: i32) -> Self {
+        self.then(Action::MoveBy { dx, dy })
+    }
+
+    pub fn press(self, button: MouseButton) -> Self {
+        self.then(Action::Press { button })
+    }
+
+    pub fn release(self, button: MouseButton) -> Self {
+        self.then(Action::Release { button })
+    }
+
+    pub fn click(self, button: MouseButton) -> Self {
+        self.then(Action::Click { button })
+    }
+
+    pub fn scroll(self, delta: i32) -> Self {
+        self.then(Action::Scroll { delta })
+    }
+
+    pub fn key_down(self, key: Key) -> Self {
+        self.then(Action::KeyDown { key })
+    }
+
+    pub fn key_up(self, key: Key) -> Self {
+        self.then(Action::KeyUp { key })
+    }
+
+    pub fn type_text(self, text: &str) -> Self {
+        self.then(Action::TypeText {
+            text: text.to_string(),
+        })
+    }
+
+    pub fn pause(self, duration: Duration) -> Self {
+        self.then(Action::Pause {
+            ms: duration.as_millis() as u64,
+        })
+    }
+
+    pub fn validate(&self, start_x: i32, start_y: i32) -> Result<(), MouseError> {
+        self.validate_on(input::screen_size(), start_x, start_y)
+    }
+
+    fn validate_on(
+        &self,
+        screen: (i32, i32),
+        start_x: i32,
+        start_y: i32,
+    ) -> Result<(), MouseError> {
+        let mut position = (start_x, start_y);
+        let mut held = Held::default();
+        for (index, action) in self.actions.iter().enumerate() {
+            let invalid = |reason: &str| MouseError::InvalidAction {
+                index,
+                reason: reason.to_string(),
+            };
+            match action {
+                Action::MoveTo { x, y } => position = check_position(*x, *y, screen, index)?,
+                Action::MoveBy { dx, dy } => {
+                    let (x, y) = offset(position, *dx, *dy, index)?;
+                    position = check_position(x, y, screen, index)?
+                }
+                Action::Press { button } => {
+                    let button = button.normalize()?;
+                    if held.buttons.contains(&button) {
+                        return Err(invalid("button is already pressed"));
+                    }
+                    held.buttons.push(button);
+                }
+                Action::Release { button } => {
+                    let button = button.normalize()?;
+                    if !held.buttons.contains(&button) {
+                        return Err(invalid("button is not pressed"));
+                    }
+                    held.buttons.retain(|&held| held != button);
+                }
+                Action::Click { button } => {
+                    button.normalize()?;
+                }
+                Action::KeyDown { key } => {
+                    key.to_virtual_key()?;
+                    if held.keys.contains(key) {
+                        return Err(invalid("key is already down"));
+                    }
+                    held.keys.push(*key);
+                }
+                Action::KeyUp { key } => {
+                    if !held.keys.contains(key) {
+                        return Err(invalid("key is not down"));
+                    }
+                    held.keys.retain(|held| held != key);
+                }
+                Action::Scroll { .. } | Action::TypeText { .. } | Action::Pause { .. } => {}
+            }
+        }
+        if !held.buttons.is_empty() || !held.keys.is_empty() {
+            return Err(MouseError::InvalidAction {
+                index: self.actions.len(),
+                reason: "chain ends with buttons or keys still held".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn perform(&self, mouse: &mut Mouse) -> Result<(), MouseError> {
+        let mut position = mouse.get_current_position()?;
+        self.validate_on(mouse.get_screen_size(), position.0, position.1)?;
+
+        // Everything between two pauses is submitted as one batch, so nothing else can
+        // interleave with it.
+        let mut held = Held::default();
+        let mut result = Ok(());
+        let mut start = 0;
+        for segment in self
+            .actions
+            .split_inclusive(|action| matches!(action, Action::Pause { .. }))
+        {
+            result = mouse.tick().and_then(|()| {
+                mouse.batch(|batch| {
+                    for (index, action) in (start..).zip(segment) {
+                        apply(batch, action, index, &mut position);
+                    }
+                })
+            });
+            if result.is_err() {
+                break;
+            }
+            start += segment.len();
+            for action in segment {
+                track(&mut held, action);
+            }
+            if let Some(Action::Pause { ms }) = segment.last() {
+                std::thread::sleep(Duration::from_millis(*ms));
+            }
+        }
+        if result.is_err() {
+            // Leave nothing stuck down from the part of the chain that did run.
+            let _ = mouse.batch(|batch| {
+                for &button in held.buttons.iter().rev() {
+                    batch.release(button);
+                }
+                for &key in held.keys.iter().rev() {
+                    batch.key_up(key);
+                }
+            });
+        }
+        result
+    }
+}
+
+fn check_position(
+    x: i32,
+    y: i32,
+    screen: (i32, i32),
+    index: usize,
+) -> Result<(i32, i32), MouseError> {
+    if x < 0 || y < 0 {
+        return Err(MouseError::InvalidAction {
+            index,
+            reason: format!("({}, {}) is negative", x, y),
+        });
+    }
+    if MousePosition::new(x, y).is_outside(screen) {
+        return Err(MouseError::InvalidAction {
+            index,
+            reason: format!("({}, {}) is off screen", x, y),
+        });
+    }
+    Ok((x, y))
+}
+
+fn offset(position: (i32, i32), dx: i32, dy: i32, index: usize) -> Result<(i32, i32), MouseError> {
+    match (position.0.checked_add(dx), position.1.checked_add(dy)) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ => Err(MouseError::InvalidAction {
+            index,
+            reason: format!("moving by ({}, {}) overflows", dx, dy),
+        }),
+    }
+}
+
+fn apply(batch: &mut InputBatch, action: &Action, index: usize, position: &mut (i32, i32)) {
+    match action {
+        Action::MoveTo { x, y } => {
+            *position = (*x, *y);
+            batch.move_to(*x, *y);
+        }
+        Action::MoveBy { dx, dy } => match offset(*position, *dx, *dy, index) {
+            Ok(target) => {
+                *position = target;
+                batch.move_to(target.0, target.1);
+            }
+            Err(err) => batch.fail(err),
+        },
+        Action::Press { button } => {
+            batch.press(*button);
+        }
+        Action::Release { button } => {
+            batch.release(*button);
+        }
+        Action::Click { button } => {
+            batch.click(*button);
+        }
+        Action::Scroll { delta } => {
+            batch.scroll(*delta);
+        }
+        Action::KeyDown { key } => {
+            batch.key_down(*key);
+        }
+        Action::KeyUp { key } => {
+            batch.key_up(*key);
+        }
+        Action::TypeText { text } => {
+            batch.text(text);
+        }
+        Action::Pause { .. } => {}
+    }
+}
+
+fn track(held: &mut Held, action: &Action) {
+    match action {
+        Action::Press { button } => held.buttons.push(*button),
+        Action::Release { button } => held.buttons.retain(|held| held != button),
+        Action::KeyDown { key } => held.keys.push(*key),
+        Action::KeyUp { key } => held.keys.retain(|held| held != key),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let actions = Actions::new()
+            .move_to(10, 10)
+            .press(MouseButton::Left)
+            .pause(Duration::from_millis(100))
+            .release(MouseButton::Left);
+        assert_eq!(
+            actions.actions(),
+            &[
+                Action::MoveTo { x: 10, y: 10 },
+                Action::Press {
+                    button: MouseButton::Left
+                },
+                Action::Pause { ms: 100 },
+                Action::Release {
+                    button: MouseButton::Left
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_unbalanced_buttons() {
+        let actions = Actions::new().release(MouseButton::Left);
+        assert!(matches!(
+            actions.validate(10, 10),
+            Err(MouseError::InvalidAction { index: 0, .. })
+        ));
+        let actions = Actions::new().press(MouseButton::Left);
+        assert!(matches!(
+            actions.validate(10, 10),
+            Err(MouseError::InvalidAction { index: 1, .. })
+        ));
+        let actions = Actions::new()
+            .press(MouseButton::Other(1))
+            .release(MouseButton::Left);
+        assert!(actions.validate(10, 10).is_ok());
+    }
+
+    #[test]
+    fn test_validate_unbalanced_keys() {
+        let actions = Actions::new().key_down(Key::Shift).key_down(Key::Shift);
+        assert!(matches!(
+            actions.validate(10, 10),
+            Err(MouseError::InvalidAction { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_bounds() {
+        let actions = Actions::new().move_to(10, 10).move_by(-20, 0);
+        assert!(matches!(
+            actions.validate(0, 0),
+            Err(MouseError::InvalidAction { index: 1, .. })
+        ));
+        let actions = Actions::new().move_by(100_000, 0);
+        assert!(actions.validate(0, 0).is_err());
+    }
+
+    #[test]
+    fn test_move_by_overflow() {
+        let actions = Actions::new().move_to(10, 10).move_by(i32::MAX, 0);
+        assert!(matches!(
+            actions.validate(0, 0),
+            Err(MouseError::InvalidAction { index: 1, .. })
+        ));
+
+        let mut batch = InputBatch::new();
+        let mut position = (10, 10);
+        apply(
+            &mut batch,
+            &Action::MoveBy {
+                dx: 0,
+                dy: i32::MAX,
+            },
+            3,
+            &mut position,
+        );
+        assert!(matches!(
+            batch.error,
+            Some(MouseError::InvalidAction { index: 3, .. })
+        ));
+        assert_eq!(position, (10, 10));
+    }
+
+    #[test]
+    fn test_perform() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        Actions::new()
+            .press(MouseButton::Left)
+            .pause(Duration::from_millis(10))
+            .move_by(50, 0)
+            .release(MouseButton::Left)
+            .key_down(Key::Shift)
+            .key_up(Key::Shift)
+            .perform(&mut mouse)
+            .unwrap();
+        assert_eq!(mouse.get_mouse_position(), (150, 100));
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_perform_rejects_invalid_chain() {
+        let mut mouse = Mouse::new();
+        let result = Actions::new().press(MouseButton::Left).perform(&mut mouse);
+        assert!(result.is_err());
+        assert!(!mouse.is_left_button_pressed());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let actions = Actions::new()
+            .move_to(1, 2)
+            .click(MouseButton::Other(6))
+            .key_down(Key::Char('a'))
+            .key_up(Key::Char('a'))
+            .pause(Duration::from_millis(5));
+        let json = serde_json::to_string(&actions).unwrap();
+        assert!(json.starts_with(r#"[{"type":"move_to","x":1,"y":2}"#));
+        let parsed: Actions = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, actions);
+    }
+}
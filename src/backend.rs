@@ -0,0 +1,127 @@
+#[cfg(feature = "uinput")]
+use crate::uinput::Uinput;
+#[cfg(feature = "vnc")]
+use crate::vnc::Vnc;
+#[cfg(feature = "x11")]
+use crate::x11::X11Input;
+use crate::{
+    input,
+    keyboard::Key,
+    mock::MockInput,
+    mouse::{ButtonAction, MouseButton, MouseError},
+    scroll::ScrollAxis,
+};
+
+// Input as InputBatch records it, before a backend turns it into its own events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InputEvent {
+    MoveTo {
+        x: i32,
+        y: i32,
+    },
+    Button {
+        button: MouseButton,
+        action: ButtonAction,
+    },
+    Scroll {
+        axis: ScrollAxis,
+        amount: i32,
+    },
+    Key {
+        key: Key,
+        action: ButtonAction,
+    },
+    // A typed character rather than a key, so it does not depend on the keyboard layout.
+    Char {
+        c: char,
+        action: ButtonAction,
+    },
+}
+
+// Where Mouse and Keyboard send their input: the local desktop, a remote framebuffer,
+// X server or Linux input device, or a desktop kept in memory for tests.
+#[derive(Clone)]
+pub(crate) enum Backend {
+    Windows,
+    #[cfg(feature = "vnc")]
+    Vnc(Vnc),
+    #[cfg(feature = "x11")]
+    X11(X11Input),
+    #[cfg(feature = "uinput")]
+    Uinput(Uinput),
+    Mock(MockInput),
+}
+
+impl Backend {
+    // Sends all of the events or none of them.
+    pub(crate) fn send(&self, events: &[InputEvent]) -> Result<(), MouseError> {
+        match self {
+            Backend::Windows => input::send_events(events),
+            #[cfg(feature = "vnc")]
+            Backend::Vnc(vnc) => Ok(vnc.send(events)?),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.send(events),
+            #[cfg(feature = "uinput")]
+            Backend::Uinput(uinput) => uinput.send(events),
+            Backend::Mock(mock) => mock.send(events),
+        }
+    }
+
+    pub(crate) fn cursor_pos(&self) -> Result<(i32, i32), MouseError> {
+        match self {
+            Backend::Windows => input::cursor_pos(),
+            // RFB never reports the remote cursor, so this is where we last moved it.
+            #[cfg(feature = "vnc")]
+            Backend::Vnc(vnc) => Ok(vnc.position()),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.position(),
+            #[cfg(feature = "uinput")]
+            Backend::Uinput(uinput) => Ok(uinput.position()),
+            Backend::Mock(mock) => Ok(mock.position()),
+        }
+    }
+
+    pub(crate) fn set_cursor_pos(&self, x: i32, y: i32) -> Result<(), MouseError> {
+        match self {
+            Backend::Windows => input::set_cursor_pos(x, y),
+            #[cfg(feature = "vnc")]
+            Backend::Vnc(_) => self.send(&[InputEvent::MoveTo { x, y }]),
+            #[cfg(feature = "x11")]
+            Backend::X11(_) => self.send(&[InputEvent::MoveTo { x, y }]),
+            #[cfg(feature = "uinput")]
+            Backend::Uinput(_) => self.send(&[InputEvent::MoveTo { x, y }]),
+            Backend::Mock(_) => self.send(&[InputEvent::MoveTo { x, y }]),
+        }
+    }
+
+    pub(crate) fn screen_size(&self) -> (i32, i32) {
+        match self {
+            Backend::Windows => input::screen_size(),
+            #[cfg(feature = "vnc")]
+            Backend::Vnc(vnc) => vnc.get_screen_size(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.get_screen_size(),
+            #[cfg(feature = "uinput")]
+            Backend::Uinput(uinput) => uinput.get_screen_size(),
+            Backend::Mock(mock) => mock.screen_size(),
+        }
+    }
+
+    pub(crate) fn button_state(&self, button: MouseButton) -> bool {
+        match self {
+            Backend::Windows => button.virtual_key().is_ok_and(input::is_key_down),
+            #[cfg(feature = "vnc")]
+            Backend::Vnc(vnc) => vnc.is_pressed(button),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.is_pressed(button),
+            #[cfg(feature = "uinput")]
+            Backend::Uinput(uinput) => uinput.is_pressed(button),
+            Backend::Mock(mock) => mock.is_pressed(button),
+        }
+    }
+
+    // The low-level hooks only see the local mouse and keyboard.
+    pub(crate) fn is_local(&self) -> bool {
+        matches!(self, Backend::Windows)
+    }
+}
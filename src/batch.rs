@@ -0,0 +1,284 @@
+use crate::{
+    backend::InputEvent,
+    input,
+    keyboard::Key,
+    mouse::{ButtonAction, MouseButton, MouseError, MousePosition},
+    scroll::ScrollAxis,
+};
+
+pub struct InputBatch {
+    pub(crate) events: Vec<InputEvent>,
+    pub(crate) buttons: Vec<(MouseButton, ButtonAction)>,
+    pub(crate) position: Option<MousePosition>,
+    pub(crate) clicks: usize,
+    pub(crate) error: Option<MouseError>,
+    pub(crate) releases_only: bool,
+    // The size of the screen the batch is for, to check moves against.
+    screen: (i32, i32),
+}
+
+impl InputBatch {
+    pub(crate) fn new() -> Self {
+        Self::for_screen(input::screen_size())
+    }
+
+    pub(crate) fn for_screen(screen: (i32, i32)) -> Self {
+        Self {
+            events: Vec::new(),
+            buttons: Vec::new(),
+            position: None,
+            clicks: 0,
+            error: None,
+            releases_only: true,
+            screen,
+        }
+    }
+
+    // Characters outside the Basic Multilingual Plane count twice, as Windows types them
+    // as a surrogate pair.
+    pub fn len(&self) -> usize {
+        self.events
+            .iter()
+            .map(|event| match event {
+                InputEvent::Char { c, .. }
+                | InputEvent::Key {
+                    key: Key::Unicode(c),
+                    ..
+                } => c.len_utf16(),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn move_to(&mut self, x: i32, y: i32) -> &mut Self {
+        let position = MousePosition::new(x, y);
+        if x < 0 || y < 0 {
+            self.fail(MouseError::InvalidInput);
+        } else if position.is_outside(self.screen) {
+            self.fail(MouseError::OutOfBounds);
+        } else {
+            self.events.push(InputEvent::MoveTo { x, y });
+            self.position = Some(position);
+            self.releases_only = false;
+        }
+        self
+    }
+
+    pub fn press(&mut self, button: MouseButton) -> &mut Self {
+        self.button(button, ButtonAction::Press)
+    }
+
+    pub fn release(&mut self, button: MouseButton) -> &mut Self {
+        self.button(button, ButtonAction::Release)
+    }
+
+    pub fn click(&mut self, button: MouseButton) -> &mut Self {
+        self.press(button).release(button);
+        self.clicks += 1;
+        self
+    }
+
+    pub fn scroll(&mut self, amount: i32) -> &mut Self {
+        self.events.push(InputEvent::Scroll {
+            axis: ScrollAxis::Vertical,
+            amount,
+        });
+        self.releases_only = false;
+        self
+    }
+
+    pub fn scroll_horizontal(&mut self, distance: i32) -> &mut Self {
+        self.events.push(InputEvent::Scroll {
+            axis: ScrollAxis::Horizontal,
+            amount: distance,
+        });
+        self.releases_only = false;
+        self
+    }
+
+    pub fn key_down(&mut self, key: Key) -> &mut Self {
+        self.key(key, ButtonAction::Press)
+    }
+
+    pub fn key_up(&mut self, key: Key) -> &mut Self {
+        self.key(key, ButtonAction::Release)
+    }
+
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            match c {
+                // Applications expect real Enter and Tab keys rather than the characters.
+                '\n' => {
+                    self.key_down(Key::Enter).key_up(Key::Enter);
+                }
+                '\t' => {
+                    self.key_down(Key::Tab).key_up(Key::Tab);
+                }
+                '\r' => {}
+                c => {
+                    self.events.push(InputEvent::Char {
+                        c,
+                        action: ButtonAction::Press,
+                    });
+                    self.events.push(InputEvent::Char {
+                        c,
+                        action: ButtonAction::Release,
+                    });
+                    self.releases_only = false;
+                }
+            }
+        }
+        self
+    }
+
+    fn button(&mut self, button: MouseButton, action: ButtonAction) -> &mut Self {
+        // Buttons past the fifth are for the backend to send or refuse.
+        let button = button.normalize().unwrap_or(button);
+        if action == ButtonAction::Press {
+            self.releases_only = false;
+        }
+        self.events.push(InputEvent::Button { button, action });
+        self.buttons.push((button, action));
+        self
+    }
+
+    fn key(&mut self, key: Key, action: ButtonAction) -> &mut Self {
+        let valid = match key {
+            Key::Unicode(_) => Ok(()),
+            key => key.to_virtual_key().map(drop),
+        };
+        match valid {
+            Ok(()) => {
+                if action == ButtonAction::Press {
+                    self.releases_only = false;
+                }
+                self.events.push(InputEvent::Key { key, action });
+            }
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    // Only the first error is kept; nothing in a failed batch is ever sent.
+    pub(crate) fn fail(&mut self, err: MouseError) {
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_adds_press_and_release() {
+        let mut batch = InputBatch::new();
+        batch.click(MouseButton::Left);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch.buttons,
+            vec![
+                (MouseButton::Left, ButtonAction::Press),
+                (MouseButton::Left, ButtonAction::Release)
+            ]
+        );
+        assert_eq!(batch.clicks, 1);
+        assert!(!batch.releases_only);
+    }
+
+    #[test]
+    fn test_releases_only() {
+        let mut batch = InputBatch::new();
+        batch.release(MouseButton::Left).key_up(Key::Control);
+        assert!(batch.releases_only);
+        batch.scroll(-120);
+        assert!(!batch.releases_only);
+    }
+
+    #[test]
+    fn test_chaining() {
+        let mut batch = InputBatch::new();
+        batch
+            .move_to(100, 100)
+            .press(MouseButton::Right)
+            .move_to(120, 130)
+            .release(MouseButton::Right)
+            .scroll(120);
+        assert_eq!(batch.len(), 5);
+        let position = batch.position.as_ref().unwrap();
+        assert_eq!((position.x, position.y), (120, 130));
+        assert!(batch.error.is_none());
+    }
+
+    #[test]
+    fn test_invalid_move_records_error() {
+        let mut batch = InputBatch::new();
+        batch.move_to(-1, 10).click(MouseButton::Left);
+        assert!(matches!(batch.error, Some(MouseError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_out_of_bounds_move_records_error() {
+        let mut batch = InputBatch::new();
+        batch.move_to(100_000, 100_000);
+        assert!(matches!(batch.error, Some(MouseError::OutOfBounds)));
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_moves_are_checked_against_the_batch_screen() {
+        let mut batch = InputBatch::for_screen((4, 2));
+        batch.move_to(4, 2);
+        assert!(batch.error.is_none());
+        assert_eq!(batch.events, vec![InputEvent::MoveTo { x: 4, y: 2 }]);
+        batch.move_to(5, 1);
+        assert!(matches!(batch.error, Some(MouseError::OutOfBounds)));
+    }
+
+    #[test]
+    fn test_later_buttons_are_left_to_the_backend() {
+        let mut batch = InputBatch::new();
+        batch.click(MouseButton::Other(8));
+        assert!(batch.error.is_none());
+        assert_eq!(batch.len(), 2);
+        assert!(matches!(
+            input::send_events(&batch.events),
+            Err(MouseError::UnsupportedButton(8))
+        ));
+    }
+
+    #[test]
+    fn test_numbered_button_is_normalized() {
+        let mut batch = InputBatch::new();
+        batch.press(MouseButton::Other(4));
+        assert_eq!(
+            batch.buttons,
+            vec![(MouseButton::Back, ButtonAction::Press)]
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let mut batch = InputBatch::new();
+        batch.text("a\r\n😀");
+        // a down/up, Enter down/up, and a surrogate pair down/up for each half.
+        assert_eq!(batch.len(), 8);
+        assert!(!batch.releases_only);
+        assert!(batch.error.is_none());
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut batch = InputBatch::new();
+        batch.key_down(Key::Shift).key_up(Key::Shift);
+        assert_eq!(batch.len(), 2);
+
+        batch.key_down(Key::F(0));
+        assert!(matches!(batch.error, Some(MouseError::InvalidInput)));
+    }
+}
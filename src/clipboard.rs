@@ -0,0 +1,693 @@
+use std::{
+    error::Error,
+    fmt, io, mem,
+    path::{Path, PathBuf},
+    ptr,
+    time::Duration,
+};
+use winapi::{
+    shared::minwindef::{BOOL, DWORD, HGLOBAL},
+    shared::windef::POINT,
+    um::{
+        shellapi::{DragQueryFileW, HDROP},
+        winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+        wingdi::{BITMAPINFOHEADER, BI_BITFIELDS, BI_RGB},
+        winuser::{
+            CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData,
+            IsClipboardFormatAvailable, OpenClipboard, SetClipboardData, CF_BITMAP, CF_DIB,
+            CF_DSPBITMAP, CF_DSPENHMETAFILE, CF_DSPMETAFILEPICT, CF_ENHMETAFILE, CF_GDIOBJLAST,
+            CF_HDROP, CF_METAFILEPICT, CF_OWNERDISPLAY, CF_PALETTE, CF_PRIVATEFIRST,
+            CF_UNICODETEXT,
+        },
+    },
+};
+
+use crate::wide::from_wide;
+#[cfg(feature = "x11")]
+use crate::x11::{Selection, X11Clipboard};
+
+#[derive(Debug)]
+pub enum ClipboardError {
+    Unavailable,
+    FormatUnavailable,
+    InvalidData(String),
+    IoError(io::Error),
+}
+
+impl Error for ClipboardError {}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardError::Unavailable => write!(f, "Clipboard is in use by another process"),
+            ClipboardError::FormatUnavailable => {
+                write!(f, "Clipboard does not contain the requested format")
+            }
+            ClipboardError::InvalidData(msg) => write!(f, "Invalid clipboard data: {}", msg),
+            ClipboardError::IoError(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for ClipboardError {
+    fn from(err: io::Error) -> Self {
+        ClipboardError::IoError(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+impl ClipboardImage {
+    pub fn to_bmp(&self) -> Result<Vec<u8>, ClipboardError> {
+        // A BMP file is a CF_DIB payload behind a 14-byte BITMAPFILEHEADER.
+        const FILE_HEADER_SIZE: usize = 14;
+        let dib = encode_dib(self)?;
+        let pixel_offset = FILE_HEADER_SIZE + mem::size_of::<BITMAPINFOHEADER>();
+        let mut bytes = Vec::with_capacity(FILE_HEADER_SIZE + dib.len());
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&((FILE_HEADER_SIZE + dib.len()) as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&(pixel_offset as u32).to_le_bytes());
+        bytes.extend_from_slice(&dib);
+        Ok(bytes)
+    }
+
+    pub fn save_bmp<P: AsRef<Path>>(&self, path: P) -> Result<(), ClipboardError> {
+        std::fs::write(path, self.to_bmp()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardContents {
+    Empty,
+    Text(String),
+    Image(ClipboardImage),
+    Files(Vec<PathBuf>),
+    // Data the crate cannot decode, kept as is so that restoring puts it back unchanged.
+    // `format` is the backend's own identifier: a Windows clipboard format or an X11 atom.
+    Raw { format: u32, bytes: Vec<u8> },
+    // Every format the clipboard offered, in the order it offered them, as raw bytes.
+    Formats(Vec<(u32, Vec<u8>)>),
+}
+
+// Layout of the DROPFILES header that precedes the file list in CF_HDROP data.
+#[repr(C)]
+struct DropFiles {
+    p_files: DWORD,
+    pt: POINT,
+    f_nc: BOOL,
+    f_wide: BOOL,
+}
+
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open() -> Result<Self, ClipboardError> {
+        // Another process may briefly hold the clipboard, e.g. a clipboard manager
+        // reacting to our own previous write.
+        for _ in 0..10 {
+            if unsafe { OpenClipboard(ptr::null_mut()) } != 0 {
+                return Ok(Self);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Err(ClipboardError::Unavailable)
+    }
+
+    fn read(&self, format: u32) -> Result<Vec<u8>, ClipboardError> {
+        if unsafe { IsClipboardFormatAvailable(format) } == 0 {
+            return Err(ClipboardError::FormatUnavailable);
+        }
+        let handle = unsafe { GetClipboardData(format) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error().into());
+        }
+        unsafe {
+            let size = GlobalSize(handle);
+            let data = GlobalLock(handle) as *const u8;
+            if data.is_null() {
+                return Err(io::Error::last_os_error().into());
+            }
+            let bytes = std::slice::from_raw_parts(data, size).to_vec();
+            GlobalUnlock(handle);
+            Ok(bytes)
+        }
+    }
+
+    fn write(&self, format: u32, bytes: &[u8]) -> Result<(), ClipboardError> {
+        unsafe {
+            let handle: HGLOBAL = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+            if handle.is_null() {
+                return Err(io::Error::last_os_error().into());
+            }
+            let data = GlobalLock(handle) as *mut u8;
+            if data.is_null() {
+                GlobalFree(handle);
+                return Err(io::Error::last_os_error().into());
+            }
+            ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            GlobalUnlock(handle);
+            // On success the system owns the memory; on failure it is still ours to free.
+            if SetClipboardData(format, handle).is_null() {
+                let err = io::Error::last_os_error();
+                GlobalFree(handle);
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), ClipboardError> {
+        if unsafe { EmptyClipboard() } == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe { CloseClipboard() };
+    }
+}
+
+#[derive(Clone)]
+pub struct Clipboard {
+    backend: Backend,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Windows,
+    #[cfg(feature = "x11")]
+    X11(X11Clipboard),
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self {
+            backend: Backend::Windows,
+        }
+    }
+
+    // The selection of the X server named by $DISPLAY, e.g. one run by WSLg or VcXsrv.
+    #[cfg(feature = "x11")]
+    pub fn x11(selection: Selection) -> Result<Self, ClipboardError> {
+        let display = std::env::var("DISPLAY")
+            .map_err(|_| ClipboardError::InvalidData("DISPLAY is not set".to_string()))?;
+        Self::x11_display(&display, selection)
+    }
+
+    #[cfg(feature = "x11")]
+    pub fn x11_display(display: &str, selection: Selection) -> Result<Self, ClipboardError> {
+        Ok(Self {
+            backend: Backend::X11(X11Clipboard::new(display, selection)?),
+        })
+    }
+
+    pub fn get_text(&self) -> Result<String, ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.get_text(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.get_text(),
+        }
+    }
+
+    pub fn set_text(&self, text: &str) -> Result<(), ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.set_text(text),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.set_text(text),
+        }
+    }
+
+    pub fn get_image(&self) -> Result<ClipboardImage, ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.get_image(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.get_image(),
+        }
+    }
+
+    pub fn set_image(&self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.set_image(image),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.set_image(image),
+        }
+    }
+
+    pub fn get_files(&self) -> Result<Vec<PathBuf>, ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.get_files(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.get_files(),
+        }
+    }
+
+    pub fn set_files<P: AsRef<Path>>(&self, files: &[P]) -> Result<(), ClipboardError> {
+        let files: Vec<&Path> = files.iter().map(AsRef::as_ref).collect();
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.set_files(&files),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.set_files(&files),
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.clear(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.clear(),
+        }
+    }
+
+    pub fn save(&self) -> Result<ClipboardContents, ClipboardError> {
+        match &self.backend {
+            Backend::Windows => WindowsClipboard.save(),
+            #[cfg(feature = "x11")]
+            Backend::X11(x11) => x11.save(),
+        }
+    }
+
+    pub fn restore(&self, contents: &ClipboardContents) -> Result<(), ClipboardError> {
+        match contents {
+            ClipboardContents::Empty => self.clear(),
+            ClipboardContents::Text(text) => self.set_text(text),
+            ClipboardContents::Image(image) => self.set_image(image),
+            ClipboardContents::Files(files) => self.set_files(files),
+            ClipboardContents::Raw { format, bytes } => match &self.backend {
+                Backend::Windows => WindowsClipboard.set_raw(*format, bytes),
+                #[cfg(feature = "x11")]
+                Backend::X11(x11) => x11.set_raw(*format, bytes),
+            },
+            ClipboardContents::Formats(formats) if formats.is_empty() => self.clear(),
+            ClipboardContents::Formats(formats) => match &self.backend {
+                Backend::Windows => WindowsClipboard.set_formats(formats),
+                #[cfg(feature = "x11")]
+                Backend::X11(x11) => x11.set_formats(formats),
+            },
+        }
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct WindowsClipboard;
+
+impl WindowsClipboard {
+    fn get_text(&self) -> Result<String, ClipboardError> {
+        let bytes = OpenedClipboard::open()?.read(CF_UNICODETEXT)?;
+        let wide: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+            .collect();
+        Ok(from_wide(&wide))
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), ClipboardError> {
+        let bytes = encode_wide_bytes(text);
+        let clipboard = OpenedClipboard::open()?;
+        clipboard.clear()?;
+        clipboard.write(CF_UNICODETEXT, &bytes)
+    }
+
+    fn get_image(&self) -> Result<ClipboardImage, ClipboardError> {
+        let bytes = OpenedClipboard::open()?.read(CF_DIB)?;
+        decode_dib(&bytes)
+    }
+
+    fn set_image(&self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        let bytes = encode_dib(image)?;
+        let clipboard = OpenedClipboard::open()?;
+        clipboard.clear()?;
+        clipboard.write(CF_DIB, &bytes)
+    }
+
+    fn get_files(&self) -> Result<Vec<PathBuf>, ClipboardError> {
+        let clipboard = OpenedClipboard::open()?;
+        if unsafe { IsClipboardFormatAvailable(CF_HDROP) } == 0 {
+            return Err(ClipboardError::FormatUnavailable);
+        }
+        let hdrop = unsafe { GetClipboardData(CF_HDROP) } as HDROP;
+        if hdrop.is_null() {
+            return Err(io::Error::last_os_error().into());
+        }
+        let count = unsafe { DragQueryFileW(hdrop, u32::MAX, ptr::null_mut(), 0) };
+        let mut files = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let len = unsafe { DragQueryFileW(hdrop, index, ptr::null_mut(), 0) };
+            let mut buffer = vec![0u16; len as usize + 1];
+            unsafe { DragQueryFileW(hdrop, index, buffer.as_mut_ptr(), buffer.len() as u32) };
+            files.push(PathBuf::from(from_wide(&buffer)));
+        }
+        drop(clipboard);
+        Ok(files)
+    }
+
+    fn set_files(&self, files: &[&Path]) -> Result<(), ClipboardError> {
+        let mut bytes = Vec::new();
+        let header = DropFiles {
+            p_files: mem::size_of::<DropFiles>() as DWORD,
+            pt: POINT { x: 0, y: 0 },
+            f_nc: 0,
+            f_wide: 1,
+        };
+        bytes.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(
+                &header as *const DropFiles as *const u8,
+                mem::size_of::<DropFiles>(),
+            )
+        });
+        for file in files {
+            let path = file.to_str().ok_or_else(|| {
+                ClipboardError::InvalidData("File path is not valid UTF-8".to_string())
+            })?;
+            bytes.extend(encode_wide_bytes(path));
+        }
+        // The list is terminated by an additional empty string.
+        bytes.extend_from_slice(&[0, 0]);
+
+        let clipboard = OpenedClipboard::open()?;
+        clipboard.clear()?;
+        clipboard.write(CF_HDROP, &bytes)
+    }
+
+    fn set_raw(&self, format: u32, bytes: &[u8]) -> Result<(), ClipboardError> {
+        let clipboard = OpenedClipboard::open()?;
+        clipboard.clear()?;
+        clipboard.write(format, bytes)
+    }
+
+    fn clear(&self) -> Result<(), ClipboardError> {
+        OpenedClipboard::open()?.clear()
+    }
+
+    fn set_formats(&self, formats: &[(u32, Vec<u8>)]) -> Result<(), ClipboardError> {
+        let clipboard = OpenedClipboard::open()?;
+        clipboard.clear()?;
+        for (format, bytes) in formats {
+            clipboard.write(*format, bytes)?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<ClipboardContents, ClipboardError> {
+        let clipboard = OpenedClipboard::open()?;
+        let mut formats = Vec::new();
+        let mut format = 0;
+        loop {
+            format = unsafe { EnumClipboardFormats(format) };
+            if format == 0 {
+                break;
+            }
+            if !is_global_memory(format) {
+                continue;
+            }
+            // An owner that renders on demand may decline to; there is nothing to keep then.
+            if let Ok(bytes) = clipboard.read(format) {
+                formats.push((format, bytes));
+            }
+        }
+        Ok(if formats.is_empty() {
+            ClipboardContents::Empty
+        } else {
+            ClipboardContents::Formats(formats)
+        })
+    }
+}
+
+// Whether data in this format is an HGLOBAL that can be copied byte for byte. The others are
+// GDI handles, hold them as metafile pictures do, or are private to the owner, and would be
+// meaningless once copied.
+// CF_BITMAP is synthesized again from the CF_DIB that is kept.
+fn is_global_memory(format: u32) -> bool {
+    !matches!(
+        format,
+        CF_BITMAP
+            | CF_METAFILEPICT
+            | CF_PALETTE
+            | CF_ENHMETAFILE
+            | CF_OWNERDISPLAY
+            | CF_DSPBITMAP
+            | CF_DSPMETAFILEPICT
+            | CF_DSPENHMETAFILE
+    ) && !(CF_PRIVATEFIRST..=CF_GDIOBJLAST).contains(&format)
+}
+
+fn encode_wide_bytes(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(Some(0))
+        .flat_map(|c| c.to_ne_bytes())
+        .collect()
+}
+
+pub(crate) fn decode_dib(bytes: &[u8]) -> Result<ClipboardImage, ClipboardError> {
+    let header_size = mem::size_of::<BITMAPINFOHEADER>();
+    if bytes.len() < header_size {
+        return Err(ClipboardError::InvalidData(
+            "DIB header is truncated".to_string(),
+        ));
+    }
+    let header: BITMAPINFOHEADER = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const _) };
+    let bytes_per_pixel = match (header.biBitCount, header.biCompression) {
+        (32, BI_RGB) | (32, BI_BITFIELDS) => 4,
+        (24, BI_RGB) => 3,
+        (bits, compression) => {
+            return Err(ClipboardError::InvalidData(format!(
+                "Unsupported DIB format: {} bpp, compression {}",
+                bits, compression
+            )))
+        }
+    };
+    let width = header.biWidth.unsigned_abs() as usize;
+    let height = header.biHeight.unsigned_abs() as usize;
+    let bottom_up = header.biHeight > 0;
+    // BI_BITFIELDS stores three DWORD colour masks after the header.
+    let mut offset = header.biSize as usize;
+    if header.biCompression == BI_BITFIELDS && header.biSize as usize == header_size {
+        offset += 12;
+    }
+    let stride = (width * bytes_per_pixel + 3) & !3;
+    if bytes.len() < offset + stride * height {
+        return Err(ClipboardError::InvalidData(
+            "DIB pixel data is truncated".to_string(),
+        ));
+    }
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        let source_row = if bottom_up { height - 1 - row } else { row };
+        let start = offset + source_row * stride;
+        for pixel in bytes[start..start + width * bytes_per_pixel].chunks_exact(bytes_per_pixel) {
+            let alpha = if bytes_per_pixel == 4 { pixel[3] } else { 255 };
+            rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], alpha]);
+        }
+    }
+    Ok(ClipboardImage {
+        width,
+        height,
+        rgba,
+    })
+}
+
+fn encode_dib(image: &ClipboardImage) -> Result<Vec<u8>, ClipboardError> {
+    if image.rgba.len() != image.width * image.height * 4 {
+        return Err(ClipboardError::InvalidData(
+            "Image buffer does not match its dimensions".to_string(),
+        ));
+    }
+    let mut header: BITMAPINFOHEADER = unsafe { mem::zeroed() };
+    header.biSize = mem::size_of::<BITMAPINFOHEADER>() as DWORD;
+    header.biWidth = image.width as i32;
+    // A negative height marks the rows as top-down, matching our RGBA buffer.
+    header.biHeight = -(image.height as i32);
+    header.biPlanes = 1;
+    header.biBitCount = 32;
+    header.biCompression = BI_RGB;
+    header.biSizeImage = image.rgba.len() as DWORD;
+
+    let mut bytes = Vec::with_capacity(header.biSize as usize + image.rgba.len());
+    bytes.extend_from_slice(unsafe {
+        std::slice::from_raw_parts(
+            &header as *const BITMAPINFOHEADER as *const u8,
+            mem::size_of::<BITMAPINFOHEADER>(),
+        )
+    });
+    for pixel in image.rgba.chunks_exact(4) {
+        bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+    }
+    Ok(bytes)
+}
+
+// The clipboard is shared by the whole session, so tests touching it must not interleave.
+#[cfg(test)]
+pub(crate) static CLIPBOARD_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::um::winuser::RegisterClipboardFormatW;
+
+    fn sample_image() -> ClipboardImage {
+        ClipboardImage {
+            width: 2,
+            height: 2,
+            rgba: vec![
+                255, 0, 0, 255, 0, 255, 0, 255, //
+                0, 0, 255, 255, 255, 255, 255, 128,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_dib_round_trip() {
+        let image = sample_image();
+        let bytes = encode_dib(&image).unwrap();
+        assert_eq!(decode_dib(&bytes).unwrap(), image);
+    }
+
+    #[test]
+    fn test_encode_dib_size_mismatch() {
+        let mut image = sample_image();
+        image.rgba.pop();
+        assert!(matches!(
+            encode_dib(&image),
+            Err(ClipboardError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_bmp() {
+        let image = sample_image();
+        let bytes = image.to_bmp().unwrap();
+        assert_eq!(&bytes[..2], b"BM");
+        assert_eq!(
+            u32::from_le_bytes(bytes[2..6].try_into().unwrap()) as usize,
+            bytes.len()
+        );
+        assert_eq!(decode_dib(&bytes[14..]).unwrap(), image);
+    }
+
+    #[test]
+    fn test_decode_dib_truncated() {
+        assert!(decode_dib(&[0u8; 8]).is_err());
+    }
+
+    #[test]
+    fn test_set_and_get_text() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        clipboard.set_text("robot_rs clipboard — ✓").unwrap();
+        assert_eq!(clipboard.get_text().unwrap(), "robot_rs clipboard — ✓");
+    }
+
+    #[test]
+    fn test_set_and_get_image() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        clipboard.set_image(&sample_image()).unwrap();
+        assert_eq!(clipboard.get_image().unwrap(), sample_image());
+    }
+
+    #[test]
+    fn test_set_and_get_files() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        let files = vec![PathBuf::from(r"C:\Windows\notepad.exe")];
+        clipboard.set_files(&files).unwrap();
+        assert_eq!(clipboard.get_files().unwrap(), files);
+    }
+
+    #[test]
+    fn test_clear() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        clipboard.set_text("to be cleared").unwrap();
+        clipboard.clear().unwrap();
+        assert!(matches!(
+            clipboard.get_text(),
+            Err(ClipboardError::FormatUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_undecodable_image_is_restored_unchanged() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        // A 16 bpp DIB, which decode_dib does not understand.
+        let mut header: BITMAPINFOHEADER = unsafe { mem::zeroed() };
+        header.biSize = mem::size_of::<BITMAPINFOHEADER>() as DWORD;
+        header.biWidth = 2;
+        header.biHeight = 1;
+        header.biPlanes = 1;
+        header.biBitCount = 16;
+        header.biCompression = BI_RGB;
+        let mut dib = unsafe {
+            std::slice::from_raw_parts(
+                &header as *const BITMAPINFOHEADER as *const u8,
+                mem::size_of::<BITMAPINFOHEADER>(),
+            )
+        }
+        .to_vec();
+        dib.extend_from_slice(&[0x1f, 0x00, 0xe0, 0x03]);
+        WindowsClipboard.set_raw(CF_DIB, &dib).unwrap();
+
+        let saved = clipboard.save().unwrap();
+        assert!(matches!(
+            &saved,
+            ClipboardContents::Formats(formats) if formats.iter().any(|(format, _)| *format == CF_DIB)
+        ));
+        clipboard.set_text("temporary").unwrap();
+        clipboard.restore(&saved).unwrap();
+        let restored = OpenedClipboard::open().unwrap().read(CF_DIB).unwrap();
+        assert_eq!(&restored[..dib.len()], &dib[..]);
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        clipboard.set_text("original").unwrap();
+        let saved = clipboard.save().unwrap();
+
+        clipboard.set_text("temporary").unwrap();
+        clipboard.restore(&saved).unwrap();
+        assert_eq!(clipboard.get_text().unwrap(), "original");
+    }
+
+    #[test]
+    fn test_save_and_restore_keeps_every_format() {
+        let _lock = CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        let html_name: Vec<u16> = "HTML Format".encode_utf16().chain(Some(0)).collect();
+        let html = unsafe { RegisterClipboardFormatW(html_name.as_ptr()) };
+        let fragment = b"Version:0.9\r\n<b>original</b>\0".to_vec();
+        {
+            let opened = OpenedClipboard::open().unwrap();
+            opened.clear().unwrap();
+            opened
+                .write(CF_UNICODETEXT, &encode_wide_bytes("original"))
+                .unwrap();
+            opened.write(html, &fragment).unwrap();
+        }
+
+        let saved = clipboard.save().unwrap();
+        clipboard.set_text("temporary").unwrap();
+        clipboard.restore(&saved).unwrap();
+
+        assert_eq!(clipboard.get_text().unwrap(), "original");
+        let restored = OpenedClipboard::open().unwrap().read(html).unwrap();
+        assert_eq!(&restored[..fragment.len()], &fragment[..]);
+    }
+}
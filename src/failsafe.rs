@@ -0,0 +1,235 @@
+use std::{
+    mem,
+    sync::{Mutex, PoisonError},
+};
+use winapi::um::winuser::{
+    GetAsyncKeyState, GetSystemMetrics, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, SM_CXSCREEN,
+    SM_CYSCREEN, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+};
+
+use crate::{
+    input::{self, keyboard_input, mouse_input, unicode_input},
+    keyboard::Key,
+    mouse::{button_flags, ButtonAction, MouseButton, MouseError},
+};
+
+static FAILSAFE: Mutex<Option<Failsafe>> = Mutex::new(None);
+
+// Keys pressed through a local Keyboard and not yet released, in the order they went down.
+static HELD_KEYS: Mutex<Vec<Key>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failsafe {
+    pub corners: Vec<Corner>,
+    pub corner_margin: i32,
+    pub hotkey: Option<Key>,
+}
+
+impl Failsafe {
+    pub fn new() -> Self {
+        Self {
+            corners: vec![
+                Corner::TopLeft,
+                Corner::TopRight,
+                Corner::BottomLeft,
+                Corner::BottomRight,
+            ],
+            corner_margin: 0,
+            hotkey: None,
+        }
+    }
+
+    pub fn enable(self) {
+        *FAILSAFE.lock().unwrap_or_else(PoisonError::into_inner) = Some(self);
+    }
+
+    pub fn disable() {
+        *FAILSAFE.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    pub fn is_enabled() -> bool {
+        FAILSAFE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        let (width, height) =
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        let in_corner = match input::cursor_pos() {
+            Ok((x, y)) => self.is_in_corner(x, y, width, height),
+            Err(_) => false,
+        };
+        in_corner || self.is_hotkey_down()
+    }
+
+    fn is_in_corner(&self, x: i32, y: i32, screen_width: i32, screen_height: i32) -> bool {
+        let margin = self.corner_margin.max(0);
+        let left = x <= margin;
+        let top = y <= margin;
+        let right = x >= screen_width - 1 - margin;
+        let bottom = y >= screen_height - 1 - margin;
+        self.corners.iter().any(|corner| match corner {
+            Corner::TopLeft => top && left,
+            Corner::TopRight => top && right,
+            Corner::BottomLeft => bottom && left,
+            Corner::BottomRight => bottom && right,
+        })
+    }
+
+    fn is_hotkey_down(&self) -> bool {
+        match self.hotkey.map(Key::to_virtual_key) {
+            Some(Ok(code)) => unsafe { GetAsyncKeyState(code as i32) as u16 & 0x8000 != 0 },
+            _ => false,
+        }
+    }
+}
+
+impl Default for Failsafe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn check() -> Result<(), MouseError> {
+    let triggered = FAILSAFE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .is_some_and(Failsafe::is_triggered);
+    abort_if(triggered)
+}
+
+// Split from check so that tests can trip the failsafe without a corner or hotkey.
+fn abort_if(triggered: bool) -> Result<(), MouseError> {
+    if triggered {
+        release_held_inputs();
+        return Err(MouseError::Aborted);
+    }
+    Ok(())
+}
+
+pub(crate) fn track_key(key: Key, action: ButtonAction) {
+    let mut held = HELD_KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+    match action {
+        ButtonAction::Press if !held.contains(&key) => held.push(key),
+        ButtonAction::Press => {}
+        ButtonAction::Release => held.retain(|&other| other != key),
+    }
+}
+
+fn release_held_inputs() {
+    let buttons = [
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::Back,
+        MouseButton::Forward,
+    ];
+    let keys = [VK_SHIFT, VK_CONTROL, VK_MENU, VK_LWIN, VK_RWIN];
+
+    let mut inputs = Vec::new();
+    for button in buttons {
+        if let (Ok(virtual_key), Ok((_, up_flag, data))) =
+            (button.virtual_key(), button_flags(button))
+        {
+            if unsafe { GetAsyncKeyState(virtual_key) } as u16 & 0x8000 != 0 {
+                inputs.push(mouse_input(up_flag, data));
+            }
+        }
+    }
+    let held = mem::take(&mut *HELD_KEYS.lock().unwrap_or_else(PoisonError::into_inner));
+    for key in held.into_iter().rev() {
+        if let Key::Unicode(c) = key {
+            let mut utf16 = [0u16; 2];
+            for &unit in c.encode_utf16(&mut utf16).iter() {
+                inputs.push(unicode_input(unit, KEYEVENTF_KEYUP));
+            }
+        } else if let Ok(virtual_key) = key.to_virtual_key() {
+            let mut flags = KEYEVENTF_KEYUP;
+            if key.is_extended() {
+                flags |= KEYEVENTF_EXTENDEDKEY;
+            }
+            inputs.push(keyboard_input(virtual_key, flags));
+        }
+    }
+    for virtual_key in keys {
+        if unsafe { GetAsyncKeyState(virtual_key) } as u16 & 0x8000 != 0 {
+            inputs.push(keyboard_input(virtual_key as u16, KEYEVENTF_KEYUP));
+        }
+    }
+    // Best effort: we are already reporting an abort and have nowhere to put a second error.
+    let _ = input::send(&mut inputs);
+}
+
+#[cfg(test)]
+mod tests {
+    use winapi::um::winuser::VK_F22;
+
+    use super::*;
+    use crate::keyboard::Keyboard;
+
+    #[test]
+    fn test_default_covers_all_corners() {
+        let failsafe = Failsafe::new();
+        assert!(failsafe.is_in_corner(0, 0, 1920, 1080));
+        assert!(failsafe.is_in_corner(1919, 0, 1920, 1080));
+        assert!(failsafe.is_in_corner(0, 1079, 1920, 1080));
+        assert!(failsafe.is_in_corner(1919, 1079, 1920, 1080));
+        assert!(!failsafe.is_in_corner(960, 540, 1920, 1080));
+        assert!(!failsafe.is_in_corner(0, 540, 1920, 1080));
+    }
+
+    #[test]
+    fn test_configured_corners_only() {
+        let failsafe = Failsafe {
+            corners: vec![Corner::TopLeft],
+            ..Failsafe::new()
+        };
+        assert!(failsafe.is_in_corner(0, 0, 1920, 1080));
+        assert!(!failsafe.is_in_corner(1919, 1079, 1920, 1080));
+    }
+
+    #[test]
+    fn test_corner_margin() {
+        let failsafe = Failsafe {
+            corner_margin: 5,
+            ..Failsafe::new()
+        };
+        assert!(failsafe.is_in_corner(5, 5, 1920, 1080));
+        assert!(failsafe.is_in_corner(1914, 1074, 1920, 1080));
+        assert!(!failsafe.is_in_corner(6, 5, 1920, 1080));
+    }
+
+    #[test]
+    fn test_no_hotkey_is_not_down() {
+        assert!(!Failsafe::new().is_hotkey_down());
+    }
+
+    #[test]
+    fn test_check_when_disabled() {
+        assert!(!Failsafe::is_enabled());
+        assert!(check().is_ok());
+    }
+
+    #[test]
+    fn test_trigger_aborts_and_releases_held_keys() {
+        let mut keyboard = Keyboard::new();
+        keyboard.press_key(Key::F(22)).unwrap();
+        assert!(input::is_key_down(VK_F22));
+
+        assert!(abort_if(false).is_ok());
+        assert!(input::is_key_down(VK_F22));
+        assert!(matches!(abort_if(true), Err(MouseError::Aborted)));
+        assert!(!input::is_key_down(VK_F22));
+    }
+}
@@ -0,0 +1,389 @@
+use std::{io, mem, ptr};
+use winapi::{
+    shared::{
+        minwindef::{DWORD, FALSE},
+        ntdef::HANDLE,
+        windef::{HWND, POINT},
+        winerror::ERROR_ACCESS_DENIED,
+    },
+    um::{
+        handleapi::CloseHandle,
+        processthreadsapi::{GetCurrentProcess, OpenProcess, OpenProcessToken},
+        securitybaseapi::{GetSidSubAuthority, GetSidSubAuthorityCount, GetTokenInformation},
+        winnt::{
+            TokenIntegrityLevel, PROCESS_QUERY_LIMITED_INFORMATION, TOKEN_MANDATORY_LABEL,
+            TOKEN_QUERY,
+        },
+        winuser::{
+            GetAsyncKeyState, GetCursorPos, GetForegroundWindow, GetSystemMetrics,
+            GetWindowThreadProcessId, SendInput, SetCursorPos, WindowFromPoint, INPUT,
+            INPUT_KEYBOARD, INPUT_MOUSE, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+            MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_MIDDLEDOWN,
+            MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_WHEEL, MOUSEEVENTF_XDOWN,
+            SM_CXSCREEN, SM_CYSCREEN,
+        },
+    },
+};
+
+use crate::{
+    backend::InputEvent,
+    keyboard::Key,
+    mouse::{button_flags, ButtonAction, MouseError},
+    scroll::ScrollAxis,
+};
+
+pub(crate) fn mouse_input(flags: DWORD, data: i32) -> INPUT {
+    let mut input = INPUT {
+        type_: INPUT_MOUSE,
+        u: unsafe { mem::zeroed() },
+    };
+    unsafe {
+        let mi = input.u.mi_mut();
+        mi.dwFlags = flags;
+        mi.mouseData = data as DWORD;
+    }
+    input
+}
+
+pub(crate) fn absolute_move_input(x: i32, y: i32) -> INPUT {
+    // Absolute coordinates are normalised to 0..=65535 across the primary screen; round
+    // up so that the system maps the value back onto exactly the requested pixel.
+    let (width, height) = screen_size();
+    let normalize = |value: i32, size: i32| {
+        ((value as i64 * 65536 + size as i64 - 1) / size.max(1) as i64) as i32
+    };
+    let mut input = mouse_input(MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE, 0);
+    unsafe {
+        let mi = input.u.mi_mut();
+        mi.dx = normalize(x, width);
+        mi.dy = normalize(y, height);
+    }
+    input
+}
+
+pub(crate) fn keyboard_input(virtual_key: u16, flags: DWORD) -> INPUT {
+    let mut input = INPUT {
+        type_: INPUT_KEYBOARD,
+        u: unsafe { mem::zeroed() },
+    };
+    unsafe {
+        let ki = input.u.ki_mut();
+        ki.wVk = virtual_key;
+        ki.dwFlags = flags;
+    }
+    input
+}
+
+pub(crate) fn unicode_input(unit: u16, flags: DWORD) -> INPUT {
+    let mut input = keyboard_input(0, flags | KEYEVENTF_UNICODE);
+    unsafe { input.u.ki_mut().wScan = unit };
+    input
+}
+
+pub(crate) fn screen_size() -> (i32, i32) {
+    unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) }
+}
+
+pub(crate) fn send_events(events: &[InputEvent]) -> Result<(), MouseError> {
+    let mut inputs = Vec::with_capacity(events.len());
+    for event in events {
+        match *event {
+            InputEvent::MoveTo { x, y } => inputs.push(absolute_move_input(x, y)),
+            InputEvent::Button { button, action } => {
+                let (down_flag, up_flag, data) = button_flags(button)?;
+                let flags = match action {
+                    ButtonAction::Press => down_flag,
+                    ButtonAction::Release => up_flag,
+                };
+                inputs.push(mouse_input(flags, data));
+            }
+            InputEvent::Scroll { axis, amount } => {
+                let flags = match axis {
+                    ScrollAxis::Vertical => MOUSEEVENTF_WHEEL,
+                    ScrollAxis::Horizontal => MOUSEEVENTF_HWHEEL,
+                };
+                inputs.push(mouse_input(flags, amount));
+            }
+            InputEvent::Key {
+                key: Key::Unicode(c),
+                action,
+            }
+            | InputEvent::Char { c, action } => {
+                let mut utf16 = [0u16; 2];
+                for &unit in c.encode_utf16(&mut utf16).iter() {
+                    inputs.push(unicode_input(unit, key_flags(action)));
+                }
+            }
+            InputEvent::Key { key, action } => {
+                let mut flags = key_flags(action);
+                if key.is_extended() {
+                    flags |= KEYEVENTF_EXTENDEDKEY;
+                }
+                inputs.push(keyboard_input(key.to_virtual_key()?, flags));
+            }
+        }
+    }
+    send(&mut inputs)
+}
+
+fn key_flags(action: ButtonAction) -> DWORD {
+    match action {
+        ButtonAction::Press => 0,
+        ButtonAction::Release => KEYEVENTF_KEYUP,
+    }
+}
+
+pub(crate) fn send(inputs: &mut [INPUT]) -> Result<(), MouseError> {
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    // User Interface Privilege Isolation silently drops input aimed at a process with a
+    // higher integrity level: SendInput still reports success, so check up front.
+    let start = cursor_pos().unwrap_or((0, 0));
+    if is_blocked(inputs, start, |target| is_window_elevated(target.window())) {
+        return Err(MouseError::InjectionBlocked);
+    }
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_mut_ptr(),
+            mem::size_of::<INPUT>() as i32,
+        )
+    };
+    if sent as usize != inputs.len() {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32) {
+            return Err(MouseError::InjectionBlocked);
+        }
+        return Err(os_error(err));
+    }
+    Ok(())
+}
+
+pub(crate) fn set_cursor_pos(x: i32, y: i32) -> Result<(), MouseError> {
+    if unsafe { SetCursorPos(x, y) } == 0 {
+        return Err(os_error(io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+pub(crate) fn cursor_pos() -> Result<(i32, i32), MouseError> {
+    let mut point = POINT { x: 0, y: 0 };
+    if unsafe { GetCursorPos(&mut point) } == 0 {
+        // GetCursorPos only fails when the calling thread is not attached to the input
+        // desktop, e.g. in a service or while the workstation is locked.
+        let err = io::Error::last_os_error();
+        return Err(MouseError::BackendUnavailable(err.to_string()));
+    }
+    Ok((point.x, point.y))
+}
+
+// Only the high bit reflects the current state; the low bit means "pressed at some
+// point since the last query" and is shared with every other caller in the session.
+pub(crate) fn is_key_down(virtual_key: i32) -> bool {
+    let state = unsafe { GetAsyncKeyState(virtual_key) } as u16;
+    state & 0x8000 != 0
+}
+
+pub(crate) fn os_error(err: io::Error) -> MouseError {
+    match err.raw_os_error() {
+        Some(code) if code == ERROR_ACCESS_DENIED as i32 => MouseError::PermissionDenied,
+        Some(code) => MouseError::Os {
+            code,
+            message: err.to_string(),
+        },
+        None => MouseError::IoError(err),
+    }
+}
+
+// Where UIPI checks an event: mouse input against the window under the cursor, keyboard
+// input against the foreground window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Point(i32, i32),
+    Foreground,
+}
+
+impl Target {
+    fn window(self) -> HWND {
+        match self {
+            Target::Point(x, y) => unsafe { WindowFromPoint(POINT { x, y }) },
+            Target::Foreground => unsafe { GetForegroundWindow() },
+        }
+    }
+}
+
+// Only presses and wheel turns are checked. Button-up, key-up and move events always go
+// through, so held buttons and keys can be released whatever window is in front.
+fn is_blocked<F>(inputs: &[INPUT], start: (i32, i32), elevated: F) -> bool
+where
+    F: Fn(Target) -> bool,
+{
+    const PRESSES: DWORD = MOUSEEVENTF_LEFTDOWN
+        | MOUSEEVENTF_RIGHTDOWN
+        | MOUSEEVENTF_MIDDLEDOWN
+        | MOUSEEVENTF_XDOWN
+        | MOUSEEVENTF_WHEEL
+        | MOUSEEVENTF_HWHEEL;
+    let (width, height) = screen_size();
+    let (mut x, mut y) = start;
+    inputs.iter().any(|input| match input.type_ {
+        INPUT_MOUSE => {
+            let mi = unsafe { input.u.mi() };
+            if mi.dwFlags & MOUSEEVENTF_MOVE != 0 {
+                if mi.dwFlags & MOUSEEVENTF_ABSOLUTE != 0 {
+                    x = (mi.dx as i64 * width as i64 / 65536) as i32;
+                    y = (mi.dy as i64 * height as i64 / 65536) as i32;
+                } else {
+                    x = x.saturating_add(mi.dx);
+                    y = y.saturating_add(mi.dy);
+                }
+            }
+            mi.dwFlags & PRESSES != 0 && elevated(Target::Point(x, y))
+        }
+        INPUT_KEYBOARD => {
+            let ki = unsafe { input.u.ki() };
+            ki.dwFlags & KEYEVENTF_KEYUP == 0 && elevated(Target::Foreground)
+        }
+        _ => false,
+    })
+}
+
+fn is_window_elevated(hwnd: HWND) -> bool {
+    if hwnd.is_null() {
+        return false;
+    }
+    let mut process_id = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut process_id) };
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id) };
+    if process.is_null() {
+        return false;
+    }
+    let window_level = integrity_level(process);
+    unsafe { CloseHandle(process) };
+    match (
+        window_level,
+        integrity_level(unsafe { GetCurrentProcess() }),
+    ) {
+        (Some(theirs), Some(ours)) => theirs > ours,
+        _ => false,
+    }
+}
+
+fn integrity_level(process: HANDLE) -> Option<u32> {
+    let mut token = ptr::null_mut();
+    if unsafe { OpenProcessToken(process, TOKEN_QUERY, &mut token) } == 0 {
+        return None;
+    }
+    let mut len = 0;
+    unsafe { GetTokenInformation(token, TokenIntegrityLevel, ptr::null_mut(), 0, &mut len) };
+    // Use u64 storage so the buffer is suitably aligned for TOKEN_MANDATORY_LABEL.
+    let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+    let level = unsafe {
+        if GetTokenInformation(
+            token,
+            TokenIntegrityLevel,
+            buffer.as_mut_ptr() as *mut _,
+            len,
+            &mut len,
+        ) == 0
+        {
+            None
+        } else {
+            let label = &*(buffer.as_ptr() as *const TOKEN_MANDATORY_LABEL);
+            let count = *GetSidSubAuthorityCount(label.Label.Sid);
+            Some(*GetSidSubAuthority(label.Label.Sid, count as u32 - 1))
+        }
+    };
+    unsafe { CloseHandle(token) };
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use winapi::um::winuser::{MOUSEEVENTF_LEFTUP, VK_SHIFT};
+
+    use super::*;
+
+    #[test]
+    fn test_os_error_access_denied() {
+        let err = io::Error::from_raw_os_error(ERROR_ACCESS_DENIED as i32);
+        assert!(matches!(os_error(err), MouseError::PermissionDenied));
+    }
+
+    #[test]
+    fn test_os_error_carries_code() {
+        let err = io::Error::from_raw_os_error(87);
+        match os_error(err) {
+            MouseError::Os { code, message } => {
+                assert_eq!(code, 87);
+                assert!(!message.is_empty());
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_current_process_integrity_level() {
+        assert!(integrity_level(unsafe { GetCurrentProcess() }).is_some());
+    }
+
+    #[test]
+    fn test_absolute_move_input_round_trips() {
+        let (width, height) =
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        for (x, y) in [
+            (0, 0),
+            (1, 1),
+            (width / 2, height / 3),
+            (width - 1, height - 1),
+        ] {
+            let input = absolute_move_input(x, y);
+            let mi = unsafe { input.u.mi() };
+            assert_eq!((mi.dx as i64 * width as i64 / 65536) as i32, x);
+            assert_eq!((mi.dy as i64 * height as i64 / 65536) as i32, y);
+        }
+    }
+
+    #[test]
+    fn test_releases_are_never_blocked() {
+        let inputs = [
+            absolute_move_input(10, 10),
+            mouse_input(MOUSEEVENTF_LEFTUP, 0),
+            keyboard_input(VK_SHIFT as u16, KEYEVENTF_KEYUP),
+        ];
+        assert!(!is_blocked(&inputs, (0, 0), |_| true));
+    }
+
+    #[test]
+    fn test_mouse_press_checks_window_under_target() {
+        let (width, height) =
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        let (x, y) = (width / 2, height / 2);
+        let inputs = [
+            absolute_move_input(x, y),
+            mouse_input(MOUSEEVENTF_LEFTDOWN, 0),
+        ];
+        assert!(is_blocked(&inputs, (0, 0), |t| t == Target::Point(x, y)));
+        // An elevated foreground window does not stop clicks on other windows.
+        assert!(!is_blocked(&inputs, (0, 0), |t| t == Target::Foreground));
+    }
+
+    #[test]
+    fn test_key_press_checks_foreground() {
+        let inputs = [keyboard_input(VK_SHIFT as u16, 0)];
+        assert!(is_blocked(&inputs, (0, 0), |t| t == Target::Foreground));
+        assert!(!is_blocked(&inputs, (0, 0), |t| t != Target::Foreground));
+    }
+
+    #[test]
+    fn test_send_empty() {
+        assert!(send(&mut []).is_ok());
+    }
+
+    #[test]
+    fn test_cursor_pos() {
+        let (x, y) = cursor_pos().unwrap();
+        assert!(x >= 0 && y >= 0);
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use winapi::um::winuser::GetAsyncKeyState;
+
+use crate::{
+    clipboard::Clipboard,
+    input,
+    keyboard::Key,
+    mouse::{Mouse, MouseButton, MouseError},
+    screen::{Monitor, Screen},
+    window::Window,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readout {
+    pub x: i32,
+    pub y: i32,
+    pub monitor: Option<Monitor>,
+    pub color: Option<(u8, u8, u8)>,
+    pub window_title: Option<String>,
+    pub window_class: Option<String>,
+    pub buttons: Vec<MouseButton>,
+}
+
+impl Readout {
+    pub fn capture(mouse: &Mouse) -> Result<Self, MouseError> {
+        let (x, y) = input::cursor_pos()?;
+        let window = Window::from_point(x, y);
+        Ok(Self {
+            x,
+            y,
+            monitor: Screen::monitor_at(x, y),
+            color: Screen::pixel(x, y).ok(),
+            window_title: window.map(|window| window.title()),
+            window_class: window.map(|window| window.class_name()),
+            buttons: mouse.pressed_buttons(),
+        })
+    }
+
+    pub fn snippet(&self) -> String {
+        format!("mouse.move_to({}, {})?;\nmouse.click()?;\n", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Readout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)?;
+        if let Some(monitor) = &self.monitor {
+            let name = monitor.name.trim_start_matches(r"\\.\");
+            write!(f, "  {} @ {:.0}%", name, monitor.scale_factor() * 100.0)?;
+        }
+        if let Some((r, g, b)) = self.color {
+            write!(f, "  #{:02X}{:02X}{:02X}", r, g, b)?;
+        }
+        if let Some(title) = &self.window_title {
+            write!(f, "  \"{}\"", title)?;
+        }
+        if let Some(class) = &self.window_class {
+            write!(f, " [{}]", class)?;
+        }
+        if !self.buttons.is_empty() {
+            let names: Vec<String> = self
+                .buttons
+                .iter()
+                .map(|button| format!("{:?}", button))
+                .collect();
+            write!(f, "  buttons: {}", names.join("+"))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Inspector {
+    pub interval: Duration,
+    pub copy_hotkey: Vec<Key>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_millis(50),
+            copy_hotkey: vec![Key::Control, Key::Shift, Key::Char('c')],
+        }
+    }
+
+    pub fn run(&self, mouse: &Mouse) -> Result<(), MouseError> {
+        // Waiting on standard input rather than polling a global key state means that
+        // typing in another application cannot end the session.
+        let quit = Arc::new(AtomicBool::new(false));
+        let quit_reader = quit.clone();
+        std::thread::spawn(move || {
+            let _ = io::stdin().lock().read_line(&mut String::new());
+            quit_reader.store(true, Ordering::SeqCst);
+        });
+        println!("Press Enter to quit.");
+
+        let mut stdout = io::stdout();
+        let mut last_width: usize = 0;
+        let mut hotkey_was_down = false;
+        while !quit.load(Ordering::SeqCst) {
+            let readout = Readout::capture(mouse)?;
+            let line = readout.to_string();
+            let width = line.chars().count();
+            // Pad with spaces to clear whatever is left of a longer previous line.
+            print!("\r{}{}", line, " ".repeat(last_width.saturating_sub(width)));
+            stdout.flush()?;
+            last_width = width;
+
+            let hotkey_down = is_combo_down(&self.copy_hotkey);
+            if hotkey_down && !hotkey_was_down {
+                Clipboard::new().set_text(&readout.snippet())?;
+            }
+            hotkey_was_down = hotkey_down;
+            std::thread::sleep(self.interval);
+        }
+        println!();
+        Ok(())
+    }
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_combo_down(keys: &[Key]) -> bool {
+    !keys.is_empty()
+        && keys.iter().all(|key| match key.to_virtual_key() {
+            Ok(code) => unsafe { GetAsyncKeyState(code as i32) as u16 & 0x8000 != 0 },
+            Err(_) => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::Rect;
+
+    fn sample_readout() -> Readout {
+        Readout {
+            x: 120,
+            y: 45,
+            monitor: Some(Monitor {
+                name: r"\\.\DISPLAY1".to_string(),
+                rect: Rect::new(0, 0, 1920, 1080),
+                work_area: Rect::new(0, 0, 1920, 1040),
+                primary: true,
+                dpi: 144,
+            }),
+            color: Some((255, 128, 0)),
+            window_title: Some("Untitled - Notepad".to_string()),
+            window_class: Some("Notepad".to_string()),
+            buttons: vec![MouseButton::Left],
+        }
+    }
+
+    #[test]
+    fn test_readout_display() {
+        assert_eq!(
+            sample_readout().to_string(),
+            "(120, 45)  DISPLAY1 @ 150%  #FF8000  \"Untitled - Notepad\" [Notepad]  buttons: Left"
+        );
+    }
+
+    #[test]
+    fn test_readout_snippet() {
+        assert_eq!(
+            sample_readout().snippet(),
+            "mouse.move_to(120, 45)?;\nmouse.click()?;\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_combo_is_never_down() {
+        assert!(!is_combo_down(&[]));
+    }
+
+    #[test]
+    fn test_capture_readout() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(10, 10).unwrap();
+        let readout = Readout::capture(&mouse).unwrap();
+        assert_eq!((readout.x, readout.y), (10, 10));
+        assert!(readout.monitor.is_some());
+    }
+}
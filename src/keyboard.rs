@@ -0,0 +1,513 @@
+use std::{
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+    time::Duration,
+};
+use winapi::um::winuser::{
+    VkKeyScanW, VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_HOME,
+    VK_INSERT, VK_LEFT, VK_LWIN, VK_MENU, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT,
+    VK_SPACE, VK_TAB, VK_UP,
+};
+
+#[cfg(feature = "vnc")]
+use crate::vnc::Vnc;
+use crate::{
+    backend::{Backend, InputEvent},
+    batch::InputBatch,
+    clipboard::Clipboard,
+    failsafe,
+    mock::MockInput,
+    mouse::{ButtonAction, MouseError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Key {
+    Control,
+    Shift,
+    Alt,
+    Meta,
+    Enter,
+    Tab,
+    Escape,
+    Backspace,
+    Delete,
+    Insert,
+    Space,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+    // The key that types this character on the current layout, without its shift state.
+    Char(char),
+    // The character itself, whatever the layout and modifiers, so 'A' types "A".
+    Unicode(char),
+    Code(u16),
+}
+
+impl Key {
+    pub(crate) fn to_virtual_key(self) -> Result<u16, MouseError> {
+        let code = match self {
+            Key::Control => VK_CONTROL,
+            Key::Shift => VK_SHIFT,
+            Key::Alt => VK_MENU,
+            Key::Meta => VK_LWIN,
+            Key::Enter => VK_RETURN,
+            Key::Tab => VK_TAB,
+            Key::Escape => VK_ESCAPE,
+            Key::Backspace => VK_BACK,
+            Key::Delete => VK_DELETE,
+            Key::Insert => VK_INSERT,
+            Key::Space => VK_SPACE,
+            Key::Up => VK_UP,
+            Key::Down => VK_DOWN,
+            Key::Left => VK_LEFT,
+            Key::Right => VK_RIGHT,
+            Key::Home => VK_HOME,
+            Key::End => VK_END,
+            Key::PageUp => VK_PRIOR,
+            Key::PageDown => VK_NEXT,
+            Key::F(n) if (1..=24).contains(&n) => VK_F1 + n as i32 - 1,
+            Key::F(_) => return Err(MouseError::InvalidInput),
+            Key::Char(c) => {
+                let mut utf16 = [0u16; 2];
+                if c.encode_utf16(&mut utf16).len() != 1 {
+                    return Err(MouseError::InvalidInput);
+                }
+                // The low byte is the virtual key; the high byte holds the shift state,
+                // which the caller controls through explicit modifier keys.
+                let scan = unsafe { VkKeyScanW(utf16[0]) };
+                if scan == -1 {
+                    return Err(MouseError::InvalidInput);
+                }
+                (scan & 0xff) as i32
+            }
+            // Sent as a character rather than a key, so it has no virtual key.
+            Key::Unicode(_) => return Err(MouseError::InvalidInput),
+            Key::Code(code) => return Ok(code),
+        };
+        Ok(code as u16)
+    }
+
+    pub fn parse_combo(combo: &str) -> Result<Vec<Key>, MouseError> {
+        // A trailing "+" names the plus key itself, as in "ctrl++".
+        let (keys, plus) = match combo.strip_suffix("++") {
+            Some(keys) => (keys, true),
+            None => (combo, false),
+        };
+        let mut parsed = keys
+            .split('+')
+            .map(|name| name.trim().parse())
+            .collect::<Result<Vec<Key>, MouseError>>()?;
+        if plus {
+            parsed.push(Key::Char('+'));
+        }
+        Ok(parsed)
+    }
+
+    pub(crate) fn is_extended(self) -> bool {
+        matches!(
+            self,
+            Key::Delete
+                | Key::Insert
+                | Key::Up
+                | Key::Down
+                | Key::Left
+                | Key::Right
+                | Key::Home
+                | Key::End
+                | Key::PageUp
+                | Key::PageDown
+                | Key::Meta
+        )
+    }
+}
+
+impl FromStr for Key {
+    type Err = MouseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+        let key = match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Key::Control,
+            "shift" => Key::Shift,
+            "alt" => Key::Alt,
+            "meta" | "win" | "super" | "cmd" => Key::Meta,
+            "enter" | "return" => Key::Enter,
+            "tab" => Key::Tab,
+            "esc" | "escape" => Key::Escape,
+            "backspace" => Key::Backspace,
+            "del" | "delete" => Key::Delete,
+            "ins" | "insert" => Key::Insert,
+            "space" => Key::Space,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pgup" | "pageup" => Key::PageUp,
+            "pgdn" | "pagedown" => Key::PageDown,
+            lower => match lower.strip_prefix('f').map(str::parse::<u8>) {
+                Some(Ok(n)) if (1..=24).contains(&n) => Key::F(n),
+                _ => {
+                    return Err(MouseError::ConversionError(format!(
+                        "Unknown key: {}",
+                        name
+                    )))
+                }
+            },
+        };
+        Ok(key)
+    }
+}
+
+pub struct Keyboard {
+    backend: Backend,
+    paste_delay: Duration,
+    clipboard: Clipboard,
+    held_keys: Vec<Key>,
+}
+
+impl Keyboard {
+    pub fn new() -> Self {
+        Self {
+            backend: Backend::Windows,
+            paste_delay: Duration::from_millis(100),
+            clipboard: Clipboard::new(),
+            held_keys: Vec::new(),
+        }
+    }
+
+    // Types on a VNC server instead of the local desktop.
+    #[cfg(feature = "vnc")]
+    pub fn vnc(vnc: &Vnc) -> Self {
+        let mut keyboard = Self::new();
+        keyboard.backend = Backend::Vnc(vnc.clone());
+        keyboard
+    }
+
+    // Types on a MockInput, which only exists in memory.
+    pub fn mock(mock: &MockInput) -> Self {
+        let mut keyboard = Self::new();
+        keyboard.backend = Backend::Mock(mock.clone());
+        keyboard
+    }
+
+    pub fn set_paste_delay(&mut self, delay: Duration) {
+        self.paste_delay = delay;
+    }
+
+    // The clipboard paste_text goes through, for pasting into X11 applications.
+    pub fn set_clipboard(&mut self, clipboard: Clipboard) {
+        self.clipboard = clipboard;
+    }
+
+    pub fn press_key(&mut self, key: Key) -> Result<(), MouseError> {
+        self.send_key(key, false)
+    }
+
+    pub fn release_key(&mut self, key: Key) -> Result<(), MouseError> {
+        self.send_key(key, true)
+    }
+
+    pub fn tap_key(&mut self, key: Key) -> Result<(), MouseError> {
+        self.press_key(key)?;
+        self.release_key(key)
+    }
+
+    pub fn press_combo(&mut self, keys: &[Key]) -> Result<(), MouseError> {
+        // The whole chord is submitted at once so that no other input can slip in
+        // between the modifiers and the key they modify.
+        let mut batch = InputBatch::new();
+        for &key in keys {
+            batch.key_down(key);
+        }
+        for &key in keys.iter().rev() {
+            batch.key_up(key);
+        }
+        self.submit(batch)
+    }
+
+    pub fn type_text(&mut self, text: &str) -> Result<(), MouseError> {
+        let mut batch = InputBatch::new();
+        batch.text(text);
+        self.submit(batch)
+    }
+
+    pub fn paste_text(&mut self, text: &str) -> Result<(), MouseError> {
+        // The remote clipboard cannot be read back, so it is not restored.
+        #[cfg(feature = "vnc")]
+        if let Backend::Vnc(vnc) = &self.backend {
+            vnc.set_clipboard_text(text)?;
+            return self.press_combo(&[Key::Control, Key::Char('v')]);
+        }
+        if let Backend::Mock(mock) = &self.backend {
+            mock.set_clipboard_text(text);
+            return self.press_combo(&[Key::Control, Key::Char('v')]);
+        }
+        let saved = self.clipboard.save()?;
+        self.clipboard.set_text(text)?;
+        let pasted = self.press_combo(&[Key::Control, Key::Char('v')]);
+        // The target application reads the clipboard asynchronously, so give it time to
+        // do so before putting the previous contents back.
+        std::thread::sleep(self.paste_delay);
+        let restored = self.clipboard.restore(&saved);
+        pasted?;
+        restored?;
+        Ok(())
+    }
+
+    pub fn hold_key(&mut self, key: Key) -> Result<HeldKey<'_>, MouseError> {
+        self.press_key(key)?;
+        Ok(HeldKey {
+            keyboard: self,
+            key,
+        })
+    }
+
+    pub fn held_keys(&self) -> &[Key] {
+        &self.held_keys
+    }
+
+    // Releases in the reverse order of pressing, so that modifiers come up last.
+    pub fn release_all(&mut self) -> Result<(), MouseError> {
+        let mut result = Ok(());
+        while let Some(&key) = self.held_keys.last() {
+            let released = self.release_key(key);
+            // Forget the key even if the release failed so that we cannot loop forever.
+            self.held_keys.retain(|&held| held != key);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        result
+    }
+
+    fn send_key(&mut self, key: Key, release: bool) -> Result<(), MouseError> {
+        let mut batch = InputBatch::new();
+        if release {
+            batch.key_up(key);
+        } else {
+            batch.key_down(key);
+        }
+        self.submit(batch)
+    }
+
+    fn submit(&mut self, batch: InputBatch) -> Result<(), MouseError> {
+        if let Some(err) = batch.error {
+            return Err(err);
+        }
+        // Releasing is always allowed so that an aborted operation can clean up.
+        if !batch.releases_only {
+            if let Err(err) = failsafe::check() {
+                let _ = self.release_all();
+                return Err(err);
+            }
+        }
+        self.backend.send(&batch.events)?;
+        for event in batch.events {
+            let InputEvent::Key { key, action } = event else {
+                continue;
+            };
+            // The failsafe watches the local desktop, so it only releases local keys.
+            if self.backend.is_local() {
+                failsafe::track_key(key, action);
+            }
+            match action {
+                ButtonAction::Press if !self.held_keys.contains(&key) => self.held_keys.push(key),
+                ButtonAction::Press => {}
+                ButtonAction::Release => self.held_keys.retain(|&held| held != key),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        let _ = self.release_all();
+    }
+}
+
+pub struct HeldKey<'a> {
+    keyboard: &'a mut Keyboard,
+    key: Key,
+}
+
+impl HeldKey<'_> {
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    // Releases now and reports the result, which dropping the guard cannot.
+    pub fn release(self) -> Result<(), MouseError> {
+        let mut held = ManuallyDrop::new(self);
+        let key = held.key;
+        held.keyboard.release_key(key)
+    }
+}
+
+impl Deref for HeldKey<'_> {
+    type Target = Keyboard;
+
+    fn deref(&self) -> &Keyboard {
+        self.keyboard
+    }
+}
+
+impl DerefMut for HeldKey<'_> {
+    fn deref_mut(&mut self) -> &mut Keyboard {
+        self.keyboard
+    }
+}
+
+impl Drop for HeldKey<'_> {
+    fn drop(&mut self) {
+        let _ = self.keyboard.release_key(self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winapi::um::winuser::{VK_F21, VK_F23, VK_F24};
+
+    use super::*;
+    use crate::input;
+
+    #[test]
+    fn test_virtual_key_names() {
+        assert_eq!(Key::Enter.to_virtual_key().unwrap(), VK_RETURN as u16);
+        assert_eq!(Key::F(5).to_virtual_key().unwrap(), (VK_F1 + 4) as u16);
+        assert_eq!(Key::Code(0x41).to_virtual_key().unwrap(), 0x41);
+    }
+
+    #[test]
+    fn test_virtual_key_char() {
+        assert_eq!(Key::Char('v').to_virtual_key().unwrap(), b'V' as u16);
+        assert_eq!(Key::Char('7').to_virtual_key().unwrap(), b'7' as u16);
+    }
+
+    #[test]
+    fn test_unicode_key_is_sent_as_a_character() {
+        let mut batch = InputBatch::new();
+        batch.key_down(Key::Unicode('A')).key_up(Key::Unicode('A'));
+        assert!(batch.error.is_none());
+        assert_eq!(batch.len(), 2);
+        assert!(Key::Unicode('A').to_virtual_key().is_err());
+    }
+
+    #[test]
+    fn test_virtual_key_invalid() {
+        assert!(Key::F(0).to_virtual_key().is_err());
+        assert!(Key::F(25).to_virtual_key().is_err());
+        assert!(Key::Char('😀').to_virtual_key().is_err());
+    }
+
+    #[test]
+    fn test_parse_key_names() {
+        assert_eq!("Ctrl".parse::<Key>().unwrap(), Key::Control);
+        assert_eq!("pgdn".parse::<Key>().unwrap(), Key::PageDown);
+        assert_eq!("F12".parse::<Key>().unwrap(), Key::F(12));
+        assert_eq!("a".parse::<Key>().unwrap(), Key::Char('a'));
+        assert!("f25".parse::<Key>().is_err());
+        assert!("hyper".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn test_parse_combo() {
+        assert_eq!(
+            Key::parse_combo("ctrl+shift+t").unwrap(),
+            vec![Key::Control, Key::Shift, Key::Char('t')]
+        );
+        assert_eq!(
+            Key::parse_combo("ctrl++").unwrap(),
+            vec![Key::Control, Key::Char('+')]
+        );
+        assert!(Key::parse_combo("ctrl+").is_err());
+    }
+
+    #[test]
+    fn test_type_text() {
+        let mut keyboard = Keyboard::new();
+        assert!(keyboard.type_text("").is_ok());
+    }
+
+    #[test]
+    fn test_tap_key() {
+        let mut keyboard = Keyboard::new();
+        assert!(keyboard.tap_key(Key::Shift).is_ok());
+        assert!(keyboard.held_keys().is_empty());
+    }
+
+    #[test]
+    fn test_pressed_keys_are_tracked() {
+        let mut keyboard = Keyboard::new();
+        keyboard.press_key(Key::F(20)).unwrap();
+        keyboard.press_key(Key::F(21)).unwrap();
+        keyboard.press_key(Key::F(20)).unwrap();
+        assert_eq!(keyboard.held_keys(), &[Key::F(20), Key::F(21)]);
+        keyboard.release_key(Key::F(20)).unwrap();
+        assert_eq!(keyboard.held_keys(), &[Key::F(21)]);
+        keyboard.release_all().unwrap();
+        assert!(keyboard.held_keys().is_empty());
+        assert!(!input::is_key_down(VK_F21));
+    }
+
+    #[test]
+    fn test_drop_releases_held_keys() {
+        let mut keyboard = Keyboard::new();
+        keyboard.press_key(Key::F(24)).unwrap();
+        assert!(input::is_key_down(VK_F24));
+        drop(keyboard);
+        assert!(!input::is_key_down(VK_F24));
+    }
+
+    #[test]
+    fn test_hold_key_guard_releases_on_drop() {
+        let mut keyboard = Keyboard::new();
+        {
+            let held = keyboard.hold_key(Key::F(23)).unwrap();
+            assert_eq!(held.key(), Key::F(23));
+            assert_eq!(held.held_keys(), &[Key::F(23)]);
+        }
+        assert!(keyboard.held_keys().is_empty());
+        assert!(!input::is_key_down(VK_F23));
+    }
+
+    #[test]
+    fn test_press_combo() {
+        let mut keyboard = Keyboard::new();
+        assert!(keyboard.press_combo(&[Key::Shift, Key::Control]).is_ok());
+    }
+
+    #[test]
+    fn test_paste_text_restores_clipboard() {
+        let _lock = crate::clipboard::CLIPBOARD_LOCK.lock().unwrap();
+        let clipboard = Clipboard::new();
+        clipboard.set_text("before paste").unwrap();
+
+        let mut keyboard = Keyboard::new();
+        keyboard.paste_text("pasted").unwrap();
+        assert_eq!(clipboard.get_text().unwrap(), "before paste");
+    }
+}
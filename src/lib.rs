@@ -1,5 +1,63 @@
+#[cfg(any(feature = "server", feature = "webdriver"))]
+mod access;
+mod actions;
+mod backend;
+mod batch;
+mod cancel;
+mod clipboard;
+mod failsafe;
+mod input;
+mod inspector;
 mod keyboard;
+mod mock;
 mod mouse;
+mod screen;
+#[cfg(feature = "scripting")]
+mod script;
+mod scroll;
+#[cfg(feature = "server")]
+mod server;
+mod shared;
+#[cfg(feature = "async")]
+mod timer;
+mod tracker;
+#[cfg(feature = "uinput")]
+mod uinput;
+#[cfg(feature = "vnc")]
+mod vnc;
+#[cfg(feature = "webdriver")]
+mod webdriver;
+mod wide;
+mod window;
+#[cfg(feature = "x11")]
+mod x11;
 
-pub use mouse::Mouse;
+pub use actions::{Action, Actions};
+pub use batch::InputBatch;
+pub use cancel::CancellationToken;
+pub use clipboard::{Clipboard, ClipboardContents, ClipboardError, ClipboardImage};
+pub use failsafe::{Corner, Failsafe};
+pub use inspector::{Inspector, Readout};
+pub use keyboard::{HeldKey, Key, Keyboard};
+pub use mock::MockInput;
+pub use mouse::{
+    ButtonAction, ClickOptions, EventType, ExternalMovement, HeldButton, Mouse, MouseButton,
+    MouseError, PositionTracking, WindowMouse,
+};
+pub use screen::{Monitor, Screen, ScreenError};
+#[cfg(feature = "scripting")]
+pub use script::{Script, ScriptError, ScriptLimits};
+pub use scroll::{Easing, ScrollAmount, ScrollAxis, ScrollOptions};
+#[cfg(feature = "server")]
+pub use server::{Server, ServerError};
+pub use shared::SharedMouse;
+#[cfg(feature = "uinput")]
+pub use uinput::Uinput;
+#[cfg(feature = "vnc")]
+pub use vnc::{Vnc, VncError};
+#[cfg(feature = "webdriver")]
+pub use webdriver::WebDriver;
+pub use window::{Rect, Window, WindowError};
+#[cfg(feature = "x11")]
+pub use x11::{Selection, X11Input};
 mod event_emitter;
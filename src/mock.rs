@@ -0,0 +1,223 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    backend::InputEvent,
+    keyboard::{Key, Keyboard},
+    mouse::{ButtonAction, Mouse, MouseButton, MouseError},
+    scroll::ScrollAxis,
+};
+
+// A desktop that only exists in memory. Input sent to it updates its state instead of
+// the real one, so that code driving Mouse and Keyboard can be tested anywhere; clones
+// share the state.
+#[derive(Debug, Clone)]
+pub struct MockInput {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    width: i32,
+    height: i32,
+    position: (i32, i32),
+    buttons: Vec<MouseButton>,
+    keys: Vec<Key>,
+    text: String,
+    // Wheel units, vertical then horizontal.
+    scrolled: (i32, i32),
+    clipboard: String,
+}
+
+impl MockInput {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                width,
+                height,
+                position: (0, 0),
+                buttons: Vec::new(),
+                keys: Vec::new(),
+                text: String::new(),
+                scrolled: (0, 0),
+                clipboard: String::new(),
+            })),
+        }
+    }
+
+    pub fn mouse(&self) -> Mouse {
+        Mouse::mock(self)
+    }
+
+    pub fn keyboard(&self) -> Keyboard {
+        Keyboard::mock(self)
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        self.state().position
+    }
+
+    // Moves the cursor as the user would, behind the back of any Mouse.
+    pub fn move_cursor(&self, x: i32, y: i32) {
+        self.state().position = (x, y);
+    }
+
+    // Presses or releases a button as the user would.
+    pub fn set_button(&self, button: MouseButton, pressed: bool) {
+        self.state().set_button(button, pressed);
+    }
+
+    pub fn pressed_buttons(&self) -> Vec<MouseButton> {
+        self.state().buttons.clone()
+    }
+
+    pub fn pressed_keys(&self) -> Vec<Key> {
+        self.state().keys.clone()
+    }
+
+    // Every character typed so far, whether as text or as a character key.
+    pub fn typed_text(&self) -> String {
+        self.state().text.clone()
+    }
+
+    pub fn scrolled(&self, axis: ScrollAxis) -> i32 {
+        let (vertical, horizontal) = self.state().scrolled;
+        match axis {
+            ScrollAxis::Vertical => vertical,
+            ScrollAxis::Horizontal => horizontal,
+        }
+    }
+
+    pub fn clipboard_text(&self) -> String {
+        self.state().clipboard.clone()
+    }
+
+    pub(crate) fn screen_size(&self) -> (i32, i32) {
+        let state = self.state();
+        (state.width, state.height)
+    }
+
+    pub(crate) fn is_pressed(&self, button: MouseButton) -> bool {
+        self.state().buttons.contains(&normalize(button))
+    }
+
+    pub(crate) fn set_clipboard_text(&self, text: &str) {
+        self.state().clipboard = text.to_string();
+    }
+
+    // Checks every event before applying any, as a real backend sends all or none.
+    pub(crate) fn send(&self, events: &[InputEvent]) -> Result<(), MouseError> {
+        let mut state = self.state();
+        for event in events {
+            if let InputEvent::MoveTo { x, y } = *event {
+                if x < 0 || y < 0 || x >= state.width || y >= state.height {
+                    return Err(MouseError::OutOfBounds);
+                }
+            }
+        }
+        for event in events {
+            match *event {
+                InputEvent::MoveTo { x, y } => state.position = (x, y),
+                InputEvent::Button { button, action } => {
+                    state.set_button(button, action == ButtonAction::Press)
+                }
+                InputEvent::Scroll { axis, amount } => match axis {
+                    ScrollAxis::Vertical => state.scrolled.0 += amount,
+                    ScrollAxis::Horizontal => state.scrolled.1 += amount,
+                },
+                InputEvent::Key { key, action } => {
+                    state.keys.retain(|&held| held != key);
+                    if action == ButtonAction::Press {
+                        state.keys.push(key);
+                        if let Key::Char(c) | Key::Unicode(c) = key {
+                            state.text.push(c);
+                        }
+                    }
+                }
+                InputEvent::Char { c, action } => {
+                    if action == ButtonAction::Press {
+                        state.text.push(c);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn set_button(&mut self, button: MouseButton, pressed: bool) {
+        let button = normalize(button);
+        self.buttons.retain(|&held| held != button);
+        if pressed {
+            self.buttons.push(button);
+        }
+    }
+}
+
+// Any button number is accepted; the first five are known by their names.
+fn normalize(button: MouseButton) -> MouseButton {
+    button.normalize().unwrap_or(button)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_state() {
+        let input = MockInput::new(800, 600);
+        let mut mouse = input.mouse();
+        mouse
+            .simulate_mouse_button(MouseButton::Back, ButtonAction::Press)
+            .unwrap();
+        input.set_button(MouseButton::Other(1), true);
+        assert!(mouse.button_state(MouseButton::Back));
+        assert!(mouse.is_left_button_pressed());
+        assert!(!mouse.is_right_button_pressed());
+        assert_eq!(
+            mouse.pressed_buttons(),
+            vec![MouseButton::Left, MouseButton::Back]
+        );
+
+        mouse
+            .simulate_mouse_button(MouseButton::Back, ButtonAction::Release)
+            .unwrap();
+        input.set_button(MouseButton::Left, false);
+        assert!(mouse.pressed_buttons().is_empty());
+
+        mouse
+            .simulate_mouse_button(MouseButton::Other(7), ButtonAction::Press)
+            .unwrap();
+        assert!(mouse.button_state(MouseButton::Other(7)));
+        assert_eq!(mouse.pressed_buttons(), vec![MouseButton::Other(7)]);
+        mouse.release_all().unwrap();
+        assert!(input.pressed_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_input_is_recorded() {
+        let input = MockInput::new(800, 600);
+        let mut mouse = input.mouse();
+        mouse.move_to(10, 20).unwrap();
+        mouse.scroll(-240).unwrap();
+        assert_eq!(input.position(), (10, 20));
+        assert_eq!(input.scrolled(ScrollAxis::Vertical), -240);
+        assert!(matches!(
+            mouse.move_to(800, 0),
+            Err(MouseError::OutOfBounds)
+        ));
+        assert_eq!(input.position(), (10, 20));
+
+        let mut keyboard = input.keyboard();
+        keyboard.type_text("hi").unwrap();
+        keyboard.press_key(Key::Shift).unwrap();
+        assert_eq!(input.typed_text(), "hi");
+        assert_eq!(input.pressed_keys(), vec![Key::Shift]);
+        drop(keyboard);
+        assert!(input.pressed_keys().is_empty());
+    }
+}
@@ -1,19 +1,43 @@
 use std::{
     error::Error,
     fmt::{self, Display},
-    io::{self, Write},
+    io,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+    time::Duration,
 };
 use winapi::{
-    shared::windef::POINT,
+    shared::minwindef::DWORD,
     um::winuser::{
-        mouse_event, GetAsyncKeyState, GetCursorPos, GetSystemMetrics, SendInput, SetCursorPos,
-        INPUT, INPUT_MOUSE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
-        MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
-        MOUSEEVENTF_WHEEL, SM_CXSCREEN, SM_CYSCREEN, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON,
+        GetDoubleClickTime, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MIDDLEDOWN,
+        MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_XDOWN,
+        MOUSEEVENTF_XUP, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON, VK_XBUTTON1, VK_XBUTTON2, WHEEL_DELTA,
+        XBUTTON1, XBUTTON2,
     },
 };
 
-use crate::event_emitter::EventEmitter;
+#[cfg(feature = "async")]
+use crate::timer;
+#[cfg(feature = "uinput")]
+use crate::uinput::Uinput;
+#[cfg(feature = "vnc")]
+use crate::vnc::{Vnc, VncError};
+#[cfg(feature = "x11")]
+use crate::x11::X11Input;
+use crate::{
+    backend::Backend,
+    batch::InputBatch,
+    cancel::CancellationToken,
+    clipboard::ClipboardError,
+    event_emitter::EventEmitter,
+    failsafe, input,
+    inspector::Inspector,
+    mock::MockInput,
+    scroll::{self, ScrollAxis, ScrollOptions},
+    tracker,
+    window::{Rect, Window, WindowError},
+};
 
 #[derive(Debug)]
 pub enum MouseError {
@@ -21,6 +45,26 @@ pub enum MouseError {
     ConversionError(String),
     IoError(io::Error),
     OutOfBounds,
+    WindowError(WindowError),
+    ClipboardError(ClipboardError),
+    Aborted,
+    InjectionBlocked,
+    BackendUnavailable(String),
+    PermissionDenied,
+    Os {
+        code: i32,
+        message: String,
+    },
+    UnsupportedButton(u8),
+    InvalidAction {
+        index: usize,
+        reason: String,
+    },
+    Cancelled,
+    ExternalMovement {
+        expected: (i32, i32),
+        actual: (i32, i32),
+    },
 }
 
 impl Error for MouseError {}
@@ -32,6 +76,26 @@ impl fmt::Display for MouseError {
             MouseError::ConversionError(msg) => write!(f, "Conversion error: {}", msg),
             MouseError::IoError(err) => write!(f, "IO error: {}", err),
             MouseError::OutOfBounds => write!(f, "Mouse position out of bounds"),
+            MouseError::WindowError(err) => write!(f, "Window error: {}", err),
+            MouseError::ClipboardError(err) => write!(f, "Clipboard error: {}", err),
+            MouseError::Aborted => write!(f, "Aborted by failsafe"),
+            MouseError::InjectionBlocked => write!(
+                f,
+                "Input was blocked, the target window belongs to a more privileged process"
+            ),
+            MouseError::BackendUnavailable(msg) => write!(f, "Input backend unavailable: {}", msg),
+            MouseError::PermissionDenied => write!(f, "Permission denied"),
+            MouseError::Os { code, message } => write!(f, "OS error {}: {}", code, message),
+            MouseError::UnsupportedButton(n) => write!(f, "Mouse button {} is not supported", n),
+            MouseError::InvalidAction { index, reason } => {
+                write!(f, "Invalid action {}: {}", index, reason)
+            }
+            MouseError::Cancelled => write!(f, "Operation was cancelled"),
+            MouseError::ExternalMovement { expected, actual } => write!(
+                f,
+                "Cursor was moved externally from ({}, {}) to ({}, {})",
+                expected.0, expected.1, actual.0, actual.1
+            ),
         }
     }
 }
@@ -42,17 +106,114 @@ impl From<io::Error> for MouseError {
     }
 }
 
+impl From<WindowError> for MouseError {
+    fn from(err: WindowError) -> Self {
+        MouseError::WindowError(err)
+    }
+}
+
+impl From<ClipboardError> for MouseError {
+    fn from(err: ClipboardError) -> Self {
+        MouseError::ClipboardError(err)
+    }
+}
+
+#[cfg(feature = "vnc")]
+impl From<VncError> for MouseError {
+    fn from(err: VncError) -> Self {
+        match err {
+            VncError::IoError(err) => MouseError::IoError(err),
+            VncError::OutOfBounds => MouseError::OutOfBounds,
+            VncError::UnsupportedButton(button) => MouseError::UnsupportedButton(match button {
+                MouseButton::Left => 1,
+                MouseButton::Right => 2,
+                MouseButton::Middle => 3,
+                MouseButton::Back => 4,
+                MouseButton::Forward => 5,
+                MouseButton::Other(n) => n,
+            }),
+            VncError::UnsupportedKey(_) => MouseError::InvalidInput,
+            VncError::UnsupportedText(_) => {
+                MouseError::ClipboardError(ClipboardError::InvalidData(err.to_string()))
+            }
+            VncError::Protocol(_) | VncError::AuthFailed(_) => {
+                MouseError::BackendUnavailable(err.to_string())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
+    Back,
+    Forward,
+    Other(u8),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ButtonAction {
     Press,
     Release,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClickOptions {
+    pub button: MouseButton,
+    pub count: usize,
+    pub interval: Duration,
+    pub hold_duration: Duration,
+    pub position: Option<(i32, i32)>,
+}
+
+impl ClickOptions {
+    pub fn new(button: MouseButton) -> Self {
+        // Clicks only combine into a double or triple click when each one follows the
+        // previous within the system double-click time, so stay comfortably inside it.
+        let double_click_time = unsafe { GetDoubleClickTime() };
+        Self {
+            button,
+            count: 1,
+            interval: Duration::from_millis(double_click_time as u64 / 2),
+            hold_duration: Duration::ZERO,
+            position: None,
+        }
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn hold_duration(mut self, hold_duration: Duration) -> Self {
+        self.hold_duration = hold_duration;
+        self
+    }
+
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        Self::new(MouseButton::Left)
+    }
+}
+
 pub struct MousePosition {
     pub x: i32,
     pub y: i32,
@@ -63,10 +224,12 @@ impl MousePosition {
         Self { x, y }
     }
 
-    pub fn is_out_of_bounds(&self) -> bool {
-        let screen_width = unsafe { GetSystemMetrics(SM_CXSCREEN) };
-        let screen_height = unsafe { GetSystemMetrics(SM_CYSCREEN) };
-        self.x < 0 || self.y < 0 || self.x > screen_width || self.y > screen_height
+    pub(crate) fn is_outside(&self, (width, height): (i32, i32)) -> bool {
+        self.x < 0 || self.y < 0 || self.x > width || self.y > height
+    }
+
+    pub fn is_out_of_bounds_of(&self, rect: &Rect) -> bool {
+        !rect.contains(self.x, self.y)
     }
 
     pub fn to_u32(&self) -> Result<(u32, u32), MouseError> {
@@ -81,21 +244,21 @@ impl MousePosition {
         Ok((x_u32, y_u32))
     }
 
-    pub fn offset(&self, distance_x: i32, distance_y: i32) -> Self {
-        let x = self.x + distance_x;
-        let y = self.y + distance_y;
-
-        Self::new(x, y)
+    pub fn offset(&self, distance_x: i32, distance_y: i32) -> Result<Self, MouseError> {
+        match (
+            self.x.checked_add(distance_x),
+            self.y.checked_add(distance_y),
+        ) {
+            (Some(x), Some(y)) => Ok(Self::new(x, y)),
+            _ => Err(MouseError::OutOfBounds),
+        }
     }
 }
 
 impl Default for MousePosition {
     fn default() -> Self {
-        unsafe {
-            let mut point: POINT = std::mem::zeroed();
-            GetCursorPos(&mut point);
-            Self::new(point.x, point.y)
-        }
+        let (x, y) = input::cursor_pos().unwrap_or((0, 0));
+        Self::new(x, y)
     }
 }
 
@@ -111,16 +274,221 @@ impl Display for EventType {
     }
 }
 
+impl FromStr for MouseButton {
+    type Err = MouseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "left" => Ok(MouseButton::Left),
+            "right" => Ok(MouseButton::Right),
+            "middle" => Ok(MouseButton::Middle),
+            "back" => Ok(MouseButton::Back),
+            "forward" => Ok(MouseButton::Forward),
+            number => number.parse().map(MouseButton::Other).map_err(|_| {
+                MouseError::ConversionError(format!("Unknown mouse button: {}", name))
+            }),
+        }
+    }
+}
+
+impl MouseButton {
+    // Other(n) uses the conventional 1-based numbering: 1 left, 2 right, 3 middle,
+    // 4 back and 5 forward. Windows cannot inject buttons beyond the fifth.
+    pub(crate) fn normalize(self) -> Result<Self, MouseError> {
+        match self {
+            MouseButton::Other(1) => Ok(MouseButton::Left),
+            MouseButton::Other(2) => Ok(MouseButton::Right),
+            MouseButton::Other(3) => Ok(MouseButton::Middle),
+            MouseButton::Other(4) => Ok(MouseButton::Back),
+            MouseButton::Other(5) => Ok(MouseButton::Forward),
+            MouseButton::Other(n) => Err(MouseError::UnsupportedButton(n)),
+            button => Ok(button),
+        }
+    }
+
+    pub(crate) fn virtual_key(self) -> Result<i32, MouseError> {
+        match self.normalize()? {
+            MouseButton::Left => Ok(VK_LBUTTON),
+            MouseButton::Right => Ok(VK_RBUTTON),
+            MouseButton::Middle => Ok(VK_MBUTTON),
+            MouseButton::Back => Ok(VK_XBUTTON1),
+            MouseButton::Forward => Ok(VK_XBUTTON2),
+            MouseButton::Other(n) => Err(MouseError::UnsupportedButton(n)),
+        }
+    }
+}
+
+pub(crate) fn button_flags(button: MouseButton) -> Result<(DWORD, DWORD, i32), MouseError> {
+    match button.normalize()? {
+        MouseButton::Left => Ok((MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 0)),
+        MouseButton::Right => Ok((MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 0)),
+        MouseButton::Middle => Ok((MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, 0)),
+        MouseButton::Back => Ok((MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON1 as i32)),
+        MouseButton::Forward => Ok((MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON2 as i32)),
+        MouseButton::Other(n) => Err(MouseError::UnsupportedButton(n)),
+    }
+}
+
+// Query re-reads the cursor before every operation that starts from the current position.
+// Cached trusts the last position this Mouse set until get_current_position refreshes it.
+// Events keeps the cache and follows physical movement reported by a low-level mouse hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionTracking {
+    #[default]
+    Query,
+    Cached,
+    Events,
+}
+
+// What to do when the cursor turns out to have been moved by someone else: adopt the new
+// position, or stop with MouseError::ExternalMovement. Aborting is also checked on every
+// tick of animations. Cached tracking never notices external movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExternalMovement {
+    #[default]
+    Follow,
+    Abort,
+}
+
+const MOVEMENT_TOLERANCE: i32 = 2;
+
 pub struct Mouse {
+    backend: Backend,
     position: MousePosition,
     event_emitter: EventEmitter,
+    held_buttons: Vec<MouseButton>,
+    cancellation: Option<CancellationToken>,
+    tracking: PositionTracking,
+    external_movement: ExternalMovement,
+    seen_moves: u64,
 }
 
 impl Mouse {
     pub fn new() -> Self {
         Self {
+            backend: Backend::Windows,
             position: MousePosition::default(),
             event_emitter: EventEmitter::new(),
+            held_buttons: Vec::new(),
+            cancellation: None,
+            tracking: PositionTracking::default(),
+            external_movement: ExternalMovement::default(),
+            seen_moves: 0,
+        }
+    }
+
+    // Moves the pointer of a VNC server instead of the local one. Positions and bounds
+    // are then those of the remote framebuffer.
+    #[cfg(feature = "vnc")]
+    pub fn vnc(vnc: &Vnc) -> Self {
+        let mut mouse = Self::new();
+        let (x, y) = vnc.position();
+        mouse.position = MousePosition::new(x, y);
+        mouse.backend = Backend::Vnc(vnc.clone());
+        mouse
+    }
+
+    // Moves the pointer of an X server instead of the local one.
+    #[cfg(feature = "x11")]
+    pub fn x11(x11: &X11Input) -> Result<Self, MouseError> {
+        let mut mouse = Self::new();
+        let (x, y) = x11.position()?;
+        mouse.position = MousePosition::new(x, y);
+        mouse.backend = Backend::X11(x11.clone());
+        Ok(mouse)
+    }
+
+    // Moves the pointer of a Linux input device instead of the local one.
+    #[cfg(feature = "uinput")]
+    pub fn uinput(uinput: &Uinput) -> Self {
+        let mut mouse = Self::new();
+        let (x, y) = uinput.position();
+        mouse.position = MousePosition::new(x, y);
+        mouse.backend = Backend::Uinput(uinput.clone());
+        mouse
+    }
+
+    // Moves the cursor of a MockInput, which only exists in memory.
+    pub fn mock(mock: &MockInput) -> Self {
+        let mut mouse = Self::new();
+        let (x, y) = mock.position();
+        mouse.position = MousePosition::new(x, y);
+        mouse.backend = Backend::Mock(mock.clone());
+        mouse
+    }
+
+    // Animations and repeated clicks or scrolls check the token on every tick and stop
+    // with MouseError::Cancelled once it is cancelled.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation = token;
+    }
+
+    pub fn set_position_tracking(&mut self, tracking: PositionTracking) -> Result<(), MouseError> {
+        if tracking == PositionTracking::Events {
+            if !self.backend.is_local() {
+                return Err(MouseError::BackendUnavailable(
+                    "Tracking by events only follows the local mouse".to_string(),
+                ));
+            }
+            tracker::start()?;
+        }
+        self.tracking = tracking;
+        self.seen_moves = tracker::physical_moves();
+        Ok(())
+    }
+
+    pub fn set_external_movement(&mut self, external_movement: ExternalMovement) {
+        self.external_movement = external_movement;
+    }
+
+    pub(crate) fn tick(&mut self) -> Result<(), MouseError> {
+        failsafe::check()?;
+        if let Some(token) = &self.cancellation {
+            if token.is_cancelled() {
+                return Err(MouseError::Cancelled);
+            }
+        }
+        if self.external_movement == ExternalMovement::Abort {
+            self.sync_position()?;
+        }
+        Ok(())
+    }
+
+    // Brings the cached position up to date according to the tracking policy. The cache
+    // follows the cursor either way, so an abort is only reported once per movement.
+    fn sync_position(&mut self) -> Result<(), MouseError> {
+        let Some(actual) = self.external_position()? else {
+            return Ok(());
+        };
+        let expected = (self.position.x, self.position.y);
+        self.position = MousePosition::new(actual.0, actual.1);
+        if self.external_movement == ExternalMovement::Abort {
+            return Err(MouseError::ExternalMovement { expected, actual });
+        }
+        Ok(())
+    }
+
+    // Where the cursor is, if something other than this Mouse has moved it.
+    fn external_position(&mut self) -> Result<Option<(i32, i32)>, MouseError> {
+        match self.tracking {
+            PositionTracking::Cached => Ok(None),
+            PositionTracking::Query => {
+                let (x, y) = self.backend.cursor_pos()?;
+                // Absolute moves are normalized to 0..65535 and may land a pixel off.
+                let moved = (x - self.position.x).abs() > MOVEMENT_TOLERANCE
+                    || (y - self.position.y).abs() > MOVEMENT_TOLERANCE;
+                Ok(moved.then_some((x, y)))
+            }
+            // The hook does not see SetCursorPos, so our own cache stays authoritative
+            // until the user moves the physical mouse.
+            PositionTracking::Events => {
+                let moves = tracker::physical_moves();
+                if moves == self.seen_moves {
+                    return Ok(None);
+                }
+                self.seen_moves = moves;
+                Ok(Some(tracker::position()))
+            }
         }
     }
 
@@ -128,34 +496,34 @@ impl Mouse {
         (self.position.x, self.position.y)
     }
 
-    pub fn get_current_position(&mut self) -> (i32, i32) {
-        unsafe {
-            let mut point: POINT = std::mem::zeroed();
-            GetCursorPos(&mut point);
-            self.position = MousePosition::new(point.x, point.y);
-            (self.position.x, self.position.y)
-        }
+    pub fn get_current_position(&mut self) -> Result<(i32, i32), MouseError> {
+        let (x, y) = self.backend.cursor_pos()?;
+        self.position = MousePosition::new(x, y);
+        self.seen_moves = tracker::physical_moves();
+        Ok((x, y))
     }
 
     pub fn move_to(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        failsafe::check()?;
         if x < 0 || y < 0 {
             return Err(MouseError::InvalidInput);
         }
 
         let new_position = MousePosition::new(x, y);
-        if new_position.is_out_of_bounds() {
+        if self.is_out_of_bounds(&new_position) {
             return Err(MouseError::OutOfBounds);
         }
         let (x_u32, y_u32) = new_position.to_u32()?;
-        unsafe { SetCursorPos(x_u32 as i32, y_u32 as i32) };
+        self.backend.set_cursor_pos(x_u32 as i32, y_u32 as i32)?;
         self.position = new_position;
+        self.seen_moves = tracker::physical_moves();
         Ok(())
     }
 
     pub fn move_relative(&mut self, distance_x: i32, distance_y: i32) -> Result<(), MouseError> {
-        let new_x = self.position.x + distance_x;
-        let new_y = self.position.y + distance_y;
-        self.move_to(new_x, new_y)?;
+        self.sync_position()?;
+        let new_position = self.position.offset(distance_x, distance_y)?;
+        self.move_to(new_position.x, new_position.y)?;
         Ok(())
     }
 
@@ -170,10 +538,11 @@ impl Mouse {
         }
 
         let new_position = MousePosition::new(x, y);
-        if new_position.is_out_of_bounds() {
+        if self.is_out_of_bounds(&new_position) {
             return Err(MouseError::OutOfBounds);
         }
 
+        self.sync_position()?;
         let current_position = self.get_mouse_position();
 
         let start_x = current_position.0 as f64;
@@ -187,6 +556,7 @@ impl Mouse {
         let start_time = std::time::Instant::now();
         let mut last_progress = 0.0;
         loop {
+            self.tick()?;
             let elapsed = start_time.elapsed().as_secs_f64();
             let progress = elapsed / duration.as_secs_f64();
             if progress >= 1.0 {
@@ -219,6 +589,7 @@ impl Mouse {
         let start_time = std::time::Instant::now();
         let mut last_progress = 0.0;
         loop {
+            self.tick()?;
             let elapsed = start_time.elapsed().as_secs_f64();
             let progress = elapsed / duration.as_secs_f64();
             if progress >= 1.0 {
@@ -249,66 +620,72 @@ impl Mouse {
     }
 
     pub fn click(&mut self) -> Result<(), MouseError> {
-        let new_position = &self.position;
-        if new_position.is_out_of_bounds() {
-            return Err(MouseError::OutOfBounds);
-        }
-        let (x_u32, y_u32) = new_position.to_u32()?;
-        unsafe { mouse_event(MOUSEEVENTF_LEFTDOWN, x_u32, y_u32, 0, 0) };
-        unsafe { mouse_event(MOUSEEVENTF_LEFTUP, x_u32, y_u32, 0, 0) };
-        self.event_emitter.emit(&EventType::Click.to_string());
-        Ok(())
+        self.click_with(ClickOptions::new(MouseButton::Left))
     }
 
     pub fn double_click(&mut self) -> Result<(), MouseError> {
-        self.click()?;
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        self.click()
+        self.click_with(ClickOptions::new(MouseButton::Left).count(2))
     }
 
     pub fn multi_click(&mut self, count: usize) -> Result<(), MouseError> {
-        let new_position = &self.position;
-        if new_position.is_out_of_bounds() {
-            return Err(MouseError::OutOfBounds);
-        }
-        let (x_u32, y_u32) = new_position.to_u32()?;
-        for _ in 0..count {
-            unsafe {
-                mouse_event(MOUSEEVENTF_LEFTDOWN, x_u32, y_u32, 0, 0);
-                mouse_event(MOUSEEVENTF_LEFTUP, x_u32, y_u32, 0, 0)
-            };
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
-        Ok(())
+        self.click_with(ClickOptions::new(MouseButton::Left).count(count))
     }
 
     pub fn right_click(&mut self) -> Result<(), MouseError> {
-        let new_position = &self.position;
-        if new_position.is_out_of_bounds() {
-            return Err(MouseError::OutOfBounds);
+        self.click_with(ClickOptions::new(MouseButton::Right))
+    }
+
+    pub fn click_button(&mut self, button: MouseButton) -> Result<(), MouseError> {
+        self.click_with(ClickOptions::new(button))
+    }
+
+    pub fn click_with(&mut self, options: ClickOptions) -> Result<(), MouseError> {
+        failsafe::check()?;
+        match options.position {
+            Some((x, y)) => self.move_to(x, y)?,
+            None => {
+                self.sync_position()?;
+                if self.is_out_of_bounds(&self.position) {
+                    return Err(MouseError::OutOfBounds);
+                }
+            }
+        }
+        for i in 0..options.count {
+            if i > 0 {
+                std::thread::sleep(options.interval);
+            }
+            self.tick()?;
+            if options.hold_duration.is_zero() {
+                self.batch(|batch| {
+                    batch.click(options.button);
+                })?;
+            } else {
+                self.simulate_mouse_button(options.button, ButtonAction::Press)?;
+                std::thread::sleep(options.hold_duration);
+                let ticked = self.tick();
+                self.release_after(options.button, ticked)?;
+                self.event_emitter.emit(&EventType::Click.to_string());
+            }
         }
-        let (x_u32, y_u32) = new_position.to_u32()?;
-        unsafe { mouse_event(MOUSEEVENTF_RIGHTDOWN, x_u32, y_u32, 0, 0) };
-        unsafe { mouse_event(MOUSEEVENTF_RIGHTUP, x_u32, y_u32, 0, 0) };
         Ok(())
     }
 
     pub fn scroll(&mut self, amount: i32) -> Result<(), MouseError> {
-        let new_position = &self.position;
-        if new_position.is_out_of_bounds() {
+        failsafe::check()?;
+        self.sync_position()?;
+        if self.is_out_of_bounds(&self.position) {
             return Err(MouseError::OutOfBounds);
         }
-        let (x_u32, y_u32) = new_position.to_u32()?;
-        unsafe { mouse_event(MOUSEEVENTF_WHEEL, x_u32, y_u32, amount as u32, 0) };
-        Ok(())
+        self.batch(|batch| {
+            batch.scroll(amount);
+        })
     }
 
     pub fn scroll_horizontal(&mut self, distance: i32) -> Result<(), MouseError> {
-        let params = [0, 0, 0, distance as u32];
-        unsafe {
-            mouse_event(MOUSEEVENTF_HWHEEL, 0, 0, params[3] as u32, 0);
-        }
-        Ok(())
+        failsafe::check()?;
+        self.batch(|batch| {
+            batch.scroll_horizontal(distance);
+        })
     }
 
     pub fn scroll_with_delay(
@@ -316,46 +693,89 @@ impl Mouse {
         amount: i32,
         delay: std::time::Duration,
     ) -> Result<(), MouseError> {
-        if self.position.is_out_of_bounds() {
+        failsafe::check()?;
+        self.sync_position()?;
+        if self.is_out_of_bounds(&self.position) {
             return Err(MouseError::OutOfBounds);
         }
-        let (x_u32, y_u32) = self.position.to_u32()?;
-        let step = amount.signum();
+        let direction = amount.signum();
         let mut remaining = amount.abs();
         while remaining != 0 {
-            let scroll_amount = std::cmp::min(remaining, step.abs());
-            let scroll_direction = if amount < 0 { -1 } else { 1 };
-            unsafe {
-                mouse_event(
-                    MOUSEEVENTF_WHEEL,
-                    x_u32,
-                    y_u32,
-                    (scroll_amount * scroll_direction) as u32,
-                    0,
-                )
-            };
-            remaining -= scroll_amount;
+            self.tick()?;
+            let step = remaining.min(WHEEL_DELTA as i32);
+            self.batch(|batch| {
+                batch.scroll(step * direction);
+            })?;
+            remaining -= step;
             std::thread::sleep(delay);
         }
         Ok(())
     }
 
+    pub fn scroll_by(&mut self, options: ScrollOptions) -> Result<(), MouseError> {
+        failsafe::check()?;
+        self.sync_position()?;
+        if self.is_out_of_bounds(&self.position) {
+            return Err(MouseError::OutOfBounds);
+        }
+        let total = options.amount.to_wheel_units(options.axis)?;
+        let frames = scroll::frame_count(options.duration);
+        let start_time = std::time::Instant::now();
+        for (frame, step) in scroll::wheel_steps(total, frames, options.easing)
+            .into_iter()
+            .enumerate()
+        {
+            self.tick()?;
+            if step != 0 {
+                self.batch(|batch| match options.axis {
+                    ScrollAxis::Vertical => {
+                        batch.scroll(step);
+                    }
+                    ScrollAxis::Horizontal => {
+                        batch.scroll_horizontal(step);
+                    }
+                })?;
+            }
+            // Sleep until the frame's deadline rather than for a fixed time so that the
+            // cost of injecting input does not stretch the animation.
+            let deadline = options.duration * (frame as u32 + 1) / frames;
+            if let Some(remaining) = deadline.checked_sub(start_time.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+        Ok(())
+    }
+
     pub fn drag(&mut self, distance_x: i32, distance_y: i32) -> Result<(), MouseError> {
-        let current_position = &self.position;
-        let new_position = current_position.offset(distance_x, distance_y);
+        failsafe::check()?;
+        self.sync_position()?;
+        let new_position = self.position.offset(distance_x, distance_y)?;
+        self.drag_from_cursor(new_position)
+    }
+
+    // Drags to an absolute position, from wherever the cursor is once it has been synced.
+    pub fn drag_to(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        if x < 0 || y < 0 {
+            return Err(MouseError::InvalidInput);
+        }
+        failsafe::check()?;
+        self.sync_position()?;
+        self.drag_from_cursor(MousePosition::new(x, y))
+    }
 
-        if current_position.is_out_of_bounds() || new_position.is_out_of_bounds() {
+    fn drag_from_cursor(&mut self, new_position: MousePosition) -> Result<(), MouseError> {
+        if self.is_out_of_bounds(&self.position) || self.is_out_of_bounds(&new_position) {
             return Err(MouseError::OutOfBounds);
         }
 
-        let (current_x, current_y) = current_position.to_u32()?;
         let (new_x, new_y) = new_position.to_u32()?;
 
-        unsafe { mouse_event(MOUSEEVENTF_LEFTDOWN, current_x, current_y, 0, 0) };
-        unsafe { mouse_event(MOUSEEVENTF_LEFTUP, new_x, new_y, 0, 0) };
-        self.move_to(new_x.try_into().unwrap(), new_y.try_into().unwrap())?;
-
-        Ok(())
+        self.batch(|batch| {
+            batch
+                .press(MouseButton::Left)
+                .move_to(new_x as i32, new_y as i32)
+                .release(MouseButton::Left);
+        })
     }
 
     pub fn drag_with_duration(
@@ -364,10 +784,12 @@ impl Mouse {
         distance_y: i32,
         duration: std::time::Duration,
     ) -> Result<(), MouseError> {
+        failsafe::check()?;
+        self.sync_position()?;
         let current_position = &self.position;
-        let new_position = current_position.offset(distance_x, distance_y);
+        let new_position = self.position.offset(distance_x, distance_y)?;
 
-        if current_position.is_out_of_bounds() || new_position.is_out_of_bounds() {
+        if self.is_out_of_bounds(current_position) || self.is_out_of_bounds(&new_position) {
             return Err(MouseError::OutOfBounds);
         }
 
@@ -382,11 +804,27 @@ impl Mouse {
 
         let total_distance = (distance_x.powi(2) + distance_y.powi(2)).sqrt();
 
-        unsafe { mouse_event(MOUSEEVENTF_LEFTDOWN, current_x, current_y, 0, 0) };
+        self.simulate_mouse_button(MouseButton::Left, ButtonAction::Press)?;
+        let moved = self.drag_steps(
+            (start_x, start_y),
+            (distance_x, distance_y),
+            total_distance,
+            duration,
+        );
+        self.release_at(MouseButton::Left, new_x as i32, new_y as i32, moved)
+    }
 
+    fn drag_steps(
+        &mut self,
+        (start_x, start_y): (f64, f64),
+        (distance_x, distance_y): (f64, f64),
+        total_distance: f64,
+        duration: std::time::Duration,
+    ) -> Result<(), MouseError> {
         let start_time = std::time::Instant::now();
         let mut last_progress = 0.0;
         loop {
+            self.tick()?;
             let elapsed = start_time.elapsed().as_secs_f64();
             let progress = elapsed / duration.as_secs_f64();
             if progress >= 1.0 {
@@ -396,73 +834,437 @@ impl Mouse {
                 let current_distance = total_distance * progress;
                 let current_x = ((current_distance / total_distance) * distance_x + start_x) as i32;
                 let current_y = ((current_distance / total_distance) * distance_y + start_y) as i32;
-                self.move_to(current_x, current_y)?;
+                self.batch(|batch| {
+                    batch.move_to(current_x, current_y);
+                })?;
                 last_progress = progress;
             }
             std::thread::sleep(std::time::Duration::from_millis(1));
         }
+        Ok(())
+    }
 
-        unsafe { mouse_event(MOUSEEVENTF_LEFTUP, new_x, new_y, 0, 0) };
-        self.position = new_position;
+    fn release_after(
+        &mut self,
+        button: MouseButton,
+        result: Result<(), MouseError>,
+    ) -> Result<(), MouseError> {
+        let released = self.simulate_mouse_button(button, ButtonAction::Release);
+        result.and(released)
+    }
+
+    // Ends a drag: if the animation got there, the last move and the release go in one
+    // batch so that the button comes up exactly on the target. Otherwise, or if that
+    // batch fails, the button is still released wherever the cursor is.
+    fn release_at(
+        &mut self,
+        button: MouseButton,
+        x: i32,
+        y: i32,
+        result: Result<(), MouseError>,
+    ) -> Result<(), MouseError> {
+        let dropped = result.and_then(|()| {
+            self.batch(|batch| {
+                batch.move_to(x, y).release(button);
+            })
+        });
+        if self.held_buttons.contains(&button) {
+            return self.release_after(button, dropped);
+        }
+        dropped
+    }
+
+    pub fn simulate_mouse_button(
+        &mut self,
+        button: MouseButton,
+        action: ButtonAction,
+    ) -> Result<(), MouseError> {
+        self.batch(|batch| match action {
+            ButtonAction::Press => {
+                batch.press(button);
+            }
+            ButtonAction::Release => {
+                batch.release(button);
+            }
+        })
+    }
+
+    pub fn batch<F>(&mut self, build: F) -> Result<(), MouseError>
+    where
+        F: FnOnce(&mut InputBatch),
+    {
+        let mut batch = InputBatch::for_screen(self.get_screen_size());
+        build(&mut batch);
+        self.submit(batch)
+    }
 
+    fn submit(&mut self, batch: InputBatch) -> Result<(), MouseError> {
+        if let Some(err) = batch.error {
+            return Err(err);
+        }
+        // Releasing is always allowed so that an aborted operation can clean up.
+        if !batch.releases_only {
+            failsafe::check()?;
+        }
+        self.backend.send(&batch.events)?;
+        for (button, action) in batch.buttons {
+            match action {
+                ButtonAction::Press if !self.held_buttons.contains(&button) => {
+                    self.held_buttons.push(button)
+                }
+                ButtonAction::Press => {}
+                ButtonAction::Release => self.held_buttons.retain(|&held| held != button),
+            }
+        }
+        if let Some(position) = batch.position {
+            self.position = position;
+            self.seen_moves = tracker::physical_moves();
+        }
+        for _ in 0..batch.clicks {
+            self.event_emitter.emit(&EventType::Click.to_string());
+        }
         Ok(())
     }
 
-    pub fn simulate_mouse_button(&self, button: MouseButton, action: ButtonAction) {
-        let (down_flag, up_flag) = match button {
-            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
-            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
-            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
-        };
-        let flags = match action {
-            ButtonAction::Press => down_flag,
-            ButtonAction::Release => up_flag,
-        };
-        let mut input = INPUT {
-            type_: INPUT_MOUSE,
-            u: unsafe { std::mem::zeroed() },
-        };
-        unsafe {
-            input.u.mi_mut().dwFlags = flags;
-            SendInput(1, &mut input, std::mem::size_of::<INPUT>() as i32);
+    pub fn hold(&mut self, button: MouseButton) -> Result<HeldButton<'_>, MouseError> {
+        self.simulate_mouse_button(button, ButtonAction::Press)?;
+        Ok(HeldButton {
+            mouse: self,
+            button,
+        })
+    }
+
+    pub fn held_buttons(&self) -> &[MouseButton] {
+        &self.held_buttons
+    }
+
+    pub fn release_all(&mut self) -> Result<(), MouseError> {
+        let mut result = Ok(());
+        while let Some(&button) = self.held_buttons.last() {
+            let released = self.simulate_mouse_button(button, ButtonAction::Release);
+            // Forget the button even if the release failed so that we cannot loop forever.
+            self.held_buttons.retain(|&held| held != button);
+            if result.is_ok() {
+                result = released;
+            }
         }
+        result
     }
 
     pub fn is_left_button_pressed(&self) -> bool {
-        let state = unsafe { GetAsyncKeyState(VK_LBUTTON) } as u32;
-        state & 0x8001 != 0
+        self.button_state(MouseButton::Left)
+    }
+
+    pub fn is_right_button_pressed(&self) -> bool {
+        self.button_state(MouseButton::Right)
+    }
+
+    pub fn is_middle_button_pressed(&self) -> bool {
+        self.button_state(MouseButton::Middle)
+    }
+
+    pub fn is_back_button_pressed(&self) -> bool {
+        self.button_state(MouseButton::Back)
+    }
+
+    pub fn is_forward_button_pressed(&self) -> bool {
+        self.button_state(MouseButton::Forward)
+    }
+
+    // Like injection, this works in terms of physical buttons, so swapped buttons in the
+    // system settings do not change the result.
+    pub fn button_state(&self, button: MouseButton) -> bool {
+        self.backend.button_state(button)
+    }
+
+    // The named buttons, and any later ones this Mouse holds on backends that have them.
+    pub fn pressed_buttons(&self) -> Vec<MouseButton> {
+        let others = self
+            .held_buttons
+            .iter()
+            .copied()
+            .filter(|button| button.normalize().is_err());
+        [
+            MouseButton::Left,
+            MouseButton::Right,
+            MouseButton::Middle,
+            MouseButton::Back,
+            MouseButton::Forward,
+        ]
+        .into_iter()
+        .chain(others)
+        .filter(|&button| self.button_state(button))
+        .collect()
+    }
+
+    pub fn get_screen_size(&self) -> (i32, i32) {
+        self.backend.screen_size()
+    }
+
+    pub(crate) fn is_out_of_bounds(&self, position: &MousePosition) -> bool {
+        position.is_outside(self.get_screen_size())
+    }
+
+    pub fn in_window<'a>(&'a mut self, window: &'a Window) -> WindowMouse<'a> {
+        WindowMouse {
+            mouse: self,
+            window,
+        }
+    }
+
+    pub fn print_mouse_position(&self) {
+        if let Err(err) = Inspector::new().run(self) {
+            eprintln!("{}", err);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+const ANIMATION_FRAMES: u32 = 100;
+
+// Async counterparts of the animated methods. They wait on a timer instead of sleeping,
+// so they can be raced against timeouts; dropping one stops it between frames, and a
+// drag that is dropped midway releases its button.
+#[cfg(feature = "async")]
+impl Mouse {
+    pub async fn hover_async(
+        &mut self,
+        x: i32,
+        y: i32,
+        duration: Duration,
+    ) -> Result<(), MouseError> {
+        if x < 0 || y < 0 || duration.as_secs() == 0 {
+            return Err(MouseError::InvalidInput);
+        }
+        if self.is_out_of_bounds(&MousePosition::new(x, y)) {
+            return Err(MouseError::OutOfBounds);
+        }
+
+        self.sync_position()?;
+        let (start_x, start_y) = self.get_mouse_position();
+        let distance_x = (x - start_x) as f64;
+        let distance_y = (y - start_y) as f64;
+        self.animate(duration, |mouse, progress| {
+            let current_x = (start_x as f64 + distance_x * progress) as i32;
+            let current_y = (start_y as f64 + distance_y * progress) as i32;
+            mouse.move_to(current_x, current_y)
+        })
+        .await?;
+        self.move_to(x, y)
+    }
+
+    pub async fn move_in_circle_async(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        duration: Duration,
+    ) -> Result<(), MouseError> {
+        if radius <= 0 || duration.as_secs() == 0 {
+            return Err(MouseError::InvalidInput);
+        }
+
+        let point = |progress: f64| {
+            let angle = progress * 2.0 * std::f64::consts::PI;
+            (
+                center_x + (radius as f64 * angle.cos()) as i32,
+                center_y + (radius as f64 * angle.sin()) as i32,
+            )
+        };
+        self.animate(duration, |mouse, progress| {
+            let (x, y) = point(progress);
+            mouse.move_to(x, y)
+        })
+        .await?;
+        let (x, y) = point(1.0);
+        self.move_to(x, y)
+    }
+
+    pub async fn drag_with_duration_async(
+        &mut self,
+        distance_x: i32,
+        distance_y: i32,
+        duration: Duration,
+    ) -> Result<(), MouseError> {
+        failsafe::check()?;
+        self.sync_position()?;
+        let new_position = self.position.offset(distance_x, distance_y)?;
+
+        if self.is_out_of_bounds(&self.position) || self.is_out_of_bounds(&new_position) {
+            return Err(MouseError::OutOfBounds);
+        }
+
+        let (start_x, start_y) = self.position.to_u32()?;
+        let (end_x, end_y) = new_position.to_u32()?;
+        let distance_x = end_x as f64 - start_x as f64;
+        let distance_y = end_y as f64 - start_y as f64;
+
+        // The guard releases the button if this future is dropped before it finishes.
+        let mut held = self.hold(MouseButton::Left)?;
+        let moved = held
+            .animate(duration, |mouse, progress| {
+                let current_x = (start_x as f64 + distance_x * progress) as i32;
+                let current_y = (start_y as f64 + distance_y * progress) as i32;
+                mouse.batch(|batch| {
+                    batch.move_to(current_x, current_y);
+                })
+            })
+            .await;
+        held.release_at(end_x as i32, end_y as i32, moved)
+    }
+
+    pub async fn scroll_with_delay_async(
+        &mut self,
+        amount: i32,
+        delay: Duration,
+    ) -> Result<(), MouseError> {
+        failsafe::check()?;
+        self.sync_position()?;
+        if self.is_out_of_bounds(&self.position) {
+            return Err(MouseError::OutOfBounds);
+        }
+        let direction = amount.signum();
+        let mut remaining = amount.abs();
+        while remaining != 0 {
+            self.tick()?;
+            let step = remaining.min(WHEEL_DELTA as i32);
+            self.batch(|batch| {
+                batch.scroll(step * direction);
+            })?;
+            remaining -= step;
+            timer::sleep(delay).await;
+        }
+        Ok(())
+    }
+
+    // Calls `frame` with the progress of each frame, then waits for the next frame's
+    // deadline so that slow input does not stretch the animation.
+    async fn animate<F>(&mut self, duration: Duration, mut frame: F) -> Result<(), MouseError>
+    where
+        F: FnMut(&mut Mouse, f64) -> Result<(), MouseError>,
+    {
+        let start_time = std::time::Instant::now();
+        for step in 0..ANIMATION_FRAMES {
+            self.tick()?;
+            frame(self, step as f64 / ANIMATION_FRAMES as f64)?;
+            timer::sleep_until(start_time + duration * (step + 1) / ANIMATION_FRAMES).await;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Mouse {
+    fn drop(&mut self) {
+        let _ = self.release_all();
+    }
+}
+
+pub struct HeldButton<'a> {
+    mouse: &'a mut Mouse,
+    button: MouseButton,
+}
+
+impl HeldButton<'_> {
+    pub fn button(&self) -> MouseButton {
+        self.button
+    }
+
+    // Releases now and reports the result, which dropping the guard cannot.
+    pub fn release(self) -> Result<(), MouseError> {
+        let mut held = ManuallyDrop::new(self);
+        let button = held.button;
+        held.mouse
+            .simulate_mouse_button(button, ButtonAction::Release)
+    }
+
+    #[cfg(feature = "async")]
+    fn release_at(self, x: i32, y: i32, result: Result<(), MouseError>) -> Result<(), MouseError> {
+        let mut held = ManuallyDrop::new(self);
+        let button = held.button;
+        held.mouse.release_at(button, x, y, result)
+    }
+}
+
+impl Deref for HeldButton<'_> {
+    type Target = Mouse;
+
+    fn deref(&self) -> &Mouse {
+        self.mouse
+    }
+}
+
+impl DerefMut for HeldButton<'_> {
+    fn deref_mut(&mut self) -> &mut Mouse {
+        self.mouse
+    }
+}
+
+impl Drop for HeldButton<'_> {
+    fn drop(&mut self) {
+        let _ = self
+            .mouse
+            .simulate_mouse_button(self.button, ButtonAction::Release);
+    }
+}
+
+pub struct WindowMouse<'a> {
+    mouse: &'a mut Mouse,
+    window: &'a Window,
+}
+
+impl WindowMouse<'_> {
+    fn to_screen(&self, x: i32, y: i32) -> Result<MousePosition, MouseError> {
+        if x < 0 || y < 0 {
+            return Err(MouseError::InvalidInput);
+        }
+        // Resolved on every call so that actions follow the window if it has moved.
+        let client_rect = self.window.client_rect()?;
+        let position = MousePosition::new(client_rect.left + x, client_rect.top + y);
+        if position.is_out_of_bounds_of(&client_rect) || self.mouse.is_out_of_bounds(&position) {
+            return Err(MouseError::OutOfBounds);
+        }
+        Ok(position)
+    }
+
+    pub fn get_mouse_position(&mut self) -> Result<(i32, i32), MouseError> {
+        let client_rect = self.window.client_rect()?;
+        let (x, y) = self.mouse.get_current_position()?;
+        Ok((x - client_rect.left, y - client_rect.top))
+    }
+
+    pub fn move_to(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        let position = self.to_screen(x, y)?;
+        self.mouse.move_to(position.x, position.y)
+    }
+
+    pub fn hover(
+        &mut self,
+        x: i32,
+        y: i32,
+        duration: std::time::Duration,
+    ) -> Result<(), MouseError> {
+        let position = self.to_screen(x, y)?;
+        self.mouse.hover(position.x, position.y, duration)
     }
 
-    pub fn is_right_button_pressed(&self) -> bool {
-        let state = unsafe { GetAsyncKeyState(VK_RBUTTON) } as u32;
-        state & 0x8001 != 0
+    pub fn click_at(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        self.move_to(x, y)?;
+        self.mouse.click()
     }
 
-    pub fn is_middle_button_pressed(&self) -> bool {
-        let state = unsafe { GetAsyncKeyState(VK_MBUTTON) } as u32;
-        state & 0x8001 != 0
+    pub fn double_click_at(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        self.move_to(x, y)?;
+        self.mouse.double_click()
     }
 
-    pub fn get_screen_size(&self) -> (i32, i32) {
-        let screen_width = unsafe { GetSystemMetrics(SM_CXSCREEN) };
-        let screen_height = unsafe { GetSystemMetrics(SM_CYSCREEN) };
-        (screen_width, screen_height)
+    pub fn right_click_at(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        self.move_to(x, y)?;
+        self.mouse.right_click()
     }
 
-    pub fn print_mouse_position(&self) {
-        println!("Press q to quit.");
-        const VK_Q: i32 = 0x51;
-        loop {
-            let mut point = POINT { x: 0, y: 0 };
-            unsafe { GetCursorPos(&mut point) };
-            let position_str = format!("({}, {})", point.x, point.y);
-            print!("\r{}", position_str);
-            std::io::stdout().flush().unwrap();
-            if unsafe { GetAsyncKeyState(VK_Q) } as u32 & 0x8000 != 0 {
-                break;
-            }
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
+    pub fn drag_to(&mut self, x: i32, y: i32) -> Result<(), MouseError> {
+        let position = self.to_screen(x, y)?;
+        self.mouse.drag_to(position.x, position.y)
     }
 }
 
@@ -471,6 +1273,7 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     use super::*;
+    use crate::scroll::{Easing, ScrollAmount};
 
     #[test]
     fn test_mouse_position_new() {
@@ -479,6 +1282,15 @@ mod tests {
         assert_eq!(position.y, 20);
     }
 
+    #[test]
+    fn test_is_out_of_bounds_of_rect() {
+        let rect = Rect::new(100, 100, 300, 200);
+        assert!(!MousePosition::new(100, 100).is_out_of_bounds_of(&rect));
+        assert!(!MousePosition::new(299, 199).is_out_of_bounds_of(&rect));
+        assert!(MousePosition::new(300, 150).is_out_of_bounds_of(&rect));
+        assert!(MousePosition::new(50, 150).is_out_of_bounds_of(&rect));
+    }
+
     #[test]
     fn test_mouse_position_default() {
         let position = MousePosition::default();
@@ -488,20 +1300,20 @@ mod tests {
 
     #[test]
     fn test_is_out_of_bounds() {
+        let screen = input::screen_size();
         let mouse_pos = MousePosition::new(-10, 20);
-        assert!(mouse_pos.is_out_of_bounds());
+        assert!(mouse_pos.is_outside(screen));
 
         let mouse_pos = MousePosition::new(10, -20);
-        assert!(mouse_pos.is_out_of_bounds());
+        assert!(mouse_pos.is_outside(screen));
 
-        let screen_width = unsafe { GetSystemMetrics(SM_CXSCREEN) };
-        let screen_height = unsafe { GetSystemMetrics(SM_CYSCREEN) };
+        let (screen_width, screen_height) = screen;
 
         let mouse_pos = MousePosition::new(screen_width + 10, screen_height + 20);
-        assert!(mouse_pos.is_out_of_bounds());
+        assert!(mouse_pos.is_outside(screen));
 
         let mouse_pos = MousePosition::new(screen_width - 10, screen_height - 20);
-        assert!(!mouse_pos.is_out_of_bounds());
+        assert!(!mouse_pos.is_outside(screen));
     }
 
     #[test]
@@ -528,16 +1340,20 @@ mod tests {
     #[test]
     fn test_mouse_position_offset() {
         let position = MousePosition::new(100, 100);
-        let offset_position = position.offset(50, -50);
+        let offset_position = position.offset(50, -50).unwrap();
 
         assert_eq!(offset_position.x, 150);
         assert_eq!(offset_position.y, 50);
+        assert!(matches!(
+            position.offset(i32::MAX, 0),
+            Err(MouseError::OutOfBounds)
+        ));
     }
 
     #[test]
     fn test_mouse_default_position() {
         let position = MousePosition::default();
-        assert!(!position.is_out_of_bounds());
+        assert!(!position.is_outside(input::screen_size()));
     }
 
     #[test]
@@ -557,7 +1373,7 @@ mod tests {
     #[test]
     fn test_get_current_position() {
         let mut mouse = Mouse::new();
-        let (x, y) = mouse.get_current_position();
+        let (x, y) = mouse.get_current_position().unwrap();
         assert!(x >= 0 && y >= 0, "Mouse position should be non-negative");
     }
 
@@ -721,6 +1537,45 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_scroll_with_delay_steps_by_notch() {
+        let mut mouse = Mouse::new();
+        let start = std::time::Instant::now();
+        mouse
+            .scroll_with_delay(240, std::time::Duration::from_millis(10))
+            .unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_scroll_by_smooth() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(800, 800).unwrap();
+        let options = ScrollOptions::new(ScrollAmount::Lines(-3.0))
+            .duration(Duration::from_millis(100))
+            .easing(Easing::EaseOut);
+        let start = std::time::Instant::now();
+        mouse.scroll_by(options).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_scroll_by_horizontal_pixels() {
+        let mut mouse = Mouse::new();
+        let options = ScrollOptions::new(ScrollAmount::Pixels(30.0)).axis(ScrollAxis::Horizontal);
+        assert!(mouse.scroll_by(options).is_ok());
+    }
+
+    #[test]
+    fn test_scroll_by_invalid_amount() {
+        let mut mouse = Mouse::new();
+        let options = ScrollOptions::new(ScrollAmount::Notches(f64::INFINITY));
+        assert!(matches!(
+            mouse.scroll_by(options),
+            Err(MouseError::InvalidInput)
+        ));
+    }
+
     #[test]
     fn test_drag_within_bounds() {
         let mut mouse = Mouse::new();
@@ -733,8 +1588,10 @@ mod tests {
 
     #[test]
     fn test_simulate_left_button_press() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Left, ButtonAction::Press);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Press)
+            .unwrap();
         assert!(
             mouse.is_left_button_pressed(),
             "Left button should be pressed"
@@ -743,8 +1600,10 @@ mod tests {
 
     #[test]
     fn test_simulate_left_button_release() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Left, ButtonAction::Release);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Release)
+            .unwrap();
         assert!(
             !mouse.is_left_button_pressed(),
             "Left button should be released"
@@ -753,8 +1612,10 @@ mod tests {
 
     #[test]
     fn test_simulate_right_button_press() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Right, ButtonAction::Press);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Right, ButtonAction::Press)
+            .unwrap();
         assert!(
             mouse.is_right_button_pressed(),
             "Right button should be pressed"
@@ -763,8 +1624,10 @@ mod tests {
 
     #[test]
     fn test_simulate_right_button_release() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Right, ButtonAction::Release);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Right, ButtonAction::Release)
+            .unwrap();
         assert!(
             !mouse.is_right_button_pressed(),
             "Right button should be released"
@@ -773,8 +1636,10 @@ mod tests {
 
     #[test]
     fn test_simulate_middle_button_press() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Middle, ButtonAction::Press);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Middle, ButtonAction::Press)
+            .unwrap();
         assert!(
             mouse.is_middle_button_pressed(),
             "Middle button should be pressed"
@@ -783,8 +1648,10 @@ mod tests {
 
     #[test]
     fn test_simulate_middle_button_release() {
-        let mouse = Mouse::new();
-        mouse.simulate_mouse_button(MouseButton::Middle, ButtonAction::Release);
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Middle, ButtonAction::Release)
+            .unwrap();
         assert!(
             !mouse.is_middle_button_pressed(),
             "Middle button should be released"
@@ -818,8 +1685,8 @@ mod tests {
     #[test]
     fn test_drag_and_drop_with_duration() {
         let mut mouse = Mouse::new();
-        let start_x = mouse.position.x;
-        let start_y = mouse.position.y;
+        mouse.move_to(100, 100).unwrap();
+        let (start_x, start_y) = (100, 100);
         let distance_x = 100;
         let distance_y = 50;
         let duration = std::time::Duration::from_secs(1);
@@ -828,17 +1695,18 @@ mod tests {
             .drag_with_duration(distance_x, distance_y, duration)
             .unwrap();
 
-        let end_x = mouse.position.x;
-        let end_y = mouse.position.y;
-        assert_eq!(end_x, start_x + distance_x);
-        assert_eq!(end_y, start_y + distance_y);
+        assert_eq!(
+            mouse.get_current_position().unwrap(),
+            (start_x + distance_x, start_y + distance_y)
+        );
+        assert!(!mouse.button_state(MouseButton::Left));
     }
 
     #[test]
     fn test_drag_and_drop_with_duration_time_elapsed() {
         let mut mouse = Mouse::new();
-        let start_x = mouse.position.x;
-        let start_y = mouse.position.y;
+        mouse.move_to(100, 100).unwrap();
+        let (start_x, start_y) = (100, 100);
         let distance_x = 100;
         let distance_y = 50;
         let duration = std::time::Duration::from_secs(1);
@@ -852,9 +1720,462 @@ mod tests {
         let elapsed_time = end_time - start_time;
         assert!(elapsed_time >= duration);
 
-        let end_x = mouse.position.x;
-        let end_y = mouse.position.y;
-        assert_eq!(end_x, start_x + distance_x);
-        assert_eq!(end_y, start_y + distance_y);
+        assert_eq!(
+            mouse.get_current_position().unwrap(),
+            (start_x + distance_x, start_y + distance_y)
+        );
+        assert!(!mouse.button_state(MouseButton::Left));
+    }
+
+    #[test]
+    fn test_long_drag_is_not_mistaken_for_external_movement() {
+        let mut mouse = Mouse::new();
+        mouse.set_external_movement(ExternalMovement::Abort);
+        mouse.move_to(100, 100).unwrap();
+        mouse
+            .drag_with_duration(400, 200, Duration::from_millis(500))
+            .unwrap();
+        mouse.move_relative(10, 0).unwrap();
+        assert_eq!(mouse.get_current_position().unwrap(), (510, 300));
+    }
+
+    #[test]
+    fn test_in_window_move_to() {
+        let window = Window::foreground().unwrap();
+        let client_rect = window.client_rect().unwrap();
+        let mut mouse = Mouse::new();
+        mouse.in_window(&window).move_to(10, 20).unwrap();
+        assert_eq!(
+            mouse.get_mouse_position(),
+            (client_rect.left + 10, client_rect.top + 20)
+        );
+        assert_eq!(
+            mouse.in_window(&window).get_mouse_position().unwrap(),
+            (10, 20)
+        );
+    }
+
+    #[test]
+    fn test_in_window_drag_to_after_external_movement() {
+        let window = Window::foreground().unwrap();
+        let client_rect = window.client_rect().unwrap();
+        let mut mouse = Mouse::new();
+        mouse.in_window(&window).move_to(10, 10).unwrap();
+        input::set_cursor_pos(client_rect.left + 30, client_rect.top + 30).unwrap();
+        mouse.in_window(&window).drag_to(20, 20).unwrap();
+        assert_eq!(
+            mouse.get_current_position().unwrap(),
+            (client_rect.left + 20, client_rect.top + 20)
+        );
+    }
+
+    #[test]
+    fn test_in_window_out_of_bounds() {
+        let window = Window::foreground().unwrap();
+        let client_rect = window.client_rect().unwrap();
+        let mut mouse = Mouse::new();
+        let result = mouse
+            .in_window(&window)
+            .click_at(client_rect.width(), client_rect.height());
+        assert!(matches!(result, Err(MouseError::OutOfBounds)));
+
+        let result = mouse.in_window(&window).move_to(-1, 0);
+        assert!(matches!(result, Err(MouseError::InvalidInput)));
+    }
+
+    #[test]
+    fn test_pressed_buttons_are_tracked() {
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Press)
+            .unwrap();
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Press)
+            .unwrap();
+        assert_eq!(mouse.held_buttons(), &[MouseButton::Left]);
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Release)
+            .unwrap();
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Right, ButtonAction::Press)
+            .unwrap();
+        mouse
+            .simulate_mouse_button(MouseButton::Middle, ButtonAction::Press)
+            .unwrap();
+        mouse.release_all().unwrap();
+        assert!(mouse.held_buttons().is_empty());
+        assert!(!mouse.is_right_button_pressed());
+        assert!(!mouse.is_middle_button_pressed());
+    }
+
+    #[test]
+    fn test_drop_releases_held_buttons() {
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Right, ButtonAction::Press)
+            .unwrap();
+        drop(mouse);
+        assert!(!Mouse::new().is_right_button_pressed());
+    }
+
+    #[test]
+    fn test_hold_guard_releases_on_drop() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        {
+            let mut held = mouse.hold(MouseButton::Left).unwrap();
+            assert_eq!(held.button(), MouseButton::Left);
+            held.move_to(150, 150).unwrap();
+            assert_eq!(held.held_buttons(), &[MouseButton::Left]);
+        }
+        assert!(mouse.held_buttons().is_empty());
+        assert!(!mouse.is_left_button_pressed());
+    }
+
+    #[test]
+    fn test_drag_releases_button_on_error() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        let (width, height) = mouse.get_screen_size();
+        mouse
+            .simulate_mouse_button(MouseButton::Left, ButtonAction::Press)
+            .unwrap();
+        let result = mouse.drag_steps(
+            (100.0, 100.0),
+            (width as f64, height as f64),
+            1.0,
+            std::time::Duration::from_secs(1),
+        );
+        assert!(result.is_err());
+        let result = mouse.release_after(MouseButton::Left, result);
+        assert!(matches!(result, Err(MouseError::OutOfBounds)));
+        assert!(mouse.held_buttons().is_empty());
+        assert!(!mouse.is_left_button_pressed());
+    }
+
+    #[test]
+    fn test_batch_click_and_drag() {
+        let mut mouse = Mouse::new();
+        mouse
+            .batch(|batch| {
+                batch
+                    .move_to(100, 100)
+                    .press(MouseButton::Left)
+                    .move_to(140, 120)
+                    .release(MouseButton::Left);
+            })
+            .unwrap();
+        assert_eq!(mouse.get_mouse_position(), (140, 120));
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_batch_tracks_pressed_buttons() {
+        let mut mouse = Mouse::new();
+        mouse
+            .batch(|batch| {
+                batch.press(MouseButton::Right);
+            })
+            .unwrap();
+        assert_eq!(mouse.held_buttons(), &[MouseButton::Right]);
+        mouse.release_all().unwrap();
+    }
+
+    #[test]
+    fn test_batch_error_sends_nothing() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        let result = mouse.batch(|batch| {
+            batch.press(MouseButton::Left).move_to(-5, 10);
+        });
+        assert!(matches!(result, Err(MouseError::InvalidInput)));
+        assert!(mouse.held_buttons().is_empty());
+        assert_eq!(mouse.get_mouse_position(), (100, 100));
+    }
+
+    #[test]
+    fn test_other_button_numbering() {
+        assert_eq!(
+            MouseButton::Other(1).normalize().unwrap(),
+            MouseButton::Left
+        );
+        assert_eq!(
+            MouseButton::Other(4).normalize().unwrap(),
+            MouseButton::Back
+        );
+        assert_eq!(
+            MouseButton::Other(5).normalize().unwrap(),
+            MouseButton::Forward
+        );
+        assert!(matches!(
+            MouseButton::Other(6).normalize(),
+            Err(MouseError::UnsupportedButton(6))
+        ));
+    }
+
+    #[test]
+    fn test_button_flags_for_extra_buttons() {
+        let (down, up, data) = button_flags(MouseButton::Back).unwrap();
+        assert_eq!((down, up, data), (MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, 1));
+        let (_, _, data) = button_flags(MouseButton::Forward).unwrap();
+        assert_eq!(data, 2);
+        assert!(button_flags(MouseButton::Other(9)).is_err());
+    }
+
+    #[test]
+    fn test_simulate_back_button() {
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Back, ButtonAction::Press)
+            .unwrap();
+        assert!(mouse.is_back_button_pressed());
+        mouse
+            .simulate_mouse_button(MouseButton::Back, ButtonAction::Release)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_simulate_forward_button() {
+        let mut mouse = Mouse::new();
+        mouse
+            .simulate_mouse_button(MouseButton::Forward, ButtonAction::Press)
+            .unwrap();
+        assert!(mouse.is_forward_button_pressed());
+        mouse
+            .simulate_mouse_button(MouseButton::Forward, ButtonAction::Release)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_simulate_unsupported_button() {
+        let mut mouse = Mouse::new();
+        let result = mouse.simulate_mouse_button(MouseButton::Other(7), ButtonAction::Press);
+        assert!(matches!(result, Err(MouseError::UnsupportedButton(7))));
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_click_button() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        assert!(mouse.click_button(MouseButton::Middle).is_ok());
+        assert!(mouse.click_button(MouseButton::Other(9)).is_err());
+    }
+
+    #[test]
+    fn test_click_options_defaults() {
+        let options = ClickOptions::default();
+        assert_eq!(options.button, MouseButton::Left);
+        assert_eq!(options.count, 1);
+        assert!(options.interval > Duration::ZERO);
+        assert!(options.interval.as_millis() < unsafe { GetDoubleClickTime() } as u128);
+        assert_eq!(options.hold_duration, Duration::ZERO);
+        assert_eq!(options.position, None);
+    }
+
+    #[test]
+    fn test_click_with_emits_every_click() {
+        let mut mouse = Mouse::new();
+        let counter = Arc::new(Mutex::new(0));
+        let counter_clone = counter.clone();
+        mouse.on(EventType::Click, move || {
+            *counter_clone.lock().unwrap() += 1;
+        });
+        let options = ClickOptions::new(MouseButton::Right)
+            .count(2)
+            .interval(Duration::from_millis(10))
+            .position(100, 100);
+        mouse.click_with(options).unwrap();
+        mouse.multi_click(3).unwrap();
+        assert_eq!(*counter.lock().unwrap(), 5);
+        assert_eq!(mouse.get_mouse_position(), (100, 100));
+    }
+
+    #[test]
+    fn test_click_with_hold_duration() {
+        let mut mouse = Mouse::new();
+        let options = ClickOptions::new(MouseButton::Middle)
+            .hold_duration(Duration::from_millis(50))
+            .position(100, 100);
+        let start = std::time::Instant::now();
+        mouse.click_with(options).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_click_with_invalid_position() {
+        let mut mouse = Mouse::new();
+        let options = ClickOptions::default().position(-1, 10);
+        assert!(matches!(
+            mouse.click_with(options),
+            Err(MouseError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn test_button_state() {
+        let mut mouse = Mouse::new();
+        assert!(!mouse.button_state(MouseButton::Other(9)));
+        mouse
+            .simulate_mouse_button(MouseButton::Middle, ButtonAction::Press)
+            .unwrap();
+        assert!(mouse.button_state(MouseButton::Middle));
+        assert!(mouse.button_state(MouseButton::Other(3)));
+        assert!(mouse.pressed_buttons().contains(&MouseButton::Middle));
+        mouse
+            .simulate_mouse_button(MouseButton::Middle, ButtonAction::Release)
+            .unwrap();
+        assert!(!mouse.button_state(MouseButton::Middle));
+        assert!(!mouse.pressed_buttons().contains(&MouseButton::Middle));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_dropped_drag_releases_button() {
+        use std::{
+            future::Future,
+            task::{Context, Waker},
+        };
+
+        // Runtimes such as tokio only spawn futures that can move between threads.
+        fn assert_send<T: Send>(_: &T) {}
+
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        let mut drag = Box::pin(mouse.drag_with_duration_async(50, 50, Duration::from_secs(1)));
+        assert_send(&drag);
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(drag.as_mut().poll(&mut cx).is_pending());
+        drop(drag);
+        assert!(mouse.held_buttons().is_empty());
+        assert!(!mouse.button_state(MouseButton::Left));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_drag_drops_on_target() {
+        use std::{
+            future::Future,
+            task::{Context, Poll, Waker},
+        };
+
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        let mut drag =
+            Box::pin(mouse.drag_with_duration_async(300, 150, Duration::from_millis(500)));
+        let mut cx = Context::from_waker(Waker::noop());
+        let result = loop {
+            if let Poll::Ready(result) = drag.as_mut().poll(&mut cx) {
+                break result;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        drop(drag);
+        result.unwrap();
+        assert_eq!(mouse.get_current_position().unwrap(), (400, 250));
+        assert!(!mouse.button_state(MouseButton::Left));
+    }
+
+    #[test]
+    fn test_cancelled_hover_stops_where_it_is() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        let token = CancellationToken::new();
+        mouse.set_cancellation_token(Some(token.clone()));
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            token.cancel();
+        });
+        let result = mouse.hover(500, 100, Duration::from_secs(2));
+        canceller.join().unwrap();
+        assert!(matches!(result, Err(MouseError::Cancelled)));
+        let (x, _) = mouse.get_mouse_position();
+        assert!(x > 100 && x < 500);
+    }
+
+    #[test]
+    fn test_pre_cancelled_click_sends_nothing() {
+        let mut mouse = Mouse::new();
+        let clicks = Arc::new(Mutex::new(0));
+        let counter = clicks.clone();
+        mouse.on(EventType::Click, move || *counter.lock().unwrap() += 1);
+        let token = CancellationToken::new();
+        token.cancel();
+        mouse.set_cancellation_token(Some(token));
+        assert!(matches!(
+            mouse.multi_click(1000),
+            Err(MouseError::Cancelled)
+        ));
+        assert_eq!(*clicks.lock().unwrap(), 0);
+        assert!(mouse.held_buttons().is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_click_releases_button() {
+        let mut mouse = Mouse::new();
+        let token = CancellationToken::new();
+        token.cancel();
+        mouse.set_cancellation_token(Some(token));
+        let options = ClickOptions::new(MouseButton::Middle)
+            .count(1000)
+            .hold_duration(Duration::from_millis(10));
+        assert!(matches!(
+            mouse.click_with(options),
+            Err(MouseError::Cancelled)
+        ));
+        assert!(mouse.held_buttons().is_empty());
+        assert!(!mouse.button_state(MouseButton::Middle));
+    }
+
+    #[test]
+    fn test_query_tracking_follows_external_movement() {
+        let mut mouse = Mouse::new();
+        mouse.move_to(100, 100).unwrap();
+        input::set_cursor_pos(300, 300).unwrap();
+        mouse.move_relative(10, 0).unwrap();
+        assert_eq!(mouse.get_mouse_position(), (310, 300));
+    }
+
+    #[test]
+    fn test_cached_tracking_ignores_external_movement() {
+        let mut mouse = Mouse::new();
+        mouse
+            .set_position_tracking(PositionTracking::Cached)
+            .unwrap();
+        mouse.move_to(100, 100).unwrap();
+        input::set_cursor_pos(300, 300).unwrap();
+        mouse.move_relative(10, 0).unwrap();
+        assert_eq!(mouse.get_mouse_position(), (110, 100));
+    }
+
+    #[test]
+    fn test_external_movement_aborts_once() {
+        let mut mouse = Mouse::new();
+        mouse.set_external_movement(ExternalMovement::Abort);
+        mouse.move_to(100, 100).unwrap();
+        input::set_cursor_pos(300, 300).unwrap();
+        match mouse.move_relative(10, 0) {
+            Err(MouseError::ExternalMovement { expected, actual }) => {
+                assert_eq!(expected, (100, 100));
+                assert_eq!(actual, (300, 300));
+            }
+            other => panic!("expected ExternalMovement, got {:?}", other),
+        }
+        mouse.move_relative(10, 0).unwrap();
+        assert_eq!(mouse.get_mouse_position(), (310, 300));
+    }
+
+    #[test]
+    fn test_parse_mouse_button() {
+        assert_eq!("Right".parse::<MouseButton>().unwrap(), MouseButton::Right);
+        assert_eq!("4".parse::<MouseButton>().unwrap(), MouseButton::Other(4));
+        assert!("thumb".parse::<MouseButton>().is_err());
     }
 }
@@ -0,0 +1,290 @@
+use std::{error::Error, fmt, io, mem, ptr};
+use winapi::{
+    shared::{
+        minwindef::{BOOL, LPARAM, TRUE},
+        windef::{
+            DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+            HDC, HMONITOR, LPRECT,
+        },
+    },
+    um::{
+        shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI},
+        wingdi::{
+            BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits,
+            GetPixel, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, CAPTUREBLT, CLR_INVALID,
+            DIB_RGB_COLORS, SRCCOPY,
+        },
+        winuser::{
+            EnumDisplayMonitors, GetDC, GetMonitorInfoW, GetSystemMetrics, ReleaseDC,
+            SetThreadDpiAwarenessContext, MONITORINFOEXW, MONITORINFOF_PRIMARY, SM_CXVIRTUALSCREEN,
+            SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+        },
+    },
+};
+
+use crate::{clipboard::ClipboardImage, wide::from_wide, window::Rect};
+
+#[derive(Debug)]
+pub enum ScreenError {
+    InvalidRegion,
+    CaptureFailed,
+    IoError(io::Error),
+}
+
+impl Error for ScreenError {}
+
+impl fmt::Display for ScreenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenError::InvalidRegion => write!(f, "Capture region is empty"),
+            ScreenError::CaptureFailed => write!(f, "Failed to capture the screen"),
+            ScreenError::IoError(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for ScreenError {
+    fn from(err: io::Error) -> Self {
+        ScreenError::IoError(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    pub name: String,
+    pub rect: Rect,
+    pub work_area: Rect,
+    pub primary: bool,
+    pub dpi: u32,
+}
+
+impl Monitor {
+    pub fn scale_factor(&self) -> f64 {
+        self.dpi as f64 / 96.0
+    }
+}
+
+pub struct Screen;
+
+// Threads that are not DPI aware are always told 96, i.e. 100%, so ask from a per-monitor
+// aware context. Only this call switches context: the rest of the crate keeps working in
+// the process's own coordinates.
+fn effective_dpi(monitor: HMONITOR) -> u32 {
+    let (mut dpi, mut dpi_y) = (96, 96);
+    unsafe {
+        let mut previous = SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+        if previous.is_null() {
+            // Per-monitor v2 needs Windows 10 1703.
+            previous = SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE);
+        }
+        if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi, &mut dpi_y) != 0 {
+            dpi = 96;
+        }
+        if !previous.is_null() {
+            SetThreadDpiAwarenessContext(previous);
+        }
+    }
+    dpi
+}
+
+impl Screen {
+    pub fn monitors() -> Vec<Monitor> {
+        unsafe extern "system" fn collect(
+            monitor: HMONITOR,
+            _: HDC,
+            _: LPRECT,
+            lparam: LPARAM,
+        ) -> BOOL {
+            let monitors = &mut *(lparam as *mut Vec<Monitor>);
+            let mut info: MONITORINFOEXW = mem::zeroed();
+            info.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+            if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut _) != 0 {
+                let dpi = effective_dpi(monitor);
+                monitors.push(Monitor {
+                    name: from_wide(&info.szDevice),
+                    rect: info.rcMonitor.into(),
+                    work_area: info.rcWork.into(),
+                    primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+                    dpi,
+                });
+            }
+            TRUE
+        }
+
+        let mut monitors: Vec<Monitor> = Vec::new();
+        unsafe {
+            EnumDisplayMonitors(
+                ptr::null_mut(),
+                ptr::null(),
+                Some(collect),
+                &mut monitors as *mut Vec<Monitor> as LPARAM,
+            )
+        };
+        monitors
+    }
+
+    pub fn monitor_at(x: i32, y: i32) -> Option<Monitor> {
+        Self::monitors()
+            .into_iter()
+            .find(|monitor| monitor.rect.contains(x, y))
+    }
+
+    pub fn pixel(x: i32, y: i32) -> Result<(u8, u8, u8), ScreenError> {
+        let color = unsafe {
+            let screen = GetDC(ptr::null_mut());
+            if screen.is_null() {
+                return Err(ScreenError::CaptureFailed);
+            }
+            let color = GetPixel(screen, x, y);
+            ReleaseDC(ptr::null_mut(), screen);
+            color
+        };
+        if color == CLR_INVALID {
+            return Err(ScreenError::CaptureFailed);
+        }
+        // COLORREF is laid out as 0x00BBGGRR.
+        Ok((color as u8, (color >> 8) as u8, (color >> 16) as u8))
+    }
+
+    // Bounding box of all monitors; its origin is negative when a monitor sits to the
+    // left of or above the primary one.
+    pub fn virtual_rect() -> Rect {
+        let (left, top, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+        Rect::new(left, top, left + width, top + height)
+    }
+
+    pub fn capture(region: Rect) -> Result<ClipboardImage, ScreenError> {
+        if region.width() <= 0 || region.height() <= 0 {
+            return Err(ScreenError::InvalidRegion);
+        }
+        let (width, height) = (region.width(), region.height());
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        unsafe {
+            let screen = GetDC(ptr::null_mut());
+            if screen.is_null() {
+                return Err(ScreenError::CaptureFailed);
+            }
+            let memory = CreateCompatibleDC(screen);
+            let bitmap = CreateCompatibleBitmap(screen, width, height);
+            let previous = SelectObject(memory, bitmap as *mut _);
+            // CAPTUREBLT includes layered windows such as tooltips in the capture.
+            let copied = BitBlt(
+                memory,
+                0,
+                0,
+                width,
+                height,
+                screen,
+                region.left,
+                region.top,
+                SRCCOPY | CAPTUREBLT,
+            );
+            SelectObject(memory, previous);
+
+            let mut info: BITMAPINFO = mem::zeroed();
+            info.bmiHeader.biSize = mem::size_of::<BITMAPINFOHEADER>() as u32;
+            info.bmiHeader.biWidth = width;
+            info.bmiHeader.biHeight = -height;
+            info.bmiHeader.biPlanes = 1;
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = BI_RGB;
+            let lines = GetDIBits(
+                memory,
+                bitmap,
+                0,
+                height as u32,
+                pixels.as_mut_ptr() as *mut _,
+                &mut info,
+                DIB_RGB_COLORS,
+            );
+
+            DeleteObject(bitmap as *mut _);
+            DeleteDC(memory);
+            ReleaseDC(ptr::null_mut(), screen);
+            if copied == 0 || lines != height {
+                return Err(ScreenError::CaptureFailed);
+            }
+        }
+        // GDI hands back BGRX; the alpha byte is undefined for screen contents.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        Ok(ClipboardImage {
+            width: width as usize,
+            height: height as usize,
+            rgba: pixels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winapi::um::winuser::{AreDpiAwarenessContextsEqual, GetThreadDpiAwarenessContext};
+
+    use super::*;
+
+    #[test]
+    fn test_monitors_include_primary() {
+        let monitors = Screen::monitors();
+        assert!(!monitors.is_empty());
+        assert_eq!(monitors.iter().filter(|monitor| monitor.primary).count(), 1);
+        let primary = monitors.iter().find(|monitor| monitor.primary).unwrap();
+        assert_eq!((primary.rect.left, primary.rect.top), (0, 0));
+    }
+
+    #[test]
+    fn test_monitors_restore_dpi_awareness() {
+        let before = unsafe { GetThreadDpiAwarenessContext() };
+        Screen::monitors();
+        let after = unsafe { GetThreadDpiAwarenessContext() };
+        assert_ne!(unsafe { AreDpiAwarenessContextsEqual(before, after) }, 0);
+    }
+
+    #[test]
+    fn test_monitor_at() {
+        let monitor = Screen::monitor_at(0, 0).unwrap();
+        assert!(monitor.primary);
+        assert!(monitor.scale_factor() >= 1.0);
+        let outside = Screen::virtual_rect();
+        assert!(Screen::monitor_at(outside.right, outside.bottom).is_none());
+    }
+
+    #[test]
+    fn test_pixel_matches_capture() {
+        let image = Screen::capture(Rect::new(5, 5, 6, 6)).unwrap();
+        let (r, g, b) = Screen::pixel(5, 5).unwrap();
+        assert_eq!(&image.rgba[..3], &[r, g, b]);
+    }
+
+    #[test]
+    fn test_virtual_rect_contains_monitors() {
+        let virtual_rect = Screen::virtual_rect();
+        for monitor in Screen::monitors() {
+            assert!(virtual_rect.contains(monitor.rect.left, monitor.rect.top));
+        }
+    }
+
+    #[test]
+    fn test_capture() {
+        let image = Screen::capture(Rect::new(0, 0, 16, 8)).unwrap();
+        assert_eq!((image.width, image.height), (16, 8));
+        assert_eq!(image.rgba.len(), 16 * 8 * 4);
+        assert!(image.rgba.chunks_exact(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_capture_empty_region() {
+        assert!(matches!(
+            Screen::capture(Rect::new(10, 10, 10, 20)),
+            Err(ScreenError::InvalidRegion)
+        ));
+    }
+}
@@ -0,0 +1,609 @@
+use std::{
+    cell::{Cell, RefCell},
+    error::Error,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rhai::{
+    module_resolvers::DummyModuleResolver, Array, Dynamic, Engine, EvalAltResult, Map, Position,
+};
+
+use crate::{
+    keyboard::{Key, Keyboard},
+    mouse::{Mouse, MouseButton, MouseError},
+    screen::{Monitor, Screen},
+    scroll::{ScrollAmount, ScrollOptions},
+    window::{Rect, Window},
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+const TIMEOUT: &str = "timeout";
+const ACTION_LIMIT: &str = "action limit";
+const ABORTED: &str = "aborted";
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Parse(String),
+    Runtime(String),
+    Timeout,
+    ActionLimit,
+    OperationLimit,
+    Aborted,
+}
+
+impl Error for ScriptError {}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Parse(msg) => write!(f, "Script does not parse: {}", msg),
+            ScriptError::Runtime(msg) => write!(f, "Script failed: {}", msg),
+            ScriptError::Timeout => write!(f, "Script exceeded its time limit"),
+            ScriptError::ActionLimit => write!(f, "Script exceeded its action limit"),
+            ScriptError::OperationLimit => write!(f, "Script exceeded its operation limit"),
+            ScriptError::Aborted => write!(f, "Script aborted by the failsafe"),
+        }
+    }
+}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(err: Box<EvalAltResult>) -> Self {
+        match *err {
+            EvalAltResult::ErrorParsing(err, position) => {
+                ScriptError::Parse(format!("{} ({})", err, position))
+            }
+            EvalAltResult::ErrorTerminated(token, _) => match token.to_string().as_str() {
+                ACTION_LIMIT => ScriptError::ActionLimit,
+                ABORTED => ScriptError::Aborted,
+                _ => ScriptError::Timeout,
+            },
+            EvalAltResult::ErrorTooManyOperations(_) => ScriptError::OperationLimit,
+            err => ScriptError::Runtime(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    pub max_runtime: Option<Duration>,
+    pub max_actions: Option<u64>,
+    pub max_operations: Option<u64>,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_runtime: Some(Duration::from_secs(60)),
+            max_actions: Some(10_000),
+            max_operations: Some(10_000_000),
+        }
+    }
+}
+
+pub struct Script {
+    pub limits: ScriptLimits,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self {
+            limits: ScriptLimits::default(),
+        }
+    }
+
+    pub fn with_limits(limits: ScriptLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn run(&self, source: &str) -> Result<(), ScriptError> {
+        let context = Rc::new(Context {
+            mouse: RefCell::new(Mouse::new()),
+            keyboard: RefCell::new(Keyboard::new()),
+            limits: self.limits,
+            started: Instant::now(),
+            actions: Cell::new(0),
+        });
+        let engine = build_engine(&context);
+        let result = engine.run(source);
+        // However the script ended, whether by a limit, the failsafe or a throw, nothing
+        // it pressed may stay down.
+        let _ = context.keyboard.borrow_mut().release_all();
+        let _ = context.mouse.borrow_mut().release_all();
+        result?;
+        Ok(())
+    }
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Context {
+    mouse: RefCell<Mouse>,
+    keyboard: RefCell<Keyboard>,
+    limits: ScriptLimits,
+    started: Instant,
+    actions: Cell<u64>,
+}
+
+impl Context {
+    fn remaining(&self) -> Option<Duration> {
+        self.limits
+            .max_runtime
+            .map(|max| max.saturating_sub(self.started.elapsed()))
+    }
+
+    fn check_deadline(&self) -> ScriptResult<()> {
+        match self.remaining() {
+            Some(remaining) if remaining.is_zero() => terminate(TIMEOUT),
+            _ => Ok(()),
+        }
+    }
+
+    fn act<T>(
+        &self,
+        action: impl FnOnce(&mut Mouse, &mut Keyboard) -> Result<T, MouseError>,
+    ) -> ScriptResult<T> {
+        self.check_deadline()?;
+        let actions = self.actions.get() + 1;
+        self.actions.set(actions);
+        if self.limits.max_actions.is_some_and(|max| actions > max) {
+            return terminate(ACTION_LIMIT);
+        }
+        match action(
+            &mut self.mouse.borrow_mut(),
+            &mut self.keyboard.borrow_mut(),
+        ) {
+            Ok(value) => Ok(value),
+            Err(MouseError::Aborted) => terminate(ABORTED),
+            Err(err) => Err(err.to_string().into()),
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> ScriptResult<()> {
+        match self.remaining() {
+            Some(remaining) if remaining < duration => {
+                std::thread::sleep(remaining);
+                terminate(TIMEOUT)
+            }
+            _ => {
+                std::thread::sleep(duration);
+                Ok(())
+            }
+        }
+    }
+
+    fn wait_for<T>(&self, timeout_ms: i64, mut poll: impl FnMut() -> Option<T>) -> ScriptResult<T> {
+        let deadline = Instant::now() + millis(timeout_ms)?;
+        loop {
+            if let Some(value) = poll() {
+                return Ok(value);
+            }
+            if Instant::now() >= deadline {
+                return Err("Timed out waiting".into());
+            }
+            self.check_deadline()?;
+            self.sleep(Duration::from_millis(50))?;
+        }
+    }
+}
+
+// Limits and the failsafe end the script through ErrorTerminated because scripts cannot
+// catch it with try/catch.
+fn terminate<T>(reason: &str) -> ScriptResult<T> {
+    Err(EvalAltResult::ErrorTerminated(reason.into(), Position::NONE).into())
+}
+
+fn int(value: i64) -> ScriptResult<i32> {
+    value
+        .try_into()
+        .map_err(|_| format!("{} is out of range", value).into())
+}
+
+fn millis(value: i64) -> ScriptResult<Duration> {
+    u64::try_from(value)
+        .map(Duration::from_millis)
+        .map_err(|_| "Durations cannot be negative".into())
+}
+
+fn parse<T>(name: &str) -> ScriptResult<T>
+where
+    T: std::str::FromStr<Err = MouseError>,
+{
+    name.parse()
+        .map_err(|err: MouseError| err.to_string().into())
+}
+
+fn rect_map(rect: &Rect) -> Map {
+    let mut map = Map::new();
+    map.insert("left".into(), (rect.left as i64).into());
+    map.insert("top".into(), (rect.top as i64).into());
+    map.insert("right".into(), (rect.right as i64).into());
+    map.insert("bottom".into(), (rect.bottom as i64).into());
+    map.insert("width".into(), (rect.width() as i64).into());
+    map.insert("height".into(), (rect.height() as i64).into());
+    map
+}
+
+fn monitor_map(monitor: &Monitor) -> Map {
+    let mut map = rect_map(&monitor.rect);
+    map.insert("name".into(), monitor.name.clone().into());
+    map.insert("primary".into(), monitor.primary.into());
+    map.insert("dpi".into(), (monitor.dpi as i64).into());
+    map
+}
+
+fn build_engine(context: &Rc<Context>) -> Engine {
+    let mut engine = Engine::new();
+    // Scripts may only touch the outside world through the functions registered below.
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_call_levels(64);
+    engine.set_max_string_size(1 << 20);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(100_000);
+    if let Some(max) = context.limits.max_operations {
+        engine.set_max_operations(max);
+    }
+    if let Some(max) = context.limits.max_runtime {
+        let started = context.started;
+        engine.on_progress(move |_| (started.elapsed() > max).then(|| TIMEOUT.into()));
+    }
+
+    register_mouse(&mut engine, context);
+    register_keyboard(&mut engine, context);
+    register_screen(&mut engine, context);
+    register_windows(&mut engine, context);
+    engine
+}
+
+fn register_mouse(engine: &mut Engine, context: &Rc<Context>) {
+    let ctx = context.clone();
+    engine.register_fn("move_to", move |x: i64, y: i64| {
+        let (x, y) = (int(x)?, int(y)?);
+        ctx.act(|mouse, _| mouse.move_to(x, y))
+    });
+    let ctx = context.clone();
+    engine.register_fn("move_relative", move |dx: i64, dy: i64| {
+        let (dx, dy) = (int(dx)?, int(dy)?);
+        ctx.act(|mouse, _| mouse.move_relative(dx, dy))
+    });
+    let ctx = context.clone();
+    engine.register_fn("click", move || ctx.act(|mouse, _| mouse.click()));
+    let ctx = context.clone();
+    engine.register_fn("click", move |button: &str| {
+        let button: MouseButton = parse(button)?;
+        ctx.act(|mouse, _| mouse.click_button(button))
+    });
+    let ctx = context.clone();
+    engine.register_fn("click_at", move |x: i64, y: i64| {
+        let (x, y) = (int(x)?, int(y)?);
+        ctx.act(|mouse, _| {
+            mouse.move_to(x, y)?;
+            mouse.click()
+        })
+    });
+    let ctx = context.clone();
+    engine.register_fn("double_click", move || {
+        ctx.act(|mouse, _| mouse.double_click())
+    });
+    let ctx = context.clone();
+    engine.register_fn("right_click", move || {
+        ctx.act(|mouse, _| mouse.right_click())
+    });
+    let ctx = context.clone();
+    engine.register_fn("press", move |button: &str| {
+        let button: MouseButton = parse(button)?;
+        ctx.act(|mouse, _| mouse.simulate_mouse_button(button, crate::ButtonAction::Press))
+    });
+    let ctx = context.clone();
+    engine.register_fn("release", move |button: &str| {
+        let button: MouseButton = parse(button)?;
+        ctx.act(|mouse, _| mouse.simulate_mouse_button(button, crate::ButtonAction::Release))
+    });
+    let ctx = context.clone();
+    engine.register_fn("drag", move |dx: i64, dy: i64| {
+        let (dx, dy) = (int(dx)?, int(dy)?);
+        ctx.act(|mouse, _| mouse.drag(dx, dy))
+    });
+    let ctx = context.clone();
+    engine.register_fn("scroll", move |notches: i64| {
+        let amount = ScrollAmount::Notches(notches as f64);
+        ctx.act(|mouse, _| mouse.scroll_by(ScrollOptions::new(amount)))
+    });
+    let ctx = context.clone();
+    engine.register_fn("scroll_lines", move |lines: i64| {
+        let amount = ScrollAmount::Lines(lines as f64);
+        ctx.act(|mouse, _| mouse.scroll_by(ScrollOptions::new(amount)))
+    });
+    let ctx = context.clone();
+    engine.register_fn("position", move || -> ScriptResult<Map> {
+        let (x, y) = ctx
+            .mouse
+            .borrow_mut()
+            .get_current_position()
+            .map_err(|err| err.to_string())?;
+        let mut map = Map::new();
+        map.insert("x".into(), (x as i64).into());
+        map.insert("y".into(), (y as i64).into());
+        Ok(map)
+    });
+    let ctx = context.clone();
+    engine.register_fn("is_pressed", move |button: &str| {
+        let button: MouseButton = parse(button)?;
+        Ok::<_, Box<EvalAltResult>>(ctx.mouse.borrow().button_state(button))
+    });
+    let ctx = context.clone();
+    engine.register_fn("sleep", move |ms: i64| ctx.sleep(millis(ms)?));
+}
+
+fn register_keyboard(engine: &mut Engine, context: &Rc<Context>) {
+    let ctx = context.clone();
+    engine.register_fn("type_text", move |text: &str| {
+        ctx.act(|_, keyboard| keyboard.type_text(text))
+    });
+    let ctx = context.clone();
+    engine.register_fn("paste_text", move |text: &str| {
+        ctx.act(|_, keyboard| keyboard.paste_text(text))
+    });
+    let ctx = context.clone();
+    engine.register_fn("key", move |combo: &str| {
+        let keys = Key::parse_combo(combo).map_err(|err| err.to_string())?;
+        ctx.act(|_, keyboard| keyboard.press_combo(&keys))
+    });
+    let ctx = context.clone();
+    engine.register_fn("key_down", move |name: &str| {
+        let key: Key = parse(name)?;
+        ctx.act(|_, keyboard| keyboard.press_key(key))
+    });
+    let ctx = context.clone();
+    engine.register_fn("key_up", move |name: &str| {
+        let key: Key = parse(name)?;
+        ctx.act(|_, keyboard| keyboard.release_key(key))
+    });
+}
+
+fn register_screen(engine: &mut Engine, context: &Rc<Context>) {
+    let ctx = context.clone();
+    engine.register_fn("screen_size", move || {
+        let (width, height) = ctx.mouse.borrow().get_screen_size();
+        let mut map = Map::new();
+        map.insert("width".into(), (width as i64).into());
+        map.insert("height".into(), (height as i64).into());
+        map
+    });
+    engine.register_fn("monitors", || -> Array {
+        Screen::monitors()
+            .iter()
+            .map(|monitor| monitor_map(monitor).into())
+            .collect()
+    });
+    engine.register_fn("pixel", |x: i64, y: i64| -> ScriptResult<Map> {
+        let (r, g, b) = Screen::pixel(int(x)?, int(y)?).map_err(|err| err.to_string())?;
+        let mut map = Map::new();
+        map.insert("r".into(), (r as i64).into());
+        map.insert("g".into(), (g as i64).into());
+        map.insert("b".into(), (b as i64).into());
+        Ok(map)
+    });
+    let ctx = context.clone();
+    engine.register_fn(
+        "wait_for_pixel",
+        move |x: i64, y: i64, r: i64, g: i64, b: i64, timeout_ms: i64| {
+            let (x, y) = (int(x)?, int(y)?);
+            ctx.wait_for(timeout_ms, || {
+                Screen::pixel(x, y)
+                    .ok()
+                    .filter(|&(pr, pg, pb)| (pr as i64, pg as i64, pb as i64) == (r, g, b))
+                    .map(|_| ())
+            })
+        },
+    );
+}
+
+fn register_windows(engine: &mut Engine, context: &Rc<Context>) {
+    engine.register_type_with_name::<Window>("Window");
+    engine.register_fn("find_window", |title: &str| -> ScriptResult<Window> {
+        Window::find_by_title(title).map_err(|err| err.to_string().into())
+    });
+    engine.register_fn("windows", || -> Array {
+        Window::all().into_iter().map(Dynamic::from).collect()
+    });
+    engine.register_fn("foreground_window", || -> Dynamic {
+        Window::foreground().map_or(Dynamic::UNIT, Dynamic::from)
+    });
+    let ctx = context.clone();
+    engine.register_fn("wait_for_window", move |title: &str, timeout_ms: i64| {
+        ctx.wait_for(timeout_ms, || Window::find_by_title(title).ok())
+    });
+    let ctx = context.clone();
+    engine.register_fn(
+        "wait_for_close",
+        move |window: &mut Window, timeout_ms: i64| {
+            ctx.wait_for(timeout_ms, || (!window.exists()).then_some(()))
+        },
+    );
+
+    engine.register_fn("title", |window: &mut Window| window.title());
+    engine.register_fn("class_name", |window: &mut Window| window.class_name());
+    engine.register_fn("process_id", |window: &mut Window| {
+        window.process_id() as i64
+    });
+    engine.register_fn("exists", |window: &mut Window| window.exists());
+    engine.register_fn("is_visible", |window: &mut Window| window.is_visible());
+    engine.register_fn("is_minimized", |window: &mut Window| window.is_minimized());
+    engine.register_fn("is_maximized", |window: &mut Window| window.is_maximized());
+    engine.register_fn("is_focused", |window: &mut Window| window.is_focused());
+    engine.register_fn("rect", |window: &mut Window| -> ScriptResult<Map> {
+        let rect = window.rect().map_err(|err| err.to_string())?;
+        Ok(rect_map(&rect))
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("focus", move |window: &mut Window| {
+        let window = *window;
+        ctx.act(|_, _| Ok(window.focus()?))
+    });
+    let ctx = context.clone();
+    engine.register_fn("minimize", move |window: &mut Window| {
+        let window = *window;
+        ctx.act(|_, _| Ok(window.minimize()?))
+    });
+    let ctx = context.clone();
+    engine.register_fn("maximize", move |window: &mut Window| {
+        let window = *window;
+        ctx.act(|_, _| Ok(window.maximize()?))
+    });
+    let ctx = context.clone();
+    engine.register_fn("restore", move |window: &mut Window| {
+        let window = *window;
+        ctx.act(|_, _| Ok(window.restore()?))
+    });
+    let ctx = context.clone();
+    engine.register_fn("close", move |window: &mut Window| {
+        let window = *window;
+        ctx.act(|_, _| Ok(window.close()?))
+    });
+    let ctx = context.clone();
+    engine.register_fn("move_to", move |window: &mut Window, x: i64, y: i64| {
+        let (window, x, y) = (*window, int(x)?, int(y)?);
+        ctx.act(|_, _| Ok(window.move_to(x, y)?))
+    });
+    let ctx = context.clone();
+    engine.register_fn(
+        "resize",
+        move |window: &mut Window, width: i64, height: i64| {
+            let (window, width, height) = (*window, int(width)?, int(height)?);
+            ctx.act(|_, _| Ok(window.resize(width, height)?))
+        },
+    );
+    let ctx = context.clone();
+    engine.register_fn("click_at", move |window: &mut Window, x: i64, y: i64| {
+        let (window, x, y) = (*window, int(x)?, int(y)?);
+        ctx.act(|mouse, _| mouse.in_window(&window).click_at(x, y))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use winapi::um::winuser::{VK_F19, VK_MBUTTON};
+
+    use super::*;
+
+    #[test]
+    fn test_variables_and_loops() {
+        let source = r#"
+            let total = 0;
+            for i in 0..10 {
+                total += i;
+            }
+            if total != 45 {
+                throw "bad total";
+            }
+        "#;
+        assert!(Script::new().run(source).is_ok());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(matches!(
+            Script::new().run("let = ;"),
+            Err(ScriptError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_runtime_error() {
+        assert!(matches!(
+            Script::new().run("move_to(-1, -1);"),
+            Err(ScriptError::Runtime(_))
+        ));
+    }
+
+    #[test]
+    fn test_runtime_limit() {
+        let script = Script::with_limits(ScriptLimits {
+            max_runtime: Some(Duration::from_millis(100)),
+            max_operations: None,
+            ..ScriptLimits::default()
+        });
+        let start = Instant::now();
+        assert!(matches!(
+            script.run("sleep(5000);"),
+            Err(ScriptError::Timeout)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(matches!(script.run("loop {}"), Err(ScriptError::Timeout)));
+    }
+
+    #[test]
+    fn test_timeout_releases_held_keys() {
+        let script = Script::with_limits(ScriptLimits {
+            max_runtime: Some(Duration::from_millis(100)),
+            ..ScriptLimits::default()
+        });
+        assert!(matches!(
+            script.run(r#"key_down("f19"); press("middle"); sleep(5000);"#),
+            Err(ScriptError::Timeout)
+        ));
+        assert!(!crate::input::is_key_down(VK_F19));
+        assert!(!crate::input::is_key_down(VK_MBUTTON));
+    }
+
+    #[test]
+    fn test_action_limit_cannot_be_caught() {
+        let script = Script::with_limits(ScriptLimits {
+            max_actions: Some(3),
+            ..ScriptLimits::default()
+        });
+        assert!(matches!(
+            script.run("loop { try { move_to(100, 100); } catch {} }"),
+            Err(ScriptError::ActionLimit)
+        ));
+    }
+
+    #[test]
+    fn test_operation_limit() {
+        let script = Script::with_limits(ScriptLimits {
+            max_operations: Some(1_000),
+            ..ScriptLimits::default()
+        });
+        assert!(matches!(
+            script.run("loop {}"),
+            Err(ScriptError::OperationLimit)
+        ));
+    }
+
+    #[test]
+    fn test_modules_are_disabled() {
+        assert!(Script::new().run("import \"os\" as os;").is_err());
+    }
+
+    #[test]
+    fn test_mouse_functions() {
+        let source = r#"
+            move_to(200, 150);
+            let p = position();
+            if p.x != 200 || p.y != 150 {
+                throw "moved to " + p;
+            }
+        "#;
+        Script::new().run(source).unwrap();
+    }
+
+    #[test]
+    fn test_screen_functions() {
+        let source = r#"
+            let size = screen_size();
+            let color = pixel(0, 0);
+            if size.width <= 0 || monitors().len() == 0 || color.r > 255 {
+                throw "no screen";
+            }
+        "#;
+        Script::new().run(source).unwrap();
+    }
+}
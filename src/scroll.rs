@@ -0,0 +1,209 @@
+use std::time::Duration;
+use winapi::um::winuser::{
+    SystemParametersInfoW, SPI_GETWHEELSCROLLCHARS, SPI_GETWHEELSCROLLLINES, WHEEL_DELTA,
+};
+
+use crate::mouse::MouseError;
+
+// Windows has no notion of scrolling by pixels and reports no line height to derive one
+// from; at default settings most applications move roughly this far for a single notch.
+const PIXELS_PER_NOTCH: f64 = 100.0;
+const DEFAULT_LINES_PER_NOTCH: f64 = 3.0;
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+// Positive amounts scroll up (wheel rotated away from the user) or to the right.
+// Every amount is sent as Windows wheel deltas, in fractions of WHEEL_DELTA where needed,
+// and a VNC server only receives whole notches. There is no uinput REL_WHEEL_HI_RES or X11
+// XInput2 smooth scrolling behind it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollAmount {
+    Notches(f64),
+    // Converted with the user's wheel setting for lines (or characters) per notch.
+    Lines(f64),
+    // An approximation: a notch is taken to be 100 pixels, whatever the application
+    // actually scrolls for one.
+    Pixels(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollOptions {
+    pub amount: ScrollAmount,
+    pub axis: ScrollAxis,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl ScrollOptions {
+    pub fn new(amount: ScrollAmount) -> Self {
+        Self {
+            amount,
+            axis: ScrollAxis::Vertical,
+            duration: Duration::ZERO,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn axis(mut self, axis: ScrollAxis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+impl ScrollAmount {
+    pub(crate) fn to_wheel_units(self, axis: ScrollAxis) -> Result<i32, MouseError> {
+        let notches = match self {
+            ScrollAmount::Notches(notches) => notches,
+            ScrollAmount::Lines(lines) => lines / lines_per_notch(axis),
+            ScrollAmount::Pixels(pixels) => pixels / PIXELS_PER_NOTCH,
+        };
+        let units = (notches * WHEEL_DELTA as f64).round();
+        if !units.is_finite() || units.abs() > i32::MAX as f64 {
+            return Err(MouseError::InvalidInput);
+        }
+        Ok(units as i32)
+    }
+}
+
+fn lines_per_notch(axis: ScrollAxis) -> f64 {
+    let action = match axis {
+        ScrollAxis::Vertical => SPI_GETWHEELSCROLLLINES,
+        ScrollAxis::Horizontal => SPI_GETWHEELSCROLLCHARS,
+    };
+    let mut lines: u32 = 0;
+    let ok = unsafe { SystemParametersInfoW(action, 0, &mut lines as *mut u32 as *mut _, 0) };
+    // u32::MAX is WHEEL_PAGESCROLL, where a notch scrolls a whole page.
+    if ok == 0 || lines == 0 || lines == u32::MAX {
+        return DEFAULT_LINES_PER_NOTCH;
+    }
+    lines as f64
+}
+
+pub(crate) fn frame_count(duration: Duration) -> u32 {
+    (duration.as_nanos() / FRAME_INTERVAL.as_nanos()).clamp(1, u32::MAX as u128) as u32
+}
+
+// Splits a scroll into per-frame wheel deltas that follow the easing curve. Deltas need
+// not be multiples of WHEEL_DELTA, which is what makes the scroll smooth in applications
+// that support high-resolution wheels; rounding against the running total keeps the sum
+// exact.
+pub(crate) fn wheel_steps(total: i32, frames: u32, easing: Easing) -> Vec<i32> {
+    let frames = frames.max(1);
+    let mut sent = 0;
+    (1..=frames)
+        .map(|frame| {
+            let target = if frame == frames {
+                total
+            } else {
+                (total as f64 * easing.apply(frame as f64 / frames as f64)).round() as i32
+            };
+            let step = target - sent;
+            sent = target;
+            step
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_wheel_units() {
+        let vertical = ScrollAxis::Vertical;
+        assert_eq!(
+            ScrollAmount::Notches(1.0).to_wheel_units(vertical).unwrap(),
+            120
+        );
+        assert_eq!(
+            ScrollAmount::Notches(-0.5)
+                .to_wheel_units(vertical)
+                .unwrap(),
+            -60
+        );
+        assert_eq!(
+            ScrollAmount::Pixels(50.0).to_wheel_units(vertical).unwrap(),
+            60
+        );
+        assert!(ScrollAmount::Notches(f64::NAN)
+            .to_wheel_units(vertical)
+            .is_err());
+    }
+
+    #[test]
+    fn test_wheel_units_lines() {
+        let lines = lines_per_notch(ScrollAxis::Vertical);
+        let units = ScrollAmount::Lines(lines)
+            .to_wheel_units(ScrollAxis::Vertical)
+            .unwrap();
+        assert_eq!(units, 120);
+    }
+
+    #[test]
+    fn test_wheel_steps_sum_to_total() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            let steps = wheel_steps(-361, 7, easing);
+            assert_eq!(steps.len(), 7);
+            assert_eq!(steps.iter().sum::<i32>(), -361);
+        }
+        assert_eq!(wheel_steps(120, 0, Easing::Linear), vec![120]);
+    }
+
+    #[test]
+    fn test_frame_count() {
+        assert_eq!(frame_count(Duration::ZERO), 1);
+        assert_eq!(frame_count(Duration::from_millis(160)), 10);
+    }
+}
@@ -0,0 +1,774 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tungstenite::{protocol::WebSocketConfig, Message, WebSocket};
+
+use crate::{
+    access::{constant_time_eq, Slot, Slots},
+    actions::Actions,
+    input,
+    keyboard::{Key, Keyboard},
+    mouse::{ButtonAction, ClickOptions, EventType, Mouse, MouseButton, MouseError},
+    screen::{Screen, ScreenError},
+    scroll::{ScrollAmount, ScrollAxis, ScrollOptions},
+    window::Rect,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INPUT_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+const EVENTS: [&str; 3] = ["click", "move", "button"];
+
+// Messages larger than this close the connection rather than being buffered.
+const MAX_MESSAGE: usize = 1 << 20;
+
+#[derive(Debug)]
+pub enum ServerError {
+    IoError(io::Error),
+    WebSocket(Box<tungstenite::Error>),
+    Handshake(String),
+    TokenRequired,
+    Closed,
+}
+
+impl Error for ServerError {}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::IoError(err) => write!(f, "IO error: {}", err),
+            ServerError::WebSocket(err) => write!(f, "WebSocket error: {}", err),
+            ServerError::Handshake(msg) => write!(f, "WebSocket handshake failed: {}", msg),
+            ServerError::TokenRequired => {
+                write!(f, "A token is required to listen on a non-loopback address")
+            }
+            ServerError::Closed => write!(f, "Connection closed"),
+        }
+    }
+}
+
+impl From<io::Error> for ServerError {
+    fn from(err: io::Error) -> Self {
+        ServerError::IoError(err)
+    }
+}
+
+impl From<tungstenite::Error> for ServerError {
+    fn from(err: tungstenite::Error) -> Self {
+        ServerError::WebSocket(Box::new(err))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub token: Option<String>,
+    pub poll_interval: Duration,
+    pub max_sessions: usize,
+    // Shared by every clone, so the cap covers the TCP and WebSocket listeners together.
+    active: Slots,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self {
+            token: None,
+            poll_interval: Duration::from_millis(20),
+            max_sessions: 8,
+            active: Slots::default(),
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    // Serves newline-delimited JSON-RPC, one message per line.
+    pub fn serve_tcp<A: ToSocketAddrs>(&self, addr: A) -> Result<(), ServerError> {
+        let listener = self.bind(addr)?;
+        for stream in listener.incoming() {
+            let stream = stream?;
+            // Connections over the limit are closed straight away.
+            let Some(slot) = self.claim_slot() else {
+                continue;
+            };
+            let server = self.clone();
+            thread::spawn(move || {
+                let _slot = slot;
+                let reader = BufReader::new(stream.try_clone()?);
+                let mut connection = TcpConnection {
+                    reader,
+                    writer: stream,
+                    line: Vec::new(),
+                };
+                server.serve_connection(&mut connection)
+            });
+        }
+        Ok(())
+    }
+
+    // Serves JSON-RPC over WebSocket, one message per text frame.
+    pub fn serve_websocket<A: ToSocketAddrs>(&self, addr: A) -> Result<(), ServerError> {
+        let listener = self.bind(addr)?;
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let Some(slot) = self.claim_slot() else {
+                continue;
+            };
+            let server = self.clone();
+            thread::spawn(move || {
+                let _slot = slot;
+                let config = WebSocketConfig {
+                    max_message_size: Some(MAX_MESSAGE),
+                    max_frame_size: Some(MAX_MESSAGE),
+                    ..Default::default()
+                };
+                let socket = tungstenite::accept_with_config(stream, Some(config))
+                    .map_err(|err| ServerError::Handshake(err.to_string()))?;
+                server.serve_connection(&mut WebSocketConnection { socket })
+            });
+        }
+        Ok(())
+    }
+
+    // Anyone who can reach the port can drive the desktop, so only loopback is allowed
+    // without a token.
+    fn bind<A: ToSocketAddrs>(&self, addr: A) -> Result<TcpListener, ServerError> {
+        let listener = TcpListener::bind(addr)?;
+        if self.token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+            return Err(ServerError::TokenRequired);
+        }
+        Ok(listener)
+    }
+
+    fn claim_slot(&self) -> Option<Slot> {
+        self.active.claim(self.max_sessions)
+    }
+
+    fn serve_connection(&self, connection: &mut dyn Connection) -> Result<(), ServerError> {
+        // Reads time out so that subscribed events keep flowing while the client is idle.
+        connection.set_read_timeout(self.poll_interval)?;
+        let mut session = Session::new(self.token.clone());
+        let result = run_session(&mut session, connection);
+        session.close();
+        match result {
+            Err(ServerError::Closed) => Ok(()),
+            result => result,
+        }
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run_session(session: &mut Session, connection: &mut dyn Connection) -> Result<(), ServerError> {
+    loop {
+        if let Some(message) = connection.read_message()? {
+            if let Some(response) = session.handle_message(&message) {
+                connection.write_message(&response.to_string())?;
+            }
+        }
+        for notification in session.poll_events() {
+            connection.write_message(&notification.to_string())?;
+        }
+    }
+}
+
+trait Connection {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<(), ServerError>;
+    // Returns None when the read timed out without a complete message.
+    fn read_message(&mut self) -> Result<Option<String>, ServerError>;
+    fn write_message(&mut self, message: &str) -> Result<(), ServerError>;
+}
+
+struct TcpConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    line: Vec<u8>,
+}
+
+impl Connection for TcpConnection {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<(), ServerError> {
+        Ok(self.writer.set_read_timeout(Some(timeout))?)
+    }
+
+    fn read_message(&mut self) -> Result<Option<String>, ServerError> {
+        // A timed out read keeps the bytes it has read so far in `line`, so partial lines
+        // are completed by the next call. Decoding waits for the whole line, since the
+        // timeout may split a character.
+        let limit = (MAX_MESSAGE + 1 - self.line.len()) as u64;
+        match (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', &mut self.line)
+        {
+            Ok(0) if self.line.is_empty() => Err(ServerError::Closed),
+            Ok(_) if self.line.len() > MAX_MESSAGE => Err(ServerError::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Message too large",
+            ))),
+            Ok(_) => {
+                let line = std::mem::take(&mut self.line);
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+                Ok((!line.is_empty()).then(|| line.to_string()))
+            }
+            Err(err) if is_timeout(&err) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write_message(&mut self, message: &str) -> Result<(), ServerError> {
+        writeln!(self.writer, "{}", message)?;
+        Ok(self.writer.flush()?)
+    }
+}
+
+struct WebSocketConnection {
+    socket: WebSocket<TcpStream>,
+}
+
+impl Connection for WebSocketConnection {
+    fn set_read_timeout(&mut self, timeout: Duration) -> Result<(), ServerError> {
+        Ok(self.socket.get_ref().set_read_timeout(Some(timeout))?)
+    }
+
+    fn read_message(&mut self) -> Result<Option<String>, ServerError> {
+        match self.socket.read() {
+            Ok(Message::Text(text)) => Ok(Some(text)),
+            Ok(Message::Close(_)) => Err(ServerError::Closed),
+            // Pings are answered by tungstenite itself; binary frames are not part of
+            // the protocol.
+            Ok(_) => Ok(None),
+            Err(tungstenite::Error::Io(err)) if is_timeout(&err) => Ok(None),
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                Err(ServerError::Closed)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write_message(&mut self, message: &str) -> Result<(), ServerError> {
+        Ok(self.socket.send(Message::Text(message.to_string()))?)
+    }
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<MouseError> for RpcError {
+    fn from(err: MouseError) -> Self {
+        RpcError::new(INPUT_ERROR, err)
+    }
+}
+
+impl From<ScreenError> for RpcError {
+    fn from(err: ScreenError) -> Self {
+        RpcError::new(INPUT_ERROR, err)
+    }
+}
+
+// Everything a connection owns. Buttons and keys it leaves held are released when the
+// connection ends, however it ends.
+struct Session {
+    token: Option<String>,
+    authenticated: bool,
+    mouse: Mouse,
+    keyboard: Keyboard,
+    keys_down: Vec<Key>,
+    subscriptions: HashSet<String>,
+    clicks: Arc<Mutex<usize>>,
+    last_position: Option<(i32, i32)>,
+    last_buttons: Vec<MouseButton>,
+}
+
+impl Session {
+    fn new(token: Option<String>) -> Self {
+        let mut mouse = Mouse::new();
+        let clicks = Arc::new(Mutex::new(0));
+        let counter = clicks.clone();
+        mouse.on(EventType::Click, move || {
+            *counter.lock().unwrap() += 1;
+        });
+        Self {
+            authenticated: token.is_none(),
+            token,
+            mouse,
+            keyboard: Keyboard::new(),
+            keys_down: Vec::new(),
+            subscriptions: HashSet::new(),
+            clicks,
+            last_position: None,
+            last_buttons: Vec::new(),
+        }
+    }
+
+    fn handle_message(&mut self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(err) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, err))),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let err = RpcError::new(INVALID_REQUEST, "Request has no method");
+            return Some(error_response(id.unwrap_or(Value::Null), err));
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.call(method, &params);
+        // Requests without an id are notifications and get no response.
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err),
+        })
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        if method == "auth" {
+            let token: String = param(params, "token")?;
+            if self
+                .token
+                .as_ref()
+                .is_some_and(|expected| !constant_time_eq(expected.as_bytes(), token.as_bytes()))
+            {
+                return Err(RpcError::new(UNAUTHORIZED, "Invalid token"));
+            }
+            self.authenticated = true;
+            return Ok(Value::Bool(true));
+        }
+        if !self.authenticated {
+            return Err(RpcError::new(UNAUTHORIZED, "Call auth first"));
+        }
+
+        match method {
+            "mouse.position" => {
+                let (x, y) = self.mouse.get_current_position()?;
+                return Ok(json!({ "x": x, "y": y }));
+            }
+            "mouse.move_to" => self
+                .mouse
+                .move_to(param(params, "x")?, param(params, "y")?)?,
+            "mouse.move_relative" => self
+                .mouse
+                .move_relative(param(params, "dx")?, param(params, "dy")?)?,
+            "mouse.hover" => {
+                let duration = Duration::from_millis(param(params, "duration_ms")?);
+                self.mouse
+                    .hover(param(params, "x")?, param(params, "y")?, duration)?
+            }
+            "mouse.click" => {
+                let button = optional(params, "button")?.unwrap_or(MouseButton::Left);
+                let mut options = ClickOptions::new(button);
+                if let Some(count) = optional(params, "count")? {
+                    options = options.count(count);
+                }
+                if let Some(interval) = optional(params, "interval_ms")? {
+                    options = options.interval(Duration::from_millis(interval));
+                }
+                if let Some(hold) = optional(params, "hold_ms")? {
+                    options = options.hold_duration(Duration::from_millis(hold));
+                }
+                self.mouse.click_with(options)?
+            }
+            "mouse.press" => self
+                .mouse
+                .simulate_mouse_button(param(params, "button")?, ButtonAction::Press)?,
+            "mouse.release" => self
+                .mouse
+                .simulate_mouse_button(param(params, "button")?, ButtonAction::Release)?,
+            "mouse.release_all" => self.mouse.release_all()?,
+            "mouse.pressed_buttons" => return Ok(json!(self.mouse.pressed_buttons())),
+            "mouse.scroll" => {
+                let amount: f64 = param(params, "amount")?;
+                let amount = match optional::<String>(params, "unit")?.as_deref() {
+                    None | Some("notches") => ScrollAmount::Notches(amount),
+                    Some("lines") => ScrollAmount::Lines(amount),
+                    Some("pixels") => ScrollAmount::Pixels(amount),
+                    Some(unit) => {
+                        return Err(RpcError::new(
+                            INVALID_PARAMS,
+                            format!("Unknown unit {}", unit),
+                        ))
+                    }
+                };
+                let mut options = ScrollOptions::new(amount);
+                if optional(params, "horizontal")?.unwrap_or(false) {
+                    options = options.axis(ScrollAxis::Horizontal);
+                }
+                if let Some(duration) = optional(params, "duration_ms")? {
+                    options = options.duration(Duration::from_millis(duration));
+                }
+                self.mouse.scroll_by(options)?
+            }
+            "mouse.drag" => {
+                let (dx, dy) = (param(params, "dx")?, param(params, "dy")?);
+                match optional(params, "duration_ms")? {
+                    Some(duration) if duration > 0 => {
+                        self.mouse
+                            .drag_with_duration(dx, dy, Duration::from_millis(duration))?
+                    }
+                    _ => self.mouse.drag(dx, dy)?,
+                }
+            }
+            "keyboard.type_text" => self.keyboard.type_text(&param::<String>(params, "text")?)?,
+            "keyboard.paste_text" => self
+                .keyboard
+                .paste_text(&param::<String>(params, "text")?)?,
+            "keyboard.key" => {
+                let combo = Key::parse_combo(&param::<String>(params, "combo")?)
+                    .map_err(|err| RpcError::new(INVALID_PARAMS, err))?;
+                self.keyboard.press_combo(&combo)?
+            }
+            "keyboard.key_down" => {
+                let key = key_param(params)?;
+                self.keyboard.press_key(key)?;
+                if !self.keys_down.contains(&key) {
+                    self.keys_down.push(key);
+                }
+            }
+            "keyboard.key_up" => {
+                let key = key_param(params)?;
+                self.keyboard.release_key(key)?;
+                self.keys_down.retain(|&down| down != key);
+            }
+            "screen.size" => {
+                let (width, height) = self.mouse.get_screen_size();
+                return Ok(json!({ "width": width, "height": height }));
+            }
+            "screen.monitors" => {
+                let monitors: Vec<Value> = Screen::monitors()
+                    .iter()
+                    .map(|monitor| {
+                        json!({
+                            "name": monitor.name,
+                            "rect": rect_json(&monitor.rect),
+                            "work_area": rect_json(&monitor.work_area),
+                            "primary": monitor.primary,
+                            "dpi": monitor.dpi,
+                        })
+                    })
+                    .collect();
+                return Ok(Value::Array(monitors));
+            }
+            "screen.pixel" => {
+                let (r, g, b) = Screen::pixel(param(params, "x")?, param(params, "y")?)?;
+                return Ok(json!([r, g, b]));
+            }
+            "screen.capture" => {
+                let rect = match optional::<[i32; 4]>(params, "rect")? {
+                    Some([left, top, right, bottom]) => Rect::new(left, top, right, bottom),
+                    None => Screen::virtual_rect(),
+                };
+                let image = Screen::capture(rect)?;
+                return Ok(json!({
+                    "width": image.width,
+                    "height": image.height,
+                    "rgba": STANDARD.encode(&image.rgba),
+                }));
+            }
+            "actions.perform" => param::<Actions>(params, "actions")?.perform(&mut self.mouse)?,
+            "events.subscribe" => {
+                for event in event_names(params)? {
+                    self.subscriptions.insert(event);
+                }
+                // Start from the current state rather than reporting it as a change.
+                self.last_position = input::cursor_pos().ok();
+                self.last_buttons = self.mouse.pressed_buttons();
+                *self.clicks.lock().unwrap() = 0;
+            }
+            "events.unsubscribe" => {
+                for event in event_names(params)? {
+                    self.subscriptions.remove(&event);
+                }
+            }
+            _ => {
+                return Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("Unknown method {}", method),
+                ))
+            }
+        }
+        Ok(Value::Null)
+    }
+
+    fn poll_events(&mut self) -> Vec<Value> {
+        let mut events = Vec::new();
+        let clicks = std::mem::take(&mut *self.clicks.lock().unwrap());
+        if self.subscriptions.contains("click") {
+            events.extend((0..clicks).map(|_| json!({ "type": "click" })));
+        }
+        if self.subscriptions.contains("move") {
+            if let Ok((x, y)) = input::cursor_pos() {
+                if self.last_position != Some((x, y)) {
+                    self.last_position = Some((x, y));
+                    events.push(json!({ "type": "move", "x": x, "y": y }));
+                }
+            }
+        }
+        if self.subscriptions.contains("button") {
+            let buttons = self.mouse.pressed_buttons();
+            for button in buttons.iter().filter(|b| !self.last_buttons.contains(b)) {
+                events.push(json!({ "type": "button", "button": button, "pressed": true }));
+            }
+            for button in self.last_buttons.iter().filter(|b| !buttons.contains(b)) {
+                events.push(json!({ "type": "button", "button": button, "pressed": false }));
+            }
+            self.last_buttons = buttons;
+        }
+        events
+            .into_iter()
+            .map(|event| json!({ "jsonrpc": "2.0", "method": "event", "params": event }))
+            .collect()
+    }
+
+    fn close(&mut self) {
+        let _ = self.mouse.release_all();
+        for key in self.keys_down.drain(..).rev() {
+            let _ = self.keyboard.release_key(key);
+        }
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn optional<T: DeserializeOwned>(params: &Value, name: &str) -> Result<Option<T>, RpcError> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|err| RpcError::new(INVALID_PARAMS, format!("{}: {}", name, err))),
+    }
+}
+
+fn param<T: DeserializeOwned>(params: &Value, name: &str) -> Result<T, RpcError> {
+    optional(params, name)?
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing parameter {}", name)))
+}
+
+fn key_param(params: &Value) -> Result<Key, RpcError> {
+    param::<String>(params, "key")?
+        .parse()
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err))
+}
+
+fn event_names(params: &Value) -> Result<Vec<String>, RpcError> {
+    let events: Vec<String> = param(params, "events")?;
+    match events
+        .iter()
+        .find(|event| !EVENTS.contains(&event.as_str()))
+    {
+        Some(event) => Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Unknown event {}", event),
+        )),
+        None => Ok(events),
+    }
+}
+
+fn rect_json(rect: &Rect) -> Value {
+    json!({
+        "left": rect.left,
+        "top": rect.top,
+        "right": rect.right,
+        "bottom": rect.bottom,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(session: &mut Session, message: &str) -> Value {
+        session.handle_message(message).unwrap()
+    }
+
+    fn call(session: &mut Session, method: &str, params: Value) -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        request(session, &message.to_string())
+    }
+
+    #[test]
+    fn test_auth_required() {
+        let mut session = Session::new(Some("secret".to_string()));
+        let response = call(&mut session, "mouse.position", Value::Null);
+        assert_eq!(response["error"]["code"], UNAUTHORIZED);
+        let response = call(&mut session, "auth", json!({ "token": "wrong" }));
+        assert_eq!(response["error"]["code"], UNAUTHORIZED);
+        let response = call(&mut session, "auth", json!({ "token": "secret" }));
+        assert_eq!(response["result"], true);
+        let response = call(&mut session, "mouse.position", Value::Null);
+        assert!(response["result"]["x"].is_i64());
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let mut session = Session::new(None);
+        assert_eq!(request(&mut session, "{")["error"]["code"], PARSE_ERROR);
+        let response = request(&mut session, r#"{"jsonrpc":"2.0","id":1}"#);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+        let response = request(
+            &mut session,
+            r#"{"jsonrpc":"2.0","id":"a","method":"nope"}"#,
+        );
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], "a");
+        let response = call(&mut session, "mouse.move_to", json!({ "x": 1 }));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        let response = call(
+            &mut session,
+            "events.subscribe",
+            json!({ "events": ["scroll"] }),
+        );
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let mut session = Session::new(None);
+        assert!(session
+            .handle_message(r#"{"jsonrpc":"2.0","method":"mouse.position"}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_click_events() {
+        let mut session = Session::new(None);
+        call(
+            &mut session,
+            "events.subscribe",
+            json!({ "events": ["click"] }),
+        );
+        call(&mut session, "mouse.click", json!({ "count": 2 }));
+        let events = session.poll_events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["params"]["type"], "click");
+        assert!(session.poll_events().is_empty());
+    }
+
+    #[test]
+    fn test_non_loopback_needs_token() {
+        assert!(matches!(
+            Server::new().bind("0.0.0.0:0"),
+            Err(ServerError::TokenRequired)
+        ));
+        assert!(Server::new().bind("127.0.0.1:0").is_ok());
+        assert!(Server::new().token("secret").bind("0.0.0.0:0").is_ok());
+    }
+
+    #[test]
+    fn test_session_slots() {
+        let server = Server::new().max_sessions(1);
+        let slot = server.claim_slot().unwrap();
+        assert!(server.clone().claim_slot().is_none());
+        drop(slot);
+        assert!(server.claim_slot().is_some());
+    }
+
+    fn tcp_connection() -> (TcpConnection, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(20)))
+            .unwrap();
+        let connection = TcpConnection {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+            line: Vec::new(),
+        };
+        (connection, client)
+    }
+
+    #[test]
+    fn test_split_character_survives_timeout() {
+        let (mut connection, mut client) = tcp_connection();
+        let message = "\"h\u{e9}llo\"\n".as_bytes();
+        // Split inside the two bytes of the e acute.
+        client.write_all(&message[..3]).unwrap();
+        assert_eq!(connection.read_message().unwrap(), None);
+        client.write_all(&message[3..]).unwrap();
+        assert_eq!(
+            connection.read_message().unwrap().as_deref(),
+            Some("\"h\u{e9}llo\"")
+        );
+    }
+
+    #[test]
+    fn test_oversized_line_closes_connection() {
+        let (mut connection, mut client) = tcp_connection();
+        let writer = thread::spawn(move || {
+            let _ = client.write_all(&vec![b'x'; MAX_MESSAGE + 1]);
+        });
+        let result = loop {
+            match connection.read_message() {
+                Ok(None) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(ServerError::IoError(_))));
+        drop(connection);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_close_releases_held_input() {
+        let mut session = Session::new(None);
+        call(&mut session, "mouse.press", json!({ "button": "left" }));
+        call(&mut session, "keyboard.key_down", json!({ "key": "shift" }));
+        session.close();
+        assert!(session.mouse.held_buttons().is_empty());
+        assert!(session.keys_down.is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    sync::mpsc::{self, Sender},
+    thread::{self, ThreadId},
+    time::Duration,
+};
+
+use crate::{
+    mouse::{ButtonAction, ClickOptions, EventType, Mouse, MouseButton, MouseError},
+    scroll::ScrollOptions,
+};
+
+type Job = Box<dyn FnOnce(&mut Mouse) + Send>;
+
+// A cloneable handle to one Mouse owned by a worker thread. Every call is queued and run
+// to completion in order, so a drag issued from one thread cannot be interleaved with a
+// click from another, and all clones agree on the cursor position. The worker stops, and
+// releases anything still held, when the last clone is dropped.
+#[derive(Clone)]
+pub struct SharedMouse {
+    jobs: Sender<Job>,
+    worker: ThreadId,
+}
+
+impl SharedMouse {
+    pub fn new() -> Self {
+        Self::spawn(Mouse::new)
+    }
+
+    fn spawn<F>(make: F) -> Self
+    where
+        F: FnOnce() -> Mouse + Send + 'static,
+    {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let worker = thread::Builder::new()
+            .name("robot_rs-input".to_string())
+            .spawn(move || {
+                let mut mouse = make();
+                for job in queue {
+                    job(&mut mouse);
+                }
+            })
+            .expect("failed to start the input worker");
+        Self {
+            jobs,
+            worker: worker.thread().id(),
+        }
+    }
+
+    // Runs `f` on the worker with exclusive use of the mouse, for sequences that must not
+    // be split up by calls from other threads.
+    pub fn with<F, T>(&self, f: F) -> Result<T, MouseError>
+    where
+        F: FnOnce(&mut Mouse) -> Result<T, MouseError> + Send + 'static,
+        T: Send + 'static,
+    {
+        // A listener or `with` closure runs on the worker itself, so waiting for another job
+        // there would never return.
+        if thread::current().id() == self.worker {
+            return Err(MouseError::BackendUnavailable(
+                "SharedMouse used from its own input worker".to_string(),
+            ));
+        }
+        let (reply, result) = mpsc::channel();
+        let job: Job = Box::new(move |mouse| {
+            let _ = reply.send(f(mouse));
+        });
+        let stopped = || MouseError::BackendUnavailable("input worker has stopped".to_string());
+        self.jobs.send(job).map_err(|_| stopped())?;
+        result.recv().map_err(|_| stopped())?
+    }
+
+    pub fn position(&self) -> Result<(i32, i32), MouseError> {
+        self.with(|mouse| mouse.get_current_position())
+    }
+
+    pub fn move_to(&self, x: i32, y: i32) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.move_to(x, y))
+    }
+
+    pub fn move_relative(&self, distance_x: i32, distance_y: i32) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.move_relative(distance_x, distance_y))
+    }
+
+    pub fn hover(&self, x: i32, y: i32, duration: Duration) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.hover(x, y, duration))
+    }
+
+    pub fn move_in_circle(
+        &self,
+        center_x: i32,
+        center_y: i32,
+        radius: i32,
+        duration: Duration,
+    ) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.move_in_circle(center_x, center_y, radius, duration))
+    }
+
+    pub fn click(&self) -> Result<(), MouseError> {
+        self.with(|mouse| mouse.click())
+    }
+
+    pub fn double_click(&self) -> Result<(), MouseError> {
+        self.with(|mouse| mouse.double_click())
+    }
+
+    pub fn right_click(&self) -> Result<(), MouseError> {
+        self.with(|mouse| mouse.right_click())
+    }
+
+    pub fn click_with(&self, options: ClickOptions) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.click_with(options))
+    }
+
+    pub fn scroll(&self, amount: i32) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.scroll(amount))
+    }
+
+    pub fn scroll_horizontal(&self, distance: i32) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.scroll_horizontal(distance))
+    }
+
+    pub fn scroll_by(&self, options: ScrollOptions) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.scroll_by(options))
+    }
+
+    pub fn drag(&self, distance_x: i32, distance_y: i32) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.drag(distance_x, distance_y))
+    }
+
+    pub fn drag_with_duration(
+        &self,
+        distance_x: i32,
+        distance_y: i32,
+        duration: Duration,
+    ) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.drag_with_duration(distance_x, distance_y, duration))
+    }
+
+    pub fn simulate_mouse_button(
+        &self,
+        button: MouseButton,
+        action: ButtonAction,
+    ) -> Result<(), MouseError> {
+        self.with(move |mouse| mouse.simulate_mouse_button(button, action))
+    }
+
+    pub fn release_all(&self) -> Result<(), MouseError> {
+        self.with(|mouse| mouse.release_all())
+    }
+
+    pub fn held_buttons(&self) -> Result<Vec<MouseButton>, MouseError> {
+        self.with(|mouse| Ok(mouse.held_buttons().to_vec()))
+    }
+
+    pub fn on<F>(&self, event_type: EventType, listener: F) -> Result<(), MouseError>
+    where
+        F: Fn() + 'static + Send + Sync,
+    {
+        self.with(move |mouse| {
+            mouse.on(event_type, listener);
+            Ok(())
+        })
+    }
+}
+
+impl Default for SharedMouse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Mouse> for SharedMouse {
+    fn from(mouse: Mouse) -> Self {
+        Self::spawn(move || mouse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_shared_mouse_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedMouse>();
+    }
+
+    #[test]
+    fn test_clones_share_position() {
+        let shared = SharedMouse::new();
+        let other = shared.clone();
+        shared.move_to(120, 130).unwrap();
+        assert_eq!(
+            other.with(|mouse| Ok(mouse.get_mouse_position())).unwrap(),
+            (120, 130)
+        );
+    }
+
+    #[test]
+    fn test_jobs_do_not_interleave() {
+        let shared = SharedMouse::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let workers: Vec<_> = (0..4)
+            .map(|id| {
+                let shared = shared.clone();
+                let log = log.clone();
+                thread::spawn(move || {
+                    shared
+                        .with(move |_| {
+                            log.lock().unwrap().push((id, "start"));
+                            thread::sleep(Duration::from_millis(5));
+                            log.lock().unwrap().push((id, "end"));
+                            Ok(())
+                        })
+                        .unwrap();
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 8);
+        for pair in log.chunks(2) {
+            assert_eq!(pair[0].0, pair[1].0);
+            assert_eq!((pair[0].1, pair[1].1), ("start", "end"));
+        }
+    }
+
+    #[test]
+    fn test_reentrant_call_does_not_deadlock() {
+        let shared = SharedMouse::default();
+        let inner = shared.clone();
+        let result = shared.with(move |_| Ok(inner.position())).unwrap();
+        assert!(matches!(result, Err(MouseError::BackendUnavailable(_))));
+        assert!(shared.position().is_ok());
+    }
+
+    #[test]
+    fn test_panicking_job_stops_worker() {
+        let shared = SharedMouse::new();
+        let result: Result<(), MouseError> = shared.with(|_| panic!("boom"));
+        assert!(matches!(result, Err(MouseError::BackendUnavailable(_))));
+        assert!(matches!(
+            shared.release_all(),
+            Err(MouseError::BackendUnavailable(_))
+        ));
+    }
+}
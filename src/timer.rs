@@ -0,0 +1,173 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    future::Future,
+    pin::Pin,
+    sync::{Condvar, Mutex, OnceLock},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+// A single background thread wakes every pending delay, so the async methods make progress
+// under any executor without tying the crate to one runtime.
+struct Timer {
+    pending: Mutex<BinaryHeap<Reverse<Entry>>>,
+    changed: Condvar,
+}
+
+struct Entry {
+    deadline: Instant,
+    waker: Waker,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    TIMER.get_or_init(|| {
+        thread::Builder::new()
+            .name("robot_rs-timer".to_string())
+            .spawn(run)
+            .expect("failed to start the timer thread");
+        Timer {
+            pending: Mutex::new(BinaryHeap::new()),
+            changed: Condvar::new(),
+        }
+    })
+}
+
+fn run() {
+    let timer = timer();
+    let mut pending = timer.pending.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        while pending
+            .peek()
+            .is_some_and(|Reverse(entry)| entry.deadline <= now)
+        {
+            if let Some(Reverse(entry)) = pending.pop() {
+                due.push(entry.waker);
+            }
+        }
+        if !due.is_empty() {
+            // Wake outside the lock; an executor may poll, and so re-register, right away.
+            drop(pending);
+            due.into_iter().for_each(Waker::wake);
+            pending = timer.pending.lock().unwrap();
+            continue;
+        }
+        pending = match pending.peek() {
+            Some(Reverse(entry)) => {
+                let timeout = entry.deadline.saturating_duration_since(now);
+                timer.changed.wait_timeout(pending, timeout).unwrap().0
+            }
+            None => timer.changed.wait(pending).unwrap(),
+        };
+    }
+}
+
+pub(crate) struct Delay {
+    deadline: Instant,
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        let timer = timer();
+        timer.pending.lock().unwrap().push(Reverse(Entry {
+            deadline: self.deadline,
+            waker: cx.waker().clone(),
+        }));
+        timer.changed.notify_one();
+        Poll::Pending
+    }
+}
+
+pub(crate) fn sleep(duration: Duration) -> Delay {
+    sleep_until(Instant::now() + duration)
+}
+
+pub(crate) fn sleep_until(deadline: Instant) -> Delay {
+    Delay { deadline }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        task::Wake,
+        thread::{self, Thread},
+    };
+
+    use super::*;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_sleep_waits_for_deadline() {
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(30)));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_past_deadline_is_ready() {
+        let mut delay = std::pin::pin!(sleep_until(Instant::now() - Duration::from_millis(1)));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(delay.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn test_dropped_delay_does_not_block_others() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut abandoned = Box::pin(sleep(Duration::from_millis(5)));
+        assert_eq!(abandoned.as_mut().poll(&mut cx), Poll::Pending);
+        drop(abandoned);
+
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    io, ptr,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        mpsc, OnceLock,
+    },
+    thread,
+};
+use winapi::{
+    shared::{
+        minwindef::{LPARAM, LRESULT, WPARAM},
+        windef::HHOOK,
+    },
+    um::{
+        libloaderapi::GetModuleHandleW,
+        winuser::{
+            CallNextHookEx, GetMessageW, SetWindowsHookExW, HC_ACTION, LLMHF_INJECTED, MSG,
+            MSLLHOOKSTRUCT, WH_MOUSE_LL, WM_MOUSEMOVE,
+        },
+    },
+};
+
+use crate::{input, mouse::MouseError};
+
+// Both coordinates live in one atomic so a reader never sees x from one move and y from
+// the next.
+static POSITION: AtomicI64 = AtomicI64::new(0);
+// Counts moves that did not come from injected input, i.e. the user's own hand.
+static PHYSICAL_MOVES: AtomicU64 = AtomicU64::new(0);
+
+fn pack(x: i32, y: i32) -> i64 {
+    ((x as i64) << 32) | (y as u32 as i64)
+}
+
+fn unpack(packed: i64) -> (i32, i32) {
+    ((packed >> 32) as i32, packed as i32)
+}
+
+unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION && wparam as u32 == WM_MOUSEMOVE {
+        let info = &*(lparam as *const MSLLHOOKSTRUCT);
+        POSITION.store(pack(info.pt.x, info.pt.y), Ordering::SeqCst);
+        if info.flags & LLMHF_INJECTED == 0 {
+            PHYSICAL_MOVES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+}
+
+// Installs the process-wide low-level mouse hook on its own thread the first time it is
+// needed. Low-level hooks are called through that thread's message loop, which runs for
+// the rest of the process.
+pub(crate) fn start() -> Result<(), MouseError> {
+    static STARTED: OnceLock<Result<(), String>> = OnceLock::new();
+    STARTED
+        .get_or_init(|| {
+            let (x, y) = input::cursor_pos().map_err(|err| err.to_string())?;
+            POSITION.store(pack(x, y), Ordering::SeqCst);
+
+            let (installed, result) = mpsc::channel();
+            thread::Builder::new()
+                .name("robot_rs-tracker".to_string())
+                .spawn(move || {
+                    let module = unsafe { GetModuleHandleW(ptr::null()) };
+                    let handle: HHOOK =
+                        unsafe { SetWindowsHookExW(WH_MOUSE_LL, Some(hook), module, 0) };
+                    if handle.is_null() {
+                        let _ = installed.send(Err(io::Error::last_os_error().to_string()));
+                        return;
+                    }
+                    let _ = installed.send(Ok(()));
+                    let mut message: MSG = unsafe { std::mem::zeroed() };
+                    while unsafe { GetMessageW(&mut message, ptr::null_mut(), 0, 0) } > 0 {}
+                })
+                .map_err(|err| err.to_string())?;
+            result
+                .recv()
+                .unwrap_or_else(|_| Err("tracker thread exited".to_string()))
+        })
+        .clone()
+        .map_err(MouseError::BackendUnavailable)
+}
+
+pub(crate) fn position() -> (i32, i32) {
+    unpack(POSITION.load(Ordering::SeqCst))
+}
+
+pub(crate) fn physical_moves() -> u64 {
+    PHYSICAL_MOVES.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_round_trips_negative_coordinates() {
+        for (x, y) in [
+            (0, 0),
+            (1920, 1080),
+            (-1280, 200),
+            (300, -45),
+            (i32::MIN, i32::MAX),
+        ] {
+            assert_eq!(unpack(pack(x, y)), (x, y));
+        }
+    }
+}
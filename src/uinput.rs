@@ -0,0 +1,268 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    backend::InputEvent,
+    mouse::{ButtonAction, MouseButton, MouseError},
+    scroll::ScrollAxis,
+};
+
+// Event types and codes from linux/input-event-codes.h.
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const SYN_REPORT: u16 = 0x00;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_SIDE: u16 = 0x113;
+const BTN_EXTRA: u16 = 0x114;
+
+const WHEEL_DELTA: i32 = 120;
+
+// Writes pointer input as the struct input_event records of 64-bit little-endian Linux to
+// a uinput device, or to an evdev node of an existing mouse, which takes the same records.
+// The stream can be any writer, e.g. the stdin of `ssh host 'cat > /dev/input/event5'`;
+// the device has to have the buttons and relative axes that are used. Clones share it.
+//
+// The axes are relative, so positions are where we last moved the pointer and pointer
+// acceleration may make moves land short or long. Button state is what we sent.
+#[derive(Clone)]
+pub struct Uinput {
+    device: Arc<Mutex<Device>>,
+}
+
+struct Device {
+    writer: Box<dyn Write + Send>,
+    width: i32,
+    height: i32,
+    position: (i32, i32),
+    buttons: Vec<u16>,
+}
+
+impl Uinput {
+    // The pointer is taken to start at the top left corner of a width by height screen.
+    pub fn new<W: Write + Send + 'static>(writer: W, width: i32, height: i32) -> Self {
+        Self {
+            device: Arc::new(Mutex::new(Device {
+                writer: Box::new(writer),
+                width,
+                height,
+                position: (0, 0),
+                buttons: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn get_screen_size(&self) -> (i32, i32) {
+        let device = self.device();
+        (device.width, device.height)
+    }
+
+    pub(crate) fn position(&self) -> (i32, i32) {
+        self.device().position
+    }
+
+    pub(crate) fn is_pressed(&self, button: MouseButton) -> bool {
+        button_code(button).is_ok_and(|code| self.device().buttons.contains(&code))
+    }
+
+    pub(crate) fn send(&self, events: &[InputEvent]) -> Result<(), MouseError> {
+        self.device().send(events)
+    }
+
+    fn device(&self) -> MutexGuard<'_, Device> {
+        self.device.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Device {
+    // Encodes every event before sending any, so that an invalid one sends nothing.
+    fn send(&mut self, events: &[InputEvent]) -> Result<(), MouseError> {
+        let (mut position, mut buttons) = (self.position, self.buttons.clone());
+        let mut records = Vec::new();
+        for event in events {
+            match *event {
+                InputEvent::MoveTo { x, y } => {
+                    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+                        return Err(MouseError::OutOfBounds);
+                    }
+                    record(&mut records, EV_REL, REL_X, x - position.0);
+                    record(&mut records, EV_REL, REL_Y, y - position.1);
+                    position = (x, y);
+                }
+                InputEvent::Button { button, action } => {
+                    let code = button_code(button)?;
+                    buttons.retain(|&held| held != code);
+                    if action == ButtonAction::Press {
+                        buttons.push(code);
+                    }
+                    record(
+                        &mut records,
+                        EV_KEY,
+                        code,
+                        (action == ButtonAction::Press) as i32,
+                    );
+                }
+                // Amounts are in wheel units; REL_WHEEL only counts whole notches.
+                InputEvent::Scroll { axis, amount } => {
+                    let code = match axis {
+                        ScrollAxis::Vertical => REL_WHEEL,
+                        ScrollAxis::Horizontal => REL_HWHEEL,
+                    };
+                    let notches = (amount.abs() + WHEEL_DELTA / 2) / WHEEL_DELTA;
+                    let notches = notches.max((amount != 0) as i32) * amount.signum();
+                    record(&mut records, EV_REL, code, notches);
+                }
+                InputEvent::Key { .. } | InputEvent::Char { .. } => {
+                    return Err(MouseError::BackendUnavailable(
+                        "uinput input only covers the pointer".to_string(),
+                    ))
+                }
+            }
+            record(&mut records, EV_SYN, SYN_REPORT, 0);
+        }
+        self.writer.write_all(&records)?;
+        self.writer.flush()?;
+        self.position = position;
+        self.buttons = buttons;
+        Ok(())
+    }
+}
+
+// A struct input_event with a zero timestamp, which the kernel fills in.
+fn record(records: &mut Vec<u8>, kind: u16, code: u16, value: i32) {
+    records.extend_from_slice(&[0; 16]);
+    records.extend_from_slice(&kind.to_le_bytes());
+    records.extend_from_slice(&code.to_le_bytes());
+    records.extend_from_slice(&value.to_le_bytes());
+}
+
+// Back and forward are the side and extra buttons, as the kernel reports them for mice
+// and X11 maps them to buttons 8 and 9. Later buttons take the rest of the kernel's range
+// for mouse buttons, from BTN_FORWARD to 0x11f.
+fn button_code(button: MouseButton) -> Result<u16, MouseError> {
+    let code = match button {
+        MouseButton::Left | MouseButton::Other(1) => BTN_LEFT,
+        MouseButton::Right | MouseButton::Other(2) => BTN_RIGHT,
+        MouseButton::Middle | MouseButton::Other(3) => BTN_MIDDLE,
+        MouseButton::Back | MouseButton::Other(4) => BTN_SIDE,
+        MouseButton::Forward | MouseButton::Other(5) => BTN_EXTRA,
+        MouseButton::Other(number @ 6..=16) => BTN_LEFT + number as u16 - 1,
+        MouseButton::Other(number) => return Err(MouseError::UnsupportedButton(number)),
+    };
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        // The type, code and value of every record written so far.
+        fn take(&self) -> Vec<(u16, u16, i32)> {
+            let bytes = std::mem::take(&mut *self.0.lock().unwrap());
+            bytes
+                .chunks_exact(24)
+                .map(|record| {
+                    (
+                        u16::from_le_bytes([record[16], record[17]]),
+                        u16::from_le_bytes([record[18], record[19]]),
+                        i32::from_le_bytes([record[20], record[21], record[22], record[23]]),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_side_buttons() {
+        let sink = Sink::default();
+        let uinput = Uinput::new(sink.clone(), 800, 600);
+        let press = |button| InputEvent::Button {
+            button,
+            action: ButtonAction::Press,
+        };
+        uinput
+            .send(&[press(MouseButton::Back), press(MouseButton::Forward)])
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                (EV_KEY, BTN_SIDE, 1),
+                (EV_SYN, SYN_REPORT, 0),
+                (EV_KEY, BTN_EXTRA, 1),
+                (EV_SYN, SYN_REPORT, 0),
+            ]
+        );
+        assert!(uinput.is_pressed(MouseButton::Other(4)));
+        assert!(uinput.is_pressed(MouseButton::Forward));
+        assert_eq!(button_code(MouseButton::Other(6)).unwrap(), 0x115);
+        assert_eq!(button_code(MouseButton::Other(16)).unwrap(), 0x11f);
+        assert!(matches!(
+            button_code(MouseButton::Other(17)),
+            Err(MouseError::UnsupportedButton(17))
+        ));
+    }
+
+    #[test]
+    fn test_moves_and_scrolls_are_relative() {
+        let sink = Sink::default();
+        let uinput = Uinput::new(sink.clone(), 800, 600);
+        uinput
+            .send(&[
+                InputEvent::MoveTo { x: 30, y: 40 },
+                InputEvent::MoveTo { x: 10, y: 40 },
+                InputEvent::Scroll {
+                    axis: ScrollAxis::Vertical,
+                    amount: -240,
+                },
+            ])
+            .unwrap();
+        assert_eq!(
+            sink.take(),
+            vec![
+                (EV_REL, REL_X, 30),
+                (EV_REL, REL_Y, 40),
+                (EV_SYN, SYN_REPORT, 0),
+                (EV_REL, REL_X, -20),
+                (EV_REL, REL_Y, 0),
+                (EV_SYN, SYN_REPORT, 0),
+                (EV_REL, REL_WHEEL, -2),
+                (EV_SYN, SYN_REPORT, 0),
+            ]
+        );
+        assert_eq!(uinput.position(), (10, 40));
+
+        let invalid = [
+            InputEvent::MoveTo { x: 0, y: 0 },
+            InputEvent::MoveTo { x: 800, y: 0 },
+        ];
+        assert!(matches!(
+            uinput.send(&invalid),
+            Err(MouseError::OutOfBounds)
+        ));
+        assert!(sink.take().is_empty());
+        assert_eq!(uinput.position(), (10, 40));
+    }
+}
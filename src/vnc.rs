@@ -0,0 +1,805 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use des::{
+    cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit},
+    Des,
+};
+
+use crate::{
+    backend::InputEvent,
+    clipboard::ClipboardImage,
+    keyboard::Key,
+    mouse::{ButtonAction, MouseButton},
+    scroll::ScrollAxis,
+    window::Rect,
+};
+
+const SECURITY_NONE: u8 = 1;
+const SECURITY_VNC: u8 = 2;
+
+const ENCODING_RAW: i32 = 0;
+const ENCODING_COPY_RECT: i32 = 1;
+const ENCODING_DESKTOP_SIZE: i32 = -223;
+
+// Button mask bits of a PointerEvent; the wheel is buttons 4 to 7.
+const BUTTON_LEFT: u8 = 1;
+const BUTTON_MIDDLE: u8 = 1 << 1;
+const BUTTON_RIGHT: u8 = 1 << 2;
+const WHEEL_UP: u8 = 1 << 3;
+const WHEEL_DOWN: u8 = 1 << 4;
+const WHEEL_LEFT: u8 = 1 << 5;
+const WHEEL_RIGHT: u8 = 1 << 6;
+
+const WHEEL_DELTA: i32 = 120;
+
+#[derive(Debug)]
+pub enum VncError {
+    IoError(io::Error),
+    Protocol(String),
+    AuthFailed(String),
+    OutOfBounds,
+    UnsupportedButton(MouseButton),
+    UnsupportedKey(Key),
+    UnsupportedText(char),
+}
+
+impl Error for VncError {}
+
+impl fmt::Display for VncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VncError::IoError(err) => write!(f, "IO error: {}", err),
+            VncError::Protocol(msg) => write!(f, "RFB protocol error: {}", msg),
+            VncError::AuthFailed(msg) => write!(f, "VNC authentication failed: {}", msg),
+            VncError::OutOfBounds => write!(f, "Position is outside the remote framebuffer"),
+            VncError::UnsupportedButton(button) => {
+                write!(f, "{:?} cannot be sent over VNC", button)
+            }
+            VncError::UnsupportedKey(key) => write!(f, "{:?} has no X keysym", key),
+            VncError::UnsupportedText(c) => {
+                write!(
+                    f,
+                    "{:?} is not Latin-1, which is all a VNC clipboard holds",
+                    c
+                )
+            }
+        }
+    }
+}
+
+impl From<io::Error> for VncError {
+    fn from(err: io::Error) -> Self {
+        VncError::IoError(err)
+    }
+}
+
+// A connection to a VNC server. Mouse::vnc and Keyboard::vnc send their input to it as
+// RFB PointerEvent and KeyEvent messages; clones share the connection.
+#[derive(Clone)]
+pub struct Vnc {
+    name: String,
+    connection: Arc<Mutex<Connection>>,
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    width: usize,
+    height: usize,
+    // RGBA, updated from the FramebufferUpdate messages the server sends.
+    framebuffer: Vec<u8>,
+    position: (i32, i32),
+    buttons: u8,
+}
+
+impl Vnc {
+    pub fn connect<A: ToSocketAddrs>(addr: A, password: Option<&str>) -> Result<Self, VncError> {
+        let writer = TcpStream::connect(addr)?;
+        writer.set_nodelay(true)?;
+        // Servers that stop answering should fail a capture rather than hang it.
+        writer.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let mut connection = Connection {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+            width: 0,
+            height: 0,
+            framebuffer: Vec::new(),
+            position: (0, 0),
+            buttons: 0,
+        };
+        let name = connection.handshake(password)?;
+        Ok(Self {
+            name,
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_screen_size(&self) -> (i32, i32) {
+        let connection = self.connection();
+        (connection.width as i32, connection.height as i32)
+    }
+
+    pub fn capture(&self, region: Rect) -> Result<ClipboardImage, VncError> {
+        self.connection().capture(region)
+    }
+
+    pub fn pixel(&self, x: i32, y: i32) -> Result<(u8, u8, u8), VncError> {
+        let image = self.capture(Rect::new(x, y, x + 1, y + 1))?;
+        Ok((image.rgba[0], image.rgba[1], image.rgba[2]))
+    }
+
+    pub(crate) fn position(&self) -> (i32, i32) {
+        self.connection().position
+    }
+
+    pub(crate) fn is_pressed(&self, button: MouseButton) -> bool {
+        let buttons = self.connection().buttons;
+        button_mask(button).is_ok_and(|mask| buttons & mask != 0)
+    }
+
+    pub(crate) fn send(&self, events: &[InputEvent]) -> Result<(), VncError> {
+        self.connection().send(events)
+    }
+
+    // Sets the server's clipboard with a ClientCutText message.
+    pub(crate) fn set_clipboard_text(&self, text: &str) -> Result<(), VncError> {
+        let latin1 = text
+            .chars()
+            .map(|c| u8::try_from(c).map_err(|_| VncError::UnsupportedText(c)))
+            .collect::<Result<Vec<u8>, VncError>>()?;
+        let mut message = vec![6, 0, 0, 0];
+        message.extend_from_slice(&(latin1.len() as u32).to_be_bytes());
+        message.extend_from_slice(&latin1);
+        Ok(self.connection().writer.write_all(&message)?)
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Connection {
+    fn capture(&mut self, region: Rect) -> Result<ClipboardImage, VncError> {
+        self.check_region(region)?;
+        self.refresh(region)?;
+        // The update may have carried a DesktopSize that shrank the framebuffer.
+        self.check_region(region)?;
+        let (width, height) = (region.width() as usize, region.height() as usize);
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for y in region.top as usize..region.bottom as usize {
+            let start = (y * self.width + region.left as usize) * 4;
+            rgba.extend_from_slice(&self.framebuffer[start..start + width * 4]);
+        }
+        Ok(ClipboardImage {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    // Encodes every event before sending any, so that an invalid one sends nothing.
+    fn send(&mut self, events: &[InputEvent]) -> Result<(), VncError> {
+        let (mut position, mut buttons) = (self.position, self.buttons);
+        let mut messages = Vec::new();
+        for event in events {
+            match *event {
+                InputEvent::MoveTo { x, y } => {
+                    if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                        return Err(VncError::OutOfBounds);
+                    }
+                    position = (x, y);
+                    pointer_event(&mut messages, position, buttons);
+                }
+                InputEvent::Button { button, action } => {
+                    let mask = button_mask(button)?;
+                    match action {
+                        ButtonAction::Press => buttons |= mask,
+                        ButtonAction::Release => buttons &= !mask,
+                    }
+                    pointer_event(&mut messages, position, buttons);
+                }
+                // Amounts are in wheel units; RFB can only send whole notches.
+                InputEvent::Scroll { axis, amount } => {
+                    let wheel = match (axis, amount > 0) {
+                        (ScrollAxis::Vertical, true) => WHEEL_UP,
+                        (ScrollAxis::Vertical, false) => WHEEL_DOWN,
+                        (ScrollAxis::Horizontal, true) => WHEEL_RIGHT,
+                        (ScrollAxis::Horizontal, false) => WHEEL_LEFT,
+                    };
+                    let notches = (amount.abs() + WHEEL_DELTA / 2) / WHEEL_DELTA;
+                    for _ in 0..notches.max((amount != 0) as i32) {
+                        pointer_event(&mut messages, position, buttons | wheel);
+                        pointer_event(&mut messages, position, buttons);
+                    }
+                }
+                InputEvent::Key { key, action } => {
+                    key_event(&mut messages, keysym(key)?, action == ButtonAction::Press)
+                }
+                InputEvent::Char { c, action } => {
+                    key_event(&mut messages, char_keysym(c), action == ButtonAction::Press)
+                }
+            }
+        }
+        self.writer.write_all(&messages)?;
+        self.position = position;
+        self.buttons = buttons;
+        Ok(())
+    }
+
+    fn check_region(&self, region: Rect) -> Result<(), VncError> {
+        let bounds = Rect::new(0, 0, self.width as i32, self.height as i32);
+        if region.width() <= 0
+            || region.height() <= 0
+            || !bounds.contains(region.left, region.top)
+            || !bounds.contains(region.right - 1, region.bottom - 1)
+        {
+            return Err(VncError::OutOfBounds);
+        }
+        Ok(())
+    }
+
+    // Asks for a full update of the region and waits for it.
+    fn refresh(&mut self, region: Rect) -> Result<(), VncError> {
+        let mut request = vec![3, 0];
+        for value in [region.left, region.top, region.width(), region.height()] {
+            request.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        self.writer.write_all(&request)?;
+        while !self.read_server_message()? {}
+        Ok(())
+    }
+
+    // Returns the desktop name.
+    fn handshake(&mut self, password: Option<&str>) -> Result<String, VncError> {
+        let mut version = [0u8; 12];
+        self.reader.read_exact(&mut version)?;
+        let minor = match &version {
+            b"RFB 003.003\n" => 3,
+            b"RFB 003.007\n" => 7,
+            v if v.starts_with(b"RFB 003.") || v.starts_with(b"RFB 004.") => 8,
+            _ => return Err(VncError::Protocol("Not an RFB server".to_string())),
+        };
+        self.writer
+            .write_all(format!("RFB 003.{:03}\n", minor).as_bytes())?;
+
+        let security = if minor == 3 {
+            match self.read_u32()? {
+                0 => return Err(VncError::Protocol(self.read_string()?)),
+                security => security as u8,
+            }
+        } else {
+            let count = self.read_u8()?;
+            if count == 0 {
+                return Err(VncError::Protocol(self.read_string()?));
+            }
+            let mut offered = vec![0u8; count as usize];
+            self.reader.read_exact(&mut offered)?;
+            let security = match password {
+                Some(_) if offered.contains(&SECURITY_VNC) => SECURITY_VNC,
+                _ if offered.contains(&SECURITY_NONE) => SECURITY_NONE,
+                _ if offered.contains(&SECURITY_VNC) => SECURITY_VNC,
+                _ => {
+                    return Err(VncError::Protocol(
+                        "The server offers no supported security type".to_string(),
+                    ))
+                }
+            };
+            self.writer.write_all(&[security])?;
+            security
+        };
+
+        match security {
+            SECURITY_NONE => {}
+            SECURITY_VNC => {
+                let password = password.ok_or_else(|| {
+                    VncError::AuthFailed("The server requires a password".to_string())
+                })?;
+                let mut challenge = [0u8; 16];
+                self.reader.read_exact(&mut challenge)?;
+                self.writer
+                    .write_all(&vnc_auth_response(password, challenge))?;
+            }
+            _ => {
+                return Err(VncError::Protocol(format!(
+                    "Unsupported security type {}",
+                    security
+                )))
+            }
+        }
+        // RFB 3.3 and 3.7 send no result for the None security type.
+        if (security == SECURITY_VNC || minor >= 8) && self.read_u32()? != 0 {
+            let reason = if minor >= 8 {
+                self.read_string()?
+            } else {
+                "Wrong password".to_string()
+            };
+            return Err(VncError::AuthFailed(reason));
+        }
+
+        // ClientInit, asking to share the desktop with other viewers.
+        self.writer.write_all(&[1])?;
+        self.width = self.read_u16()? as usize;
+        self.height = self.read_u16()? as usize;
+        let mut server_format = [0u8; 16];
+        self.reader.read_exact(&mut server_format)?;
+        let name = self.read_string()?;
+        self.framebuffer = vec![0; self.width * self.height * 4];
+
+        // 32 bits per pixel, little-endian true colour with red in the lowest byte, so
+        // raw pixels arrive as R, G, B, unused.
+        let mut set_pixel_format = vec![0, 0, 0, 0, 32, 24, 0, 1];
+        set_pixel_format.extend_from_slice(&[0, 255, 0, 255, 0, 255, 0, 8, 16, 0, 0, 0]);
+        self.writer.write_all(&set_pixel_format)?;
+        let encodings = [ENCODING_RAW, ENCODING_COPY_RECT, ENCODING_DESKTOP_SIZE];
+        let mut set_encodings = vec![2, 0];
+        set_encodings.extend_from_slice(&(encodings.len() as u16).to_be_bytes());
+        for encoding in encodings {
+            set_encodings.extend_from_slice(&encoding.to_be_bytes());
+        }
+        self.writer.write_all(&set_encodings)?;
+        Ok(name)
+    }
+
+    // Returns whether the message was a framebuffer update.
+    fn read_server_message(&mut self) -> Result<bool, VncError> {
+        match self.read_u8()? {
+            0 => {
+                self.read_u8()?;
+                for _ in 0..self.read_u16()? {
+                    self.read_rectangle()?;
+                }
+                Ok(true)
+            }
+            // SetColourMapEntries; we asked for true colour, so there is nothing to map.
+            1 => {
+                self.read_u8()?;
+                self.read_u16()?;
+                let count = self.read_u16()? as usize;
+                self.skip(count * 6)?;
+                Ok(false)
+            }
+            // Bell
+            2 => Ok(false),
+            // ServerCutText
+            3 => {
+                self.skip(3)?;
+                let length = self.read_u32()? as usize;
+                self.skip(length)?;
+                Ok(false)
+            }
+            kind => Err(VncError::Protocol(format!(
+                "Unknown server message {}",
+                kind
+            ))),
+        }
+    }
+
+    fn read_rectangle(&mut self) -> Result<(), VncError> {
+        let x = self.read_u16()? as usize;
+        let y = self.read_u16()? as usize;
+        let width = self.read_u16()? as usize;
+        let height = self.read_u16()? as usize;
+        let encoding = self.read_u32()? as i32;
+        match encoding {
+            ENCODING_RAW => {
+                // Checked before allocating, as the size comes from the server.
+                self.check_rect(x, y, width, height)?;
+                let mut pixels = vec![0u8; width * height * 4];
+                self.reader.read_exact(&mut pixels)?;
+                for pixel in pixels.chunks_exact_mut(4) {
+                    pixel[3] = 255;
+                }
+                self.blit(x, y, width, height, &pixels)
+            }
+            ENCODING_COPY_RECT => {
+                let src_x = self.read_u16()? as usize;
+                let src_y = self.read_u16()? as usize;
+                self.check_rect(x, y, width, height)?;
+                self.check_rect(src_x, src_y, width, height)?;
+                let mut pixels = Vec::with_capacity(width * height * 4);
+                for row in src_y..src_y + height {
+                    let start = (row * self.width + src_x) * 4;
+                    pixels.extend_from_slice(&self.framebuffer[start..start + width * 4]);
+                }
+                self.blit(x, y, width, height, &pixels)
+            }
+            ENCODING_DESKTOP_SIZE => {
+                self.width = width;
+                self.height = height;
+                self.framebuffer = vec![0; width * height * 4];
+                Ok(())
+            }
+            _ => Err(VncError::Protocol(format!(
+                "Unrequested encoding {}",
+                encoding
+            ))),
+        }
+    }
+
+    fn blit(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+    ) -> Result<(), VncError> {
+        // Empty rectangles are legal and carry no pixels.
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        for (row, line) in pixels.chunks_exact(width * 4).enumerate() {
+            let start = ((y + row) * self.width + x) * 4;
+            self.framebuffer[start..start + width * 4].copy_from_slice(line);
+        }
+        Ok(())
+    }
+
+    fn check_rect(&self, x: usize, y: usize, width: usize, height: usize) -> Result<(), VncError> {
+        if x + width > self.width || y + height > self.height {
+            return Err(VncError::Protocol(
+                "Rectangle outside the framebuffer".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), VncError> {
+        io::copy(&mut (&mut self.reader).take(length as u64), &mut io::sink())?;
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, VncError> {
+        let mut bytes = [0u8; 1];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, VncError> {
+        let mut bytes = [0u8; 2];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u16::from_be_bytes(bytes))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, VncError> {
+        let mut bytes = [0u8; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn read_string(&mut self) -> Result<String, VncError> {
+        let length = self.read_u32()? as usize;
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(length as u64)
+            .read_to_end(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+fn pointer_event(messages: &mut Vec<u8>, (x, y): (i32, i32), mask: u8) {
+    messages.extend_from_slice(&[5, mask]);
+    messages.extend_from_slice(&(x as u16).to_be_bytes());
+    messages.extend_from_slice(&(y as u16).to_be_bytes());
+}
+
+fn key_event(messages: &mut Vec<u8>, keysym: u32, down: bool) {
+    messages.extend_from_slice(&[4, down as u8, 0, 0]);
+    messages.extend_from_slice(&keysym.to_be_bytes());
+}
+
+fn button_mask(button: MouseButton) -> Result<u8, VncError> {
+    match button.normalize() {
+        Ok(MouseButton::Left) => Ok(BUTTON_LEFT),
+        Ok(MouseButton::Middle) => Ok(BUTTON_MIDDLE),
+        Ok(MouseButton::Right) => Ok(BUTTON_RIGHT),
+        _ => Err(VncError::UnsupportedButton(button)),
+    }
+}
+
+// VNC authentication encrypts the challenge with DES, keyed by the first eight bytes
+// of the password with the bits of every byte mirrored.
+fn vnc_auth_response(password: &str, mut challenge: [u8; 16]) -> [u8; 16] {
+    let mut key = [0u8; 8];
+    for (byte, &c) in key.iter_mut().zip(password.as_bytes()) {
+        *byte = c.reverse_bits();
+    }
+    let cipher = Des::new_from_slice(&key).expect("DES keys are eight bytes");
+    for block in challenge.chunks_exact_mut(8) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    }
+    challenge
+}
+
+fn keysym(key: Key) -> Result<u32, VncError> {
+    Ok(match key {
+        Key::Control => 0xffe3,
+        Key::Shift => 0xffe1,
+        Key::Alt => 0xffe9,
+        Key::Meta => 0xffeb,
+        Key::Enter => 0xff0d,
+        Key::Tab => 0xff09,
+        Key::Escape => 0xff1b,
+        Key::Backspace => 0xff08,
+        Key::Delete => 0xffff,
+        Key::Insert => 0xff63,
+        Key::Space => 0x20,
+        Key::Up => 0xff52,
+        Key::Down => 0xff54,
+        Key::Left => 0xff51,
+        Key::Right => 0xff53,
+        Key::Home => 0xff50,
+        Key::End => 0xff57,
+        Key::PageUp => 0xff55,
+        Key::PageDown => 0xff56,
+        Key::F(n) if (1..=24).contains(&n) => 0xffbe + n as u32 - 1,
+        Key::Char(c) | Key::Unicode(c) => char_keysym(c),
+        // Windows virtual key codes have no portable keysym.
+        Key::F(_) | Key::Code(_) => return Err(VncError::UnsupportedKey(key)),
+    })
+}
+
+// Latin-1 characters are their own keysyms; X11 maps the rest of Unicode above
+// 0x01000000.
+fn char_keysym(c: char) -> u32 {
+    match c as u32 {
+        code @ (0x20..=0x7e | 0xa0..=0xff) => code,
+        code => 0x0100_0000 + code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    // Runs a scripted RFB 3.8 server for a 4x2 framebuffer on another thread.
+    fn fake_server<F>(session: F) -> (String, thread::JoinHandle<()>)
+    where
+        F: FnOnce(&mut TcpStream) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"RFB 003.008\n").unwrap();
+            let mut buffer = [0u8; 12];
+            stream.read_exact(&mut buffer).unwrap();
+            assert_eq!(&buffer, b"RFB 003.008\n");
+            stream.write_all(&[1, SECURITY_NONE]).unwrap();
+            stream.read_exact(&mut buffer[..1]).unwrap();
+            assert_eq!(buffer[0], SECURITY_NONE);
+            stream.write_all(&0u32.to_be_bytes()).unwrap();
+            stream.read_exact(&mut buffer[..1]).unwrap();
+            let mut server_init = vec![0, 4, 0, 2];
+            server_init.extend_from_slice(&[0; 16]);
+            server_init.extend_from_slice(&4u32.to_be_bytes());
+            server_init.extend_from_slice(b"test");
+            stream.write_all(&server_init).unwrap();
+            // SetPixelFormat, then SetEncodings with three encodings.
+            let mut setup = [0u8; 20 + 4 + 12];
+            stream.read_exact(&mut setup).unwrap();
+            assert_eq!((setup[0], setup[20]), (0, 2));
+            session(&mut stream);
+        });
+        (addr, server)
+    }
+
+    fn read_bytes(stream: &mut TcpStream, length: usize) -> Vec<u8> {
+        let mut bytes = vec![0u8; length];
+        stream.read_exact(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn press(button: MouseButton) -> InputEvent {
+        InputEvent::Button {
+            button,
+            action: ButtonAction::Press,
+        }
+    }
+
+    fn release(button: MouseButton) -> InputEvent {
+        InputEvent::Button {
+            button,
+            action: ButtonAction::Release,
+        }
+    }
+
+    #[test]
+    fn test_pointer_events() {
+        let (addr, server) = fake_server(|stream| {
+            assert_eq!(read_bytes(stream, 6), [5, 0, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 6), [5, BUTTON_LEFT, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 6), [5, 0, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 6), [5, WHEEL_DOWN, 0, 3, 0, 1]);
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        assert_eq!(vnc.name(), "test");
+        assert_eq!(vnc.get_screen_size(), (4, 2));
+        vnc.send(&[InputEvent::MoveTo { x: 3, y: 1 }, press(MouseButton::Left)])
+            .unwrap();
+        assert!(vnc.is_pressed(MouseButton::Left));
+        let scroll = InputEvent::Scroll {
+            axis: ScrollAxis::Vertical,
+            amount: -120,
+        };
+        vnc.send(&[release(MouseButton::Left), scroll]).unwrap();
+        assert_eq!(vnc.position(), (3, 1));
+        assert!(!vnc.is_pressed(MouseButton::Left));
+
+        // An invalid event stops the whole batch from being sent.
+        let off_screen = InputEvent::MoveTo { x: 4, y: 0 };
+        assert!(matches!(
+            vnc.send(&[press(MouseButton::Left), off_screen]),
+            Err(VncError::OutOfBounds)
+        ));
+        assert!(!vnc.is_pressed(MouseButton::Left));
+        assert!(matches!(
+            vnc.send(&[press(MouseButton::Back)]),
+            Err(VncError::UnsupportedButton(_))
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_key_events() {
+        let (addr, server) = fake_server(|stream| {
+            let down = [4, 1, 0, 0, 0, 0, 0xff, 0xe3];
+            assert_eq!(read_bytes(stream, 8), down);
+            assert_eq!(read_bytes(stream, 8), [4, 1, 0, 0, 0, 0, 0, 0xe9]);
+            assert_eq!(read_bytes(stream, 8), [4, 0, 0, 0, 0, 0, 0, 0xe9]);
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        vnc.send(&[
+            InputEvent::Key {
+                key: Key::Control,
+                action: ButtonAction::Press,
+            },
+            InputEvent::Char {
+                c: 'é',
+                action: ButtonAction::Press,
+            },
+            InputEvent::Char {
+                c: 'é',
+                action: ButtonAction::Release,
+            },
+        ])
+        .unwrap();
+        let key = InputEvent::Key {
+            key: Key::Code(0x41),
+            action: ButtonAction::Press,
+        };
+        assert!(matches!(vnc.send(&[key]), Err(VncError::UnsupportedKey(_))));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_set_clipboard_text() {
+        let (addr, server) = fake_server(|stream| {
+            assert_eq!(read_bytes(stream, 10), [6, 0, 0, 0, 0, 0, 0, 2, b'h', 0xe9]);
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        vnc.set_clipboard_text("hé").unwrap();
+        assert!(matches!(
+            vnc.set_clipboard_text("€"),
+            Err(VncError::UnsupportedText('€'))
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_mouse_and_keyboard_share_the_connection() {
+        let (addr, server) = fake_server(|stream| {
+            assert_eq!(read_bytes(stream, 6), [5, 0, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 6), [5, BUTTON_LEFT, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 6), [5, 0, 0, 3, 0, 1]);
+            assert_eq!(read_bytes(stream, 8), [4, 1, 0, 0, 0, 0, 0, b'a']);
+            assert_eq!(read_bytes(stream, 8), [4, 0, 0, 0, 0, 0, 0, b'a']);
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        let mut mouse = crate::Mouse::vnc(&vnc);
+        assert_eq!(mouse.get_screen_size(), (4, 2));
+        crate::Actions::new()
+            .move_to(3, 1)
+            .click(MouseButton::Left)
+            .perform(&mut mouse)
+            .unwrap();
+        assert_eq!(mouse.get_current_position().unwrap(), (3, 1));
+        // Bounds are those of the remote framebuffer, not of the local screen.
+        assert!(mouse.move_to(10, 1).is_err());
+        crate::Keyboard::vnc(&vnc).type_text("a").unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_capture() {
+        let (addr, server) = fake_server(|stream| {
+            assert_eq!(read_bytes(stream, 10), [3, 0, 0, 1, 0, 0, 0, 2, 0, 1]);
+            let mut update = vec![0, 0, 0, 1, 0, 1, 0, 0, 0, 2, 0, 1, 0, 0, 0, 0];
+            update.extend_from_slice(&[10, 20, 30, 0, 40, 50, 60, 0]);
+            // A bell before the update must be skipped.
+            stream.write_all(&[2]).unwrap();
+            stream.write_all(&update).unwrap();
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        let image = vnc.capture(Rect::new(1, 0, 3, 1)).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.rgba, vec![10, 20, 30, 255, 40, 50, 60, 255]);
+        assert!(vnc.capture(Rect::new(3, 0, 5, 1)).is_err());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_capture_survives_empty_rect_and_resize() {
+        let (addr, server) = fake_server(|stream| {
+            read_bytes(stream, 10);
+            // A zero-width Raw rectangle, then a DesktopSize shrinking the screen to 1x1.
+            let mut update = vec![0, 0, 0, 2];
+            update.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0]);
+            update.extend_from_slice(&[0, 0, 0, 0, 0, 1, 0, 1, 0xff, 0xff, 0xff, 0x21]);
+            stream.write_all(&update).unwrap();
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        assert!(matches!(
+            vnc.capture(Rect::new(1, 0, 3, 1)),
+            Err(VncError::OutOfBounds)
+        ));
+        assert_eq!(vnc.get_screen_size(), (1, 1));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_rectangle_outside_framebuffer_is_refused() {
+        let (addr, server) = fake_server(|stream| {
+            read_bytes(stream, 10);
+            // A 65535x65535 Raw rectangle with no pixels behind it.
+            let mut update = vec![0, 0, 0, 1];
+            update.extend_from_slice(&[0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+            stream.write_all(&update).unwrap();
+        });
+        let vnc = Vnc::connect(addr, None).unwrap();
+        assert!(matches!(
+            vnc.capture(Rect::new(0, 0, 1, 1)),
+            Err(VncError::Protocol(_))
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_keysyms() {
+        assert_eq!(keysym(Key::F(1)).unwrap(), 0xffbe);
+        assert_eq!(char_keysym('A'), 0x41);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('€'), 0x0100_20ac);
+        assert!(keysym(Key::Code(0x41)).is_err());
+    }
+
+    #[test]
+    fn test_vnc_auth_response() {
+        let challenge: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let response = vnc_auth_response("password", challenge);
+        assert_eq!(
+            response,
+            [
+                0xb8, 0x66, 0x92, 0x41, 0x25, 0xc8, 0xee, 0xbb, 0x9d, 0xeb, 0xc1, 0xdb, 0x61, 0xc5,
+                0x38, 0xe2
+            ]
+        );
+        // Only the first eight characters of a password count.
+        assert_eq!(response, vnc_auth_response("password123", challenge));
+    }
+}
@@ -0,0 +1,1012 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use winapi::um::winuser::{
+    VK_ADD, VK_CANCEL, VK_CLEAR, VK_DECIMAL, VK_DIVIDE, VK_HELP, VK_MULTIPLY, VK_NUMPAD0, VK_PAUSE,
+    VK_RCONTROL, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SEPARATOR, VK_SUBTRACT,
+};
+
+use crate::{
+    access::{constant_time_eq, Slots},
+    keyboard::{Key, Keyboard},
+    mouse::{ButtonAction, Mouse, MouseButton, MouseError},
+    scroll::{ScrollAmount, ScrollAxis},
+};
+
+// Requests larger than this are refused rather than buffered.
+const MAX_BODY: usize = 1 << 20;
+const MAX_LINE: usize = 8 << 10;
+const MAX_HEADERS: usize = 100;
+
+// Idle connections are closed so that they do not hold on to a connection slot.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// How often animated pointer moves and scrolls are stepped.
+const MOVE_STEP: Duration = Duration::from_millis(10);
+
+// Serves the W3C WebDriver session, Perform Actions and Release Actions endpoints so
+// that existing WebDriver clients can drive the desktop. There is only one desktop,
+// so only one session can exist at a time.
+//
+// With a token, every path must start with it, as in http://host:4444/<token>/session;
+// WebDriver clients take such a prefix as part of their remote URL.
+#[derive(Debug, Clone)]
+pub struct WebDriver {
+    pub token: Option<String>,
+    pub max_connections: usize,
+    active: Slots,
+}
+
+impl WebDriver {
+    pub fn new() -> Self {
+        Self {
+            token: None,
+            max_connections: 8,
+            active: Slots::default(),
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    pub fn serve<A: ToSocketAddrs>(&self, addr: A) -> Result<(), io::Error> {
+        let listener = self.bind(addr)?;
+        let state = Arc::new(Mutex::new(None));
+        for stream in listener.incoming() {
+            let stream = stream?;
+            // Connections over the limit are closed straight away.
+            let Some(slot) = self.active.claim(self.max_connections) else {
+                continue;
+            };
+            let state = state.clone();
+            let token = self.token.clone();
+            thread::spawn(move || {
+                let _slot = slot;
+                stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+                handle_connection(stream, token.as_deref(), &state)
+            });
+        }
+        Ok(())
+    }
+
+    // Anyone who can reach the port can drive the desktop, so only loopback is allowed
+    // without a token.
+    fn bind<A: ToSocketAddrs>(&self, addr: A) -> Result<TcpListener, io::Error> {
+        let listener = TcpListener::bind(addr)?;
+        if self.token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "A token is required to listen on a non-loopback address",
+            ));
+        }
+        Ok(listener)
+    }
+}
+
+impl Default for WebDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Session {
+    id: String,
+    mouse: Mouse,
+    keyboard: Keyboard,
+    // Keys and buttons in the order they went down, so they can be released in reverse.
+    pressed: Vec<Pressed>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pressed {
+    Key(Key),
+    Button(MouseButton),
+}
+
+// An action with a duration. Every animation in a tick is stepped in turn until the tick
+// ends, so they run alongside each other rather than one after another.
+#[derive(Debug)]
+enum Animation {
+    Move {
+        start: (i32, i32),
+        distance: (i32, i32),
+        duration: Duration,
+    },
+    Scroll {
+        total: (i32, i32),
+        sent: (i32, i32),
+        duration: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    Viewport,
+    Pointer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TickAction {
+    Pause,
+    KeyDown(Key),
+    KeyUp(Key),
+    PointerMove {
+        x: i32,
+        y: i32,
+        origin: Origin,
+        duration: u64,
+    },
+    PointerDown(MouseButton),
+    PointerUp(MouseButton),
+    Scroll {
+        x: i32,
+        y: i32,
+        delta_x: i32,
+        delta_y: i32,
+        duration: u64,
+    },
+}
+
+// A tick lasts as long as the longest duration of the actions in it.
+#[derive(Debug, Default, PartialEq)]
+struct Tick {
+    duration: u64,
+    actions: Vec<TickAction>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Failure {
+    status: u16,
+    error: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            error: "invalid argument",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<MouseError> for Failure {
+    fn from(err: MouseError) -> Self {
+        let error = match err {
+            MouseError::OutOfBounds => "move target out of bounds",
+            _ => "unknown error",
+        };
+        Self {
+            status: 500,
+            error,
+            message: err.to_string(),
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    close: bool,
+    body: Vec<u8>,
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    token: Option<&str>,
+    state: &Mutex<Option<Session>>,
+) -> Result<(), io::Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    while let Some(mut request) = read_request(&mut reader)? {
+        let routed = authorize(token, &mut request).and_then(|()| route(state, &request));
+        let (status, body) = match routed {
+            Ok(value) => (200, json!({ "value": value })),
+            Err(failure) => (
+                failure.status,
+                json!({ "value": {
+                    "error": failure.error,
+                    "message": failure.message,
+                    "stacktrace": "",
+                } }),
+            ),
+        };
+        write_response(&mut writer, status, &body)?;
+        if request.close {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Strips the token from the front of the path, or refuses the request without it.
+fn authorize(token: Option<&str>, request: &mut Request) -> Result<(), Failure> {
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let path = request.path.strip_prefix('/').unwrap_or(&request.path);
+    let (given, rest) = path.split_once('/').unwrap_or((path, ""));
+    if !constant_time_eq(given.as_bytes(), token.as_bytes()) {
+        return Err(Failure {
+            status: 401,
+            error: "unknown error",
+            message: "Invalid token".to_string(),
+        });
+    }
+    request.path = format!("/{}", rest);
+    Ok(())
+}
+
+// Reads one line of the request head, refusing lines that would not fit in MAX_LINE.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<usize, io::Error> {
+    line.clear();
+    let read = reader.by_ref().take(MAX_LINE as u64 + 1).read_line(line)?;
+    if line.len() > MAX_LINE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Request line too long",
+        ));
+    }
+    Ok(read)
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Option<Request>, io::Error> {
+    let mut line = String::new();
+    if read_line(reader, &mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Bad request line",
+        ));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        // Query strings carry nothing for the endpoints we serve.
+        path: path.split('?').next().unwrap_or_default().to_string(),
+        close: parts.next() == Some("HTTP/1.0"),
+        body: Vec::new(),
+    };
+    let mut length = 0;
+    for headers in 0.. {
+        if read_line(reader, &mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Too many headers",
+            ));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Bad Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("connection") {
+            request.close = value.eq_ignore_ascii_case("close");
+        }
+    }
+    if length > MAX_BODY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Request body too large",
+        ));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(Some(request))
+}
+
+fn write_response(writer: &mut impl Write, status: u16, body: &Value) -> Result<(), io::Error> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\n\
+         Content-Length: {}\r\nCache-Control: no-cache\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+fn route(state: &Mutex<Option<Session>>, request: &Request) -> Result<Value, Failure> {
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    let mut state = state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => Ok(json!({
+            "ready": state.is_none(),
+            "message": if state.is_none() { "ready" } else { "a session is running" },
+        })),
+        ("POST", ["session"]) => {
+            if state.is_some() {
+                return Err(Failure {
+                    status: 500,
+                    error: "session not created",
+                    message: "Only one session can run at a time".to_string(),
+                });
+            }
+            let id = new_session_id();
+            *state = Some(Session {
+                id: id.clone(),
+                mouse: Mouse::new(),
+                keyboard: Keyboard::new(),
+                pressed: Vec::new(),
+            });
+            Ok(json!({
+                "sessionId": id,
+                "capabilities": { "browserName": "robot_rs", "platformName": "windows" },
+            }))
+        }
+        ("DELETE", ["session", id]) => {
+            session(&mut state, id)?.release_actions()?;
+            *state = None;
+            Ok(Value::Null)
+        }
+        ("POST", ["session", id, "actions"]) => {
+            let body: Value = serde_json::from_slice(&request.body)
+                .map_err(|err| Failure::invalid_argument(err.to_string()))?;
+            let ticks = parse_ticks(&body)?;
+            session(&mut state, id)?.perform_actions(ticks)?;
+            Ok(Value::Null)
+        }
+        ("DELETE", ["session", id, "actions"]) => {
+            session(&mut state, id)?.release_actions()?;
+            Ok(Value::Null)
+        }
+        (_, ["status"] | ["session"] | ["session", _] | ["session", _, "actions"]) => {
+            Err(Failure {
+                status: 405,
+                error: "unknown method",
+                message: format!("{} is not supported for {}", request.method, request.path),
+            })
+        }
+        _ => Err(Failure {
+            status: 404,
+            error: "unknown command",
+            message: format!("No command at {}", request.path),
+        }),
+    }
+}
+
+fn session<'a>(state: &'a mut Option<Session>, id: &str) -> Result<&'a mut Session, Failure> {
+    state
+        .as_mut()
+        .filter(|session| session.id == id)
+        .ok_or_else(|| Failure {
+            status: 404,
+            error: "invalid session id",
+            message: format!("No session {}", id),
+        })
+}
+
+fn new_session_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("{:032x}", nanos ^ ((std::process::id() as u128) << 64))
+}
+
+impl Session {
+    fn perform_actions(&mut self, ticks: Vec<Tick>) -> Result<(), Failure> {
+        for tick in ticks {
+            let start = Instant::now();
+            let mut animations = Vec::new();
+            for action in tick.actions {
+                if let Some(animation) = self.dispatch(action)? {
+                    animations.push(animation);
+                }
+            }
+            let duration = Duration::from_millis(tick.duration);
+            loop {
+                let elapsed = start.elapsed();
+                for animation in &mut animations {
+                    self.step(animation, elapsed)?;
+                }
+                let Some(remaining) = duration.checked_sub(elapsed) else {
+                    break;
+                };
+                if remaining.is_zero() {
+                    break;
+                }
+                thread::sleep(if animations.is_empty() {
+                    remaining
+                } else {
+                    remaining.min(MOVE_STEP)
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn dispatch(&mut self, action: TickAction) -> Result<Option<Animation>, Failure> {
+        match action {
+            TickAction::Pause => {}
+            TickAction::KeyDown(key) => {
+                if !self.pressed.contains(&Pressed::Key(key)) {
+                    self.keyboard.press_key(key)?;
+                    self.pressed.push(Pressed::Key(key));
+                }
+            }
+            TickAction::KeyUp(key) => {
+                if self.pressed.contains(&Pressed::Key(key)) {
+                    self.keyboard.release_key(key)?;
+                    self.pressed.retain(|&down| down != Pressed::Key(key));
+                }
+            }
+            TickAction::PointerMove {
+                x,
+                y,
+                origin,
+                duration,
+            } => {
+                let (start_x, start_y) = self.mouse.get_current_position()?;
+                let target = match origin {
+                    Origin::Viewport => Some((x, y)),
+                    Origin::Pointer => start_x.checked_add(x).zip(start_y.checked_add(y)),
+                };
+                let distance =
+                    target.and_then(|(x, y)| x.checked_sub(start_x).zip(y.checked_sub(start_y)));
+                let distance = distance.ok_or(MouseError::OutOfBounds)?;
+                let mut animation = Animation::Move {
+                    start: (start_x, start_y),
+                    distance,
+                    duration: Duration::from_millis(duration),
+                };
+                // Take the first step now so that a move without a duration happens in
+                // order with the actions around it.
+                if !self.step(&mut animation, Duration::ZERO)? {
+                    return Ok(Some(animation));
+                }
+            }
+            TickAction::PointerDown(button) => {
+                if !self.pressed.contains(&Pressed::Button(button)) {
+                    self.mouse
+                        .simulate_mouse_button(button, ButtonAction::Press)?;
+                    self.pressed.push(Pressed::Button(button));
+                }
+            }
+            TickAction::PointerUp(button) => {
+                if self.pressed.contains(&Pressed::Button(button)) {
+                    self.mouse
+                        .simulate_mouse_button(button, ButtonAction::Release)?;
+                    self.pressed.retain(|&down| down != Pressed::Button(button));
+                }
+            }
+            TickAction::Scroll {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                duration,
+            } => {
+                self.mouse.move_to(x, y)?;
+                // WebDriver deltas follow DOM wheel events, where positive deltaY scrolls
+                // down; Windows wheel deltas scroll up.
+                let horizontal =
+                    ScrollAmount::Pixels(delta_x as f64).to_wheel_units(ScrollAxis::Horizontal)?;
+                let vertical =
+                    ScrollAmount::Pixels(-(delta_y as f64)).to_wheel_units(ScrollAxis::Vertical)?;
+                let mut animation = Animation::Scroll {
+                    total: (horizontal, vertical),
+                    sent: (0, 0),
+                    duration: Duration::from_millis(duration),
+                };
+                if !self.step(&mut animation, Duration::ZERO)? {
+                    return Ok(Some(animation));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // Brings an animation up to date with the time since its tick started, and returns
+    // whether it has finished.
+    fn step(&mut self, animation: &mut Animation, elapsed: Duration) -> Result<bool, Failure> {
+        let progress = |duration: Duration| {
+            if elapsed >= duration {
+                1.0
+            } else {
+                elapsed.as_secs_f64() / duration.as_secs_f64()
+            }
+        };
+        match animation {
+            Animation::Move {
+                start,
+                distance,
+                duration,
+            } => {
+                let progress = progress(*duration);
+                // Both stay between the start and the target, so neither can overflow.
+                let x = start.0 + (distance.0 as f64 * progress) as i32;
+                let y = start.1 + (distance.1 as f64 * progress) as i32;
+                self.mouse.move_to(x, y)?;
+                Ok(progress >= 1.0)
+            }
+            Animation::Scroll {
+                total,
+                sent,
+                duration,
+            } => {
+                let progress = progress(*duration);
+                // Rounding against the running total keeps the sum exact.
+                let horizontal = (total.0 as f64 * progress).round() as i32 - sent.0;
+                if horizontal != 0 {
+                    self.mouse.scroll_horizontal(horizontal)?;
+                    sent.0 += horizontal;
+                }
+                let vertical = (total.1 as f64 * progress).round() as i32 - sent.1;
+                if vertical != 0 {
+                    self.mouse.scroll(vertical)?;
+                    sent.1 += vertical;
+                }
+                Ok(progress >= 1.0)
+            }
+        }
+    }
+
+    // Releases keys and buttons in the reverse of the order they were pressed.
+    fn release_actions(&mut self) -> Result<(), Failure> {
+        let mut result = Ok(());
+        while let Some(pressed) = self.pressed.pop() {
+            let released = match pressed {
+                Pressed::Key(key) => self.keyboard.release_key(key),
+                Pressed::Button(button) => self
+                    .mouse
+                    .simulate_mouse_button(button, ButtonAction::Release),
+            };
+            result = result.and(released);
+        }
+        Ok(result?)
+    }
+}
+
+// Transposes the per-source action lists into ticks: tick n holds the nth action of
+// every input source.
+fn parse_ticks(body: &Value) -> Result<Vec<Tick>, Failure> {
+    let sources = body["actions"]
+        .as_array()
+        .ok_or_else(|| Failure::invalid_argument("actions must be an array"))?;
+    let mut ticks: Vec<Tick> = Vec::new();
+    for source in sources {
+        let kind = source["type"].as_str().unwrap_or_default();
+        if kind == "pointer" {
+            let pointer_type = source["parameters"]["pointerType"].as_str();
+            if !matches!(pointer_type, None | Some("mouse")) {
+                return Err(Failure {
+                    status: 500,
+                    error: "unsupported operation",
+                    message: format!("Pointer type {} is not supported", pointer_type.unwrap()),
+                });
+            }
+        }
+        let actions = source["actions"]
+            .as_array()
+            .ok_or_else(|| Failure::invalid_argument("Source actions must be an array"))?;
+        for (index, action) in actions.iter().enumerate() {
+            if ticks.len() <= index {
+                ticks.push(Tick::default());
+            }
+            let (action, duration) = parse_action(kind, action)?;
+            let tick = &mut ticks[index];
+            tick.duration = tick.duration.max(duration);
+            tick.actions.push(action);
+        }
+    }
+    Ok(ticks)
+}
+
+fn parse_action(source: &str, action: &Value) -> Result<(TickAction, u64), Failure> {
+    let kind = action["type"].as_str().unwrap_or_default();
+    let duration = optional_number(action, "duration")?.unwrap_or(0);
+    let duration = u64::try_from(duration)
+        .map_err(|_| Failure::invalid_argument("duration must not be negative"))?;
+    let parsed = match (source, kind) {
+        ("none" | "key" | "pointer" | "wheel", "pause") => TickAction::Pause,
+        ("key", "keyDown") => TickAction::KeyDown(parse_key(action)?),
+        ("key", "keyUp") => TickAction::KeyUp(parse_key(action)?),
+        ("pointer", "pointerDown") => TickAction::PointerDown(parse_button(action)?),
+        ("pointer", "pointerUp") => TickAction::PointerUp(parse_button(action)?),
+        ("pointer", "pointerMove") => TickAction::PointerMove {
+            x: number(action, "x")?,
+            y: number(action, "y")?,
+            origin: parse_origin(action)?,
+            duration,
+        },
+        ("wheel", "scroll") => {
+            if parse_origin(action)? != Origin::Viewport {
+                return Err(Failure::invalid_argument(
+                    "Scroll origin must be the viewport",
+                ));
+            }
+            TickAction::Scroll {
+                x: number(action, "x")?,
+                y: number(action, "y")?,
+                delta_x: number(action, "deltaX")?,
+                delta_y: number(action, "deltaY")?,
+                duration,
+            }
+        }
+        _ => {
+            return Err(Failure::invalid_argument(format!(
+                "Unknown {} action {}",
+                source, kind
+            )))
+        }
+    };
+    let duration = match parsed {
+        TickAction::Pause | TickAction::PointerMove { .. } | TickAction::Scroll { .. } => duration,
+        _ => 0,
+    };
+    Ok((parsed, duration))
+}
+
+fn optional_number(action: &Value, name: &str) -> Result<Option<i64>, Failure> {
+    match &action[name] {
+        Value::Null => Ok(None),
+        value => value
+            .as_i64()
+            .map(Some)
+            .ok_or_else(|| Failure::invalid_argument(format!("{} must be an integer", name))),
+    }
+}
+
+fn number(action: &Value, name: &str) -> Result<i32, Failure> {
+    let value = optional_number(action, name)?.unwrap_or(0);
+    i32::try_from(value).map_err(|_| Failure::invalid_argument(format!("{} is out of range", name)))
+}
+
+fn parse_origin(action: &Value) -> Result<Origin, Failure> {
+    match &action["origin"] {
+        Value::Null => Ok(Origin::Viewport),
+        Value::String(origin) if origin == "viewport" => Ok(Origin::Viewport),
+        Value::String(origin) if origin == "pointer" => Ok(Origin::Pointer),
+        // Element origins need a DOM, which the desktop does not have.
+        _ => Err(Failure::invalid_argument(
+            "origin must be \"viewport\" or \"pointer\"",
+        )),
+    }
+}
+
+fn parse_button(action: &Value) -> Result<MouseButton, Failure> {
+    match action["button"].as_u64() {
+        Some(0) => Ok(MouseButton::Left),
+        Some(1) => Ok(MouseButton::Middle),
+        Some(2) => Ok(MouseButton::Right),
+        Some(3) => Ok(MouseButton::Back),
+        Some(4) => Ok(MouseButton::Forward),
+        _ => Err(Failure::invalid_argument("button must be 0 to 4")),
+    }
+}
+
+fn parse_key(action: &Value) -> Result<Key, Failure> {
+    let value = action["value"].as_str().unwrap_or_default();
+    let mut chars = value.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return Err(Failure::invalid_argument(
+            "value must be a single character",
+        ));
+    };
+    key_for_char(c).ok_or_else(|| {
+        Failure::invalid_argument(format!("Key U+{:04X} is not supported", c as u32))
+    })
+}
+
+// WebDriver sends special keys as code points in a private use area.
+fn key_for_char(c: char) -> Option<Key> {
+    let code = |vk: i32| Some(Key::Code(vk as u16));
+    match c as u32 {
+        0xE001 => code(VK_CANCEL),
+        0xE002 => code(VK_HELP),
+        0xE003 => Some(Key::Backspace),
+        0xE004 => Some(Key::Tab),
+        0xE005 => code(VK_CLEAR),
+        0xE006 | 0xE007 => Some(Key::Enter),
+        0xE008 => Some(Key::Shift),
+        0xE009 => Some(Key::Control),
+        0xE00A => Some(Key::Alt),
+        0xE00B => code(VK_PAUSE),
+        0xE00C => Some(Key::Escape),
+        0xE00D => Some(Key::Space),
+        0xE00E | 0xE054 => Some(Key::PageUp),
+        0xE00F | 0xE055 => Some(Key::PageDown),
+        0xE010 | 0xE056 => Some(Key::End),
+        0xE011 | 0xE057 => Some(Key::Home),
+        0xE012 | 0xE058 => Some(Key::Left),
+        0xE013 | 0xE059 => Some(Key::Up),
+        0xE014 | 0xE05A => Some(Key::Right),
+        0xE015 | 0xE05B => Some(Key::Down),
+        0xE016 | 0xE05C => Some(Key::Insert),
+        0xE017 | 0xE05D => Some(Key::Delete),
+        0xE018 => Some(Key::Char(';')),
+        0xE019 => Some(Key::Char('=')),
+        n @ 0xE01A..=0xE023 => code(VK_NUMPAD0 + (n - 0xE01A) as i32),
+        0xE024 => code(VK_MULTIPLY),
+        0xE025 => code(VK_ADD),
+        0xE026 => code(VK_SEPARATOR),
+        0xE027 => code(VK_SUBTRACT),
+        0xE028 => code(VK_DECIMAL),
+        0xE029 => code(VK_DIVIDE),
+        n @ 0xE031..=0xE03C => Some(Key::F((n - 0xE030) as u8)),
+        0xE03D => Some(Key::Meta),
+        0xE050 => code(VK_RSHIFT),
+        0xE051 => code(VK_RCONTROL),
+        0xE052 => code(VK_RMENU),
+        0xE053 => code(VK_RWIN),
+        0xE000..=0xF8FF => None,
+        // Typed as the character itself, so "A" and "!" do not depend on the Shift key.
+        _ => Some(Key::Unicode(c)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_ticks() {
+        let body = json!({ "actions": [
+            { "type": "pointer", "id": "mouse", "parameters": { "pointerType": "mouse" },
+              "actions": [
+                { "type": "pointerMove", "x": 10, "y": 20, "duration": 100 },
+                { "type": "pointerDown", "button": 0 },
+                { "type": "pointerUp", "button": 0 },
+            ] },
+            { "type": "key", "id": "keyboard", "actions": [
+                { "type": "keyDown", "value": "\u{E008}" },
+                { "type": "pause", "duration": 250 },
+            ] },
+        ] });
+        let ticks = parse_ticks(&body).unwrap();
+        assert_eq!(ticks.len(), 3);
+        assert_eq!(ticks[0].duration, 100);
+        assert_eq!(
+            ticks[0].actions,
+            vec![
+                TickAction::PointerMove {
+                    x: 10,
+                    y: 20,
+                    origin: Origin::Viewport,
+                    duration: 100,
+                },
+                TickAction::KeyDown(Key::Shift),
+            ]
+        );
+        assert_eq!(ticks[1].duration, 250);
+        assert_eq!(
+            ticks[2].actions,
+            vec![TickAction::PointerUp(MouseButton::Left)]
+        );
+    }
+
+    #[test]
+    fn test_printable_keys_are_typed_as_characters() {
+        let body = json!({ "actions": [{ "type": "key", "id": "keyboard", "actions": [
+            { "type": "keyDown", "value": "A" },
+            { "type": "keyUp", "value": "A" },
+        ] }] });
+        let ticks = parse_ticks(&body).unwrap();
+        assert_eq!(
+            ticks[0].actions,
+            vec![TickAction::KeyDown(Key::Unicode('A'))]
+        );
+        assert_eq!(ticks[1].actions, vec![TickAction::KeyUp(Key::Unicode('A'))]);
+    }
+
+    #[test]
+    fn test_parse_invalid_actions() {
+        let touch = json!({ "actions": [{ "type": "pointer", "id": "finger",
+            "parameters": { "pointerType": "touch" }, "actions": [] }] });
+        assert_eq!(
+            parse_ticks(&touch).unwrap_err().error,
+            "unsupported operation"
+        );
+        let bad_button = json!({ "actions": [{ "type": "pointer", "id": "mouse",
+            "actions": [{ "type": "pointerDown", "button": 7 }] }] });
+        assert_eq!(parse_ticks(&bad_button).unwrap_err().status, 400);
+        let key_on_pointer = json!({ "actions": [{ "type": "pointer", "id": "mouse",
+            "actions": [{ "type": "keyDown", "value": "a" }] }] });
+        assert!(parse_ticks(&key_on_pointer).is_err());
+        assert!(parse_ticks(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_key_for_char() {
+        assert_eq!(key_for_char('a'), Some(Key::Unicode('a')));
+        assert_eq!(key_for_char('A'), Some(Key::Unicode('A')));
+        assert_eq!(key_for_char('!'), Some(Key::Unicode('!')));
+        assert_eq!(key_for_char('\u{E007}'), Some(Key::Enter));
+        assert_eq!(key_for_char('\u{E031}'), Some(Key::F(1)));
+        assert_eq!(key_for_char('\u{E03C}'), Some(Key::F(12)));
+        assert_eq!(key_for_char('\u{E000}'), None);
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /session/abc/actions?x=1 HTTP/1.1\r\nContent-Length: 2\r\n\
+                   Connection: close\r\n\r\n{}";
+        let request = read_request(&mut Cursor::new(raw)).unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/session/abc/actions");
+        assert!(request.close);
+        assert_eq!(request.body, b"{}");
+        assert!(read_request(&mut Cursor::new("")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_request_limits() {
+        let long = format!(
+            "GET /status HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_LINE)
+        );
+        assert!(read_request(&mut Cursor::new(long)).is_err());
+        let many = format!("GET /status HTTP/1.1\r\n{}\r\n", "X: a\r\n".repeat(200));
+        assert!(read_request(&mut Cursor::new(many)).is_err());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let mut request = Request {
+            method: "GET".to_string(),
+            path: "/secret/session/abc".to_string(),
+            close: false,
+            body: Vec::new(),
+        };
+        assert!(authorize(None, &mut request).is_ok());
+        assert_eq!(request.path, "/secret/session/abc");
+        assert!(authorize(Some("secret"), &mut request).is_ok());
+        assert_eq!(request.path, "/session/abc");
+        assert_eq!(
+            authorize(Some("secret"), &mut request).unwrap_err().status,
+            401
+        );
+    }
+
+    #[test]
+    fn test_non_loopback_needs_token() {
+        let refused = WebDriver::new().bind("0.0.0.0:0").unwrap_err();
+        assert_eq!(refused.kind(), io::ErrorKind::PermissionDenied);
+        assert!(WebDriver::new().bind("127.0.0.1:0").is_ok());
+        assert!(WebDriver::new().token("secret").bind("0.0.0.0:0").is_ok());
+    }
+
+    #[test]
+    fn test_routes() {
+        let state = Mutex::new(None);
+        let request = |method: &str, path: &str, body: &str| Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            close: false,
+            body: body.as_bytes().to_vec(),
+        };
+        let created = route(&state, &request("POST", "/session", "{}")).unwrap();
+        let id = created["sessionId"].as_str().unwrap().to_string();
+        let failure = route(&state, &request("POST", "/session", "{}")).unwrap_err();
+        assert_eq!(failure.error, "session not created");
+
+        let actions = r#"{"actions":[{"type":"none","id":"n","actions":[{"type":"pause"}]}]}"#;
+        let path = format!("/session/{}/actions", id);
+        assert!(route(&state, &request("POST", &path, actions)).is_ok());
+        assert!(route(&state, &request("DELETE", &path, "")).is_ok());
+        let failure = route(&state, &request("POST", "/session/nope/actions", actions));
+        assert_eq!(failure.unwrap_err().error, "invalid session id");
+        assert_eq!(
+            route(&state, &request("GET", &path, "")).unwrap_err().error,
+            "unknown method"
+        );
+        assert!(route(&state, &request("DELETE", &format!("/session/{}", id), "")).is_ok());
+        assert!(state.lock().unwrap().is_none());
+    }
+
+    fn session() -> Session {
+        Session {
+            id: String::new(),
+            mouse: Mouse::new(),
+            keyboard: Keyboard::new(),
+            pressed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pointer_origin_overflow() {
+        let mut session = session();
+        session.mouse.move_to(10, 10).unwrap();
+        let ticks = vec![Tick {
+            duration: 0,
+            actions: vec![TickAction::PointerMove {
+                x: i32::MAX,
+                y: 0,
+                origin: Origin::Pointer,
+                duration: 0,
+            }],
+        }];
+        let failure = session.perform_actions(ticks).unwrap_err();
+        assert_eq!(failure.error, "move target out of bounds");
+    }
+
+    #[test]
+    fn test_tick_actions_run_alongside_each_other() {
+        let mut session = session();
+        session.mouse.move_to(100, 100).unwrap();
+        let ticks = vec![Tick {
+            duration: 200,
+            actions: vec![
+                TickAction::PointerMove {
+                    x: 200,
+                    y: 100,
+                    origin: Origin::Viewport,
+                    duration: 200,
+                },
+                TickAction::Scroll {
+                    x: 200,
+                    y: 100,
+                    delta_x: 0,
+                    delta_y: 50,
+                    duration: 200,
+                },
+            ],
+        }];
+        let start = Instant::now();
+        session.perform_actions(ticks).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(350));
+        assert_eq!(session.mouse.get_mouse_position(), (200, 100));
+    }
+
+    #[test]
+    fn test_release_actions_in_reverse_order() {
+        let mut session = session();
+        let ticks = vec![Tick {
+            duration: 0,
+            actions: vec![
+                TickAction::KeyDown(Key::Shift),
+                TickAction::PointerDown(MouseButton::Left),
+                TickAction::KeyDown(Key::Control),
+            ],
+        }];
+        session.perform_actions(ticks).unwrap();
+        assert_eq!(
+            session.pressed,
+            vec![
+                Pressed::Key(Key::Shift),
+                Pressed::Button(MouseButton::Left),
+                Pressed::Key(Key::Control),
+            ]
+        );
+        session.release_actions().unwrap();
+        assert!(session.pressed.is_empty());
+    }
+}
@@ -0,0 +1,32 @@
+pub(crate) fn to_wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(Some(0)).collect()
+}
+
+pub(crate) fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_wide_is_nul_terminated() {
+        assert_eq!(to_wide("ab"), vec![b'a' as u16, b'b' as u16, 0]);
+    }
+
+    #[test]
+    fn test_from_wide_stops_at_nul() {
+        assert_eq!(from_wide(&[b'a' as u16, b'b' as u16, 0, b'c' as u16]), "ab");
+        assert_eq!(from_wide(&[b'x' as u16]), "x");
+    }
+
+    #[test]
+    fn test_wide_round_trip() {
+        assert_eq!(
+            from_wide(&to_wide("Notepad — ünïcode")),
+            "Notepad — ünïcode"
+        );
+    }
+}
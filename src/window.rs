@@ -0,0 +1,450 @@
+use std::{
+    error::Error,
+    fmt, io, ptr,
+    time::{Duration, Instant},
+};
+use winapi::{
+    shared::{
+        minwindef::{BOOL, LPARAM, TRUE},
+        windef::{HWND, POINT, RECT},
+    },
+    um::{
+        processthreadsapi::GetCurrentThreadId,
+        winuser::{
+            AttachThreadInput, BringWindowToTop, ClientToScreen, EnumWindows, FindWindowW,
+            GetAncestor, GetClassNameW, GetClientRect, GetForegroundWindow, GetWindowRect,
+            GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
+            IsWindowVisible, IsZoomed, PostMessageW, SetForegroundWindow, SetWindowPos, ShowWindow,
+            WindowFromPoint, GA_ROOT, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER,
+            SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, WM_CLOSE,
+        },
+    },
+};
+
+use crate::wide::{from_wide, to_wide};
+
+#[derive(Debug)]
+pub enum WindowError {
+    NotFound,
+    Closed,
+    Timeout,
+    IoError(io::Error),
+}
+
+impl Error for WindowError {}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowError::NotFound => write!(f, "Window not found"),
+            WindowError::Closed => write!(f, "Window no longer exists"),
+            WindowError::Timeout => write!(f, "Timed out waiting for window"),
+            WindowError::IoError(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for WindowError {
+    fn from(err: io::Error) -> Self {
+        WindowError::IoError(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left && y >= self.top && x < self.right && y < self.bottom
+    }
+}
+
+impl From<RECT> for Rect {
+    fn from(rect: RECT) -> Self {
+        Self::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    hwnd: HWND,
+}
+
+// Window handles are global to the session and may be used from any thread.
+unsafe impl Send for Window {}
+unsafe impl Sync for Window {}
+
+impl Window {
+    fn from_hwnd(hwnd: HWND) -> Option<Self> {
+        if hwnd.is_null() {
+            None
+        } else {
+            Some(Self { hwnd })
+        }
+    }
+
+    pub fn find_by_title(title: &str) -> Result<Self, WindowError> {
+        let title = to_wide(title);
+        let hwnd = unsafe { FindWindowW(ptr::null(), title.as_ptr()) };
+        Self::from_hwnd(hwnd).ok_or(WindowError::NotFound)
+    }
+
+    pub fn find_by_class(class_name: &str) -> Result<Self, WindowError> {
+        let class_name = to_wide(class_name);
+        let hwnd = unsafe { FindWindowW(class_name.as_ptr(), ptr::null()) };
+        Self::from_hwnd(hwnd).ok_or(WindowError::NotFound)
+    }
+
+    pub fn find<P>(predicate: P) -> Result<Self, WindowError>
+    where
+        P: Fn(&Window) -> bool,
+    {
+        Self::all()
+            .into_iter()
+            .find(|window| predicate(window))
+            .ok_or(WindowError::NotFound)
+    }
+
+    pub fn wait_for<P>(predicate: P, timeout: Duration) -> Result<Self, WindowError>
+    where
+        P: Fn(&Window) -> bool,
+    {
+        let start_time = Instant::now();
+        loop {
+            if let Ok(window) = Self::find(&predicate) {
+                return Ok(window);
+            }
+            if start_time.elapsed() >= timeout {
+                return Err(WindowError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let windows = &mut *(lparam as *mut Vec<Window>);
+            if IsWindowVisible(hwnd) != 0 {
+                windows.push(Window { hwnd });
+            }
+            TRUE
+        }
+
+        let mut windows: Vec<Window> = Vec::new();
+        unsafe { EnumWindows(Some(collect), &mut windows as *mut Vec<Window> as LPARAM) };
+        windows
+    }
+
+    pub fn foreground() -> Option<Self> {
+        Self::from_hwnd(unsafe { GetForegroundWindow() })
+    }
+
+    pub fn from_point(x: i32, y: i32) -> Option<Self> {
+        let hwnd = unsafe { WindowFromPoint(POINT { x, y }) };
+        Self::from_hwnd(hwnd)
+            .and_then(|window| Self::from_hwnd(unsafe { GetAncestor(window.hwnd, GA_ROOT) }))
+    }
+
+    pub fn title(&self) -> String {
+        let len = unsafe { GetWindowTextLengthW(self.hwnd) };
+        if len <= 0 {
+            return String::new();
+        }
+        let mut buffer = vec![0u16; len as usize + 1];
+        let copied = unsafe { GetWindowTextW(self.hwnd, buffer.as_mut_ptr(), buffer.len() as i32) };
+        from_wide(&buffer[..copied.max(0) as usize])
+    }
+
+    pub fn class_name(&self) -> String {
+        let mut buffer = [0u16; 256];
+        let copied = unsafe { GetClassNameW(self.hwnd, buffer.as_mut_ptr(), buffer.len() as i32) };
+        from_wide(&buffer[..copied.max(0) as usize])
+    }
+
+    pub fn process_id(&self) -> u32 {
+        let mut process_id = 0;
+        unsafe { GetWindowThreadProcessId(self.hwnd, &mut process_id) };
+        process_id
+    }
+
+    pub fn exists(&self) -> bool {
+        unsafe { IsWindow(self.hwnd) != 0 }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        unsafe { IsWindowVisible(self.hwnd) != 0 }
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.hwnd) != 0 }
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        unsafe { IsZoomed(self.hwnd) != 0 }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        unsafe { GetForegroundWindow() == self.hwnd }
+    }
+
+    pub fn rect(&self) -> Result<Rect, WindowError> {
+        self.ensure_exists()?;
+        let mut rect: RECT = unsafe { std::mem::zeroed() };
+        if unsafe { GetWindowRect(self.hwnd, &mut rect) } == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(rect.into())
+    }
+
+    pub fn client_rect(&self) -> Result<Rect, WindowError> {
+        self.ensure_exists()?;
+        let mut rect: RECT = unsafe { std::mem::zeroed() };
+        let mut origin = POINT { x: 0, y: 0 };
+        unsafe {
+            if GetClientRect(self.hwnd, &mut rect) == 0
+                || ClientToScreen(self.hwnd, &mut origin) == 0
+            {
+                return Err(io::Error::last_os_error().into());
+            }
+        }
+        Ok(Rect::new(
+            origin.x,
+            origin.y,
+            origin.x + rect.right,
+            origin.y + rect.bottom,
+        ))
+    }
+
+    pub fn focus(&self) -> Result<(), WindowError> {
+        self.ensure_exists()?;
+        if self.is_minimized() {
+            self.restore()?;
+        }
+        unsafe {
+            // SetForegroundWindow is refused unless the calling thread owns the current
+            // foreground window's input, so temporarily share its input queue.
+            let current_thread = GetCurrentThreadId();
+            let foreground_thread =
+                GetWindowThreadProcessId(GetForegroundWindow(), ptr::null_mut());
+            let attached = foreground_thread != 0
+                && foreground_thread != current_thread
+                && AttachThreadInput(current_thread, foreground_thread, TRUE) != 0;
+            BringWindowToTop(self.hwnd);
+            let focused = SetForegroundWindow(self.hwnd);
+            if attached {
+                AttachThreadInput(current_thread, foreground_thread, 0);
+            }
+            if focused == 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn wait_until_focused(&self, timeout: Duration) -> Result<(), WindowError> {
+        let start_time = Instant::now();
+        while !self.is_focused() {
+            self.ensure_exists()?;
+            if start_time.elapsed() >= timeout {
+                return Err(WindowError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+
+    pub fn focus_and_wait(&self, timeout: Duration) -> Result<(), WindowError> {
+        self.focus()?;
+        self.wait_until_focused(timeout)
+    }
+
+    pub fn move_to(&self, x: i32, y: i32) -> Result<(), WindowError> {
+        self.set_position(x, y, 0, 0, SWP_NOSIZE)
+    }
+
+    pub fn resize(&self, width: i32, height: i32) -> Result<(), WindowError> {
+        if width <= 0 || height <= 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput).into());
+        }
+        self.set_position(0, 0, width, height, SWP_NOMOVE)
+    }
+
+    pub fn set_rect(&self, rect: Rect) -> Result<(), WindowError> {
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput).into());
+        }
+        self.set_position(rect.left, rect.top, rect.width(), rect.height(), 0)
+    }
+
+    pub fn minimize(&self) -> Result<(), WindowError> {
+        self.show(SW_MINIMIZE)
+    }
+
+    pub fn maximize(&self) -> Result<(), WindowError> {
+        self.show(SW_MAXIMIZE)
+    }
+
+    pub fn restore(&self) -> Result<(), WindowError> {
+        self.show(SW_RESTORE)
+    }
+
+    pub fn close(&self) -> Result<(), WindowError> {
+        self.ensure_exists()?;
+        if unsafe { PostMessageW(self.hwnd, WM_CLOSE, 0, 0) } == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    pub fn wait_until_closed(&self, timeout: Duration) -> Result<(), WindowError> {
+        let start_time = Instant::now();
+        while self.exists() {
+            if start_time.elapsed() >= timeout {
+                return Err(WindowError::Timeout);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+
+    fn set_position(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        flags: u32,
+    ) -> Result<(), WindowError> {
+        self.ensure_exists()?;
+        if self.is_minimized() || self.is_maximized() {
+            self.restore()?;
+        }
+        let flags = flags | SWP_NOZORDER | SWP_NOACTIVATE;
+        if unsafe { SetWindowPos(self.hwnd, ptr::null_mut(), x, y, width, height, flags) } == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    fn show(&self, command: i32) -> Result<(), WindowError> {
+        self.ensure_exists()?;
+        // ShowWindow returns the previous visibility, not success, so there is nothing to check.
+        unsafe { ShowWindow(self.hwnd, command) };
+        Ok(())
+    }
+
+    fn ensure_exists(&self) -> Result<(), WindowError> {
+        if self.exists() {
+            Ok(())
+        } else {
+            Err(WindowError::Closed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    fn open_notepad() -> (std::process::Child, Window) {
+        let child = Command::new("notepad.exe").spawn().unwrap();
+        let window =
+            Window::wait_for(|w| w.class_name() == "Notepad", Duration::from_secs(10)).unwrap();
+        (child, window)
+    }
+
+    #[test]
+    fn test_rect_size() {
+        let rect = Rect::new(10, 20, 110, 70);
+        assert_eq!(rect.width(), 100);
+        assert_eq!(rect.height(), 50);
+    }
+
+    #[test]
+    fn test_rect_contains() {
+        let rect = Rect::new(10, 20, 110, 70);
+        assert!(rect.contains(10, 20));
+        assert!(rect.contains(109, 69));
+        assert!(!rect.contains(110, 70));
+        assert!(!rect.contains(9, 30));
+    }
+
+    #[test]
+    fn test_find_by_title_not_found() {
+        let result = Window::find_by_title("robot_rs window that does not exist");
+        assert!(matches!(result, Err(WindowError::NotFound)));
+    }
+
+    #[test]
+    fn test_all_windows() {
+        let windows = Window::all();
+        assert!(!windows.is_empty());
+        assert!(windows.iter().all(|w| w.is_visible()));
+    }
+
+    #[test]
+    fn test_foreground_window_is_focused() {
+        let window = Window::foreground().unwrap();
+        assert!(window.is_focused());
+        assert!(window.exists());
+    }
+
+    #[test]
+    fn test_wait_for_timeout() {
+        let result = Window::wait_for(|_| false, Duration::from_millis(100));
+        assert!(matches!(result, Err(WindowError::Timeout)));
+    }
+
+    #[test]
+    fn test_focus_move_resize_close() {
+        let (mut child, window) = open_notepad();
+
+        window.focus_and_wait(Duration::from_secs(5)).unwrap();
+        assert!(window.is_focused());
+
+        window.move_to(100, 120).unwrap();
+        window.resize(400, 300).unwrap();
+        let rect = window.rect().unwrap();
+        assert_eq!((rect.left, rect.top), (100, 120));
+        assert_eq!((rect.width(), rect.height()), (400, 300));
+
+        let client = window.client_rect().unwrap();
+        assert!(rect.contains(client.left, client.top));
+
+        window.minimize().unwrap();
+        assert!(window.is_minimized());
+        window.maximize().unwrap();
+        assert!(window.is_maximized());
+        window.restore().unwrap();
+        assert!(!window.is_minimized() && !window.is_maximized());
+
+        window.close().unwrap();
+        window.wait_until_closed(Duration::from_secs(5)).unwrap();
+        assert!(matches!(window.focus(), Err(WindowError::Closed)));
+        child.wait().unwrap();
+    }
+}
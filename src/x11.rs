@@ -0,0 +1,1500 @@
+use std::{
+    collections::VecDeque,
+    env, fs,
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use crate::{
+    backend::InputEvent,
+    clipboard::{decode_dib, ClipboardContents, ClipboardError, ClipboardImage},
+    mouse::{ButtonAction, MouseButton, MouseError},
+    scroll::ScrollAxis,
+};
+
+// Just enough of the X11 core protocol to read and own selections, and to move the pointer
+// through the XTEST extension. Requests are sent in little-endian byte order, which the
+// server then also uses for replies and properties.
+
+// Predefined atoms.
+const NONE: u32 = 0;
+const PRIMARY: u32 = 1;
+const ATOM: u32 = 4;
+const STRING: u32 = 31;
+
+const CURRENT_TIME: u32 = 0;
+
+const CREATE_WINDOW: u8 = 1;
+const CHANGE_WINDOW_ATTRIBUTES: u8 = 2;
+const INTERN_ATOM: u8 = 16;
+const CHANGE_PROPERTY: u8 = 18;
+const GET_PROPERTY: u8 = 20;
+const SET_SELECTION_OWNER: u8 = 22;
+const GET_SELECTION_OWNER: u8 = 23;
+const CONVERT_SELECTION: u8 = 24;
+const SEND_EVENT: u8 = 25;
+const QUERY_POINTER: u8 = 38;
+const QUERY_EXTENSION: u8 = 98;
+
+// XTEST's FakeInput request and the core event types it takes.
+const FAKE_INPUT: u8 = 2;
+const BUTTON_PRESS: u8 = 4;
+const BUTTON_RELEASE: u8 = 5;
+const MOTION_NOTIFY: u8 = 6;
+
+const PROPERTY_NOTIFY: u8 = 28;
+const SELECTION_CLEAR: u8 = 29;
+const SELECTION_REQUEST: u8 = 30;
+const SELECTION_NOTIFY: u8 = 31;
+
+const CW_EVENT_MASK: u32 = 0x0800;
+const PROPERTY_CHANGE_MASK: u32 = 0x0040_0000;
+const PROPERTY_NEW_VALUE: u8 = 0;
+const PROPERTY_DELETED: u8 = 1;
+
+// QueryPointer's mask has a bit for each of buttons 1 to 5, starting with this one.
+const BUTTON1_MASK: u16 = 0x0100;
+const WHEEL_DELTA: i32 = 120;
+
+// How long to wait for the server, or for the owner of a selection to answer.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    // Set by explicit copy commands and pasted with Ctrl+V.
+    Clipboard,
+    // Set by selecting text and pasted with a middle click.
+    Primary,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Display {
+    host: String,
+    number: u16,
+}
+
+fn parse_display(display: &str) -> Result<Display, ClipboardError> {
+    let invalid = || ClipboardError::InvalidData(format!("Invalid X display {:?}", display));
+    let (host, rest) = display.rsplit_once(':').ok_or_else(invalid)?;
+    let number: u16 = rest
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| invalid())?;
+    if number > u16::MAX - 6000 {
+        return Err(invalid());
+    }
+    Ok(Display {
+        host: host.to_string(),
+        number,
+    })
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn open(display: &Display) -> Result<Self, ClipboardError> {
+        let local = display.host.is_empty() || display.host == "unix";
+        #[cfg(unix)]
+        if local {
+            let path = format!("/tmp/.X11-unix/X{}", display.number);
+            return Ok(Stream::Unix(UnixStream::connect(path)?));
+        }
+        let host = if local { "localhost" } else { &display.host };
+        let stream = TcpStream::connect((host, 6000 + display.number))?;
+        stream.set_nodelay(true)?;
+        Ok(Stream::Tcp(stream))
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+// Looks up the MIT-MAGIC-COOKIE-1 for the display in the Xauthority file, if there is one.
+fn read_cookie(display: &Display) -> Option<Vec<u8>> {
+    let path = env::var_os("XAUTHORITY")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".Xauthority")))?;
+    find_cookie(&fs::read(path).ok()?, display.number)
+}
+
+fn find_cookie(mut entries: &[u8], number: u16) -> Option<Vec<u8>> {
+    fn field<'a>(entries: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let length = u16::from_be_bytes([*entries.first()?, *entries.get(1)?]) as usize;
+        let value = entries.get(2..2 + length)?;
+        *entries = &entries[2 + length..];
+        Some(value)
+    }
+    let number = number.to_string();
+    // Each entry is a family followed by address, display number, name and data.
+    while entries.len() > 2 {
+        entries = &entries[2..];
+        let _address = field(&mut entries)?;
+        let entry_number = field(&mut entries)?;
+        let name = field(&mut entries)?;
+        let data = field(&mut entries)?;
+        if entry_number == number.as_bytes() && name == b"MIT-MAGIC-COOKIE-1" {
+            return Some(data.to_vec());
+        }
+    }
+    None
+}
+
+fn padded(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(4) * 4, 0);
+    padded
+}
+
+fn words(values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn timed_out() -> ClipboardError {
+    ClipboardError::IoError(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "X server did not answer",
+    ))
+}
+
+struct Connection {
+    stream: Stream,
+    // Bytes read but not yet making up a whole reply or event.
+    buffer: Vec<u8>,
+    events: VecDeque<Vec<u8>>,
+    sequence: u16,
+    root: u32,
+    id_base: u32,
+    id_step: u32,
+    next_id: u32,
+    max_request: usize,
+    // Of the first screen, in pixels.
+    screen_size: (u16, u16),
+}
+
+impl Connection {
+    fn connect(display: &str) -> Result<Self, ClipboardError> {
+        let display = parse_display(display)?;
+        Self::setup(Stream::open(&display)?, read_cookie(&display))
+    }
+
+    fn setup(mut stream: Stream, cookie: Option<Vec<u8>>) -> Result<Self, ClipboardError> {
+        let (name, data): (&[u8], Vec<u8>) = match cookie {
+            Some(cookie) => (b"MIT-MAGIC-COOKIE-1", cookie),
+            None => (b"", Vec::new()),
+        };
+        let mut setup = vec![b'l', 0];
+        for value in [11, 0, name.len() as u16, data.len() as u16, 0] {
+            setup.extend_from_slice(&value.to_le_bytes());
+        }
+        setup.extend(padded(name));
+        setup.extend(padded(&data));
+        stream.write_all(&setup)?;
+
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header)?;
+        let mut body = vec![0u8; u16_at(&header, 6) as usize * 4];
+        stream.read_exact(&mut body)?;
+        match header[0] {
+            1 => {}
+            0 => {
+                let reason = body.get(..header[1] as usize).unwrap_or_default();
+                return Err(ClipboardError::InvalidData(format!(
+                    "X server refused the connection: {}",
+                    String::from_utf8_lossy(reason)
+                )));
+            }
+            _ => {
+                return Err(ClipboardError::InvalidData(
+                    "X server asked for unsupported authentication".to_string(),
+                ))
+            }
+        }
+        let truncated = || ClipboardError::InvalidData("X server setup is truncated".to_string());
+        if body.len() < 32 {
+            return Err(truncated());
+        }
+        let vendor_length = u16_at(&body, 16) as usize;
+        let screens = 32 + vendor_length.div_ceil(4) * 4 + 8 * body[21] as usize;
+        // The fixed part of a SCREEN, which is all we read of it.
+        if body.len() < screens + 40 {
+            return Err(truncated());
+        }
+        let id_mask = u32_at(&body, 8);
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            events: VecDeque::new(),
+            sequence: 0,
+            root: u32_at(&body, screens),
+            id_base: u32_at(&body, 4),
+            // Resource ids are the base plus any value that fits the mask's bits.
+            id_step: id_mask & id_mask.wrapping_neg(),
+            next_id: 0,
+            max_request: u16_at(&body, 18) as usize * 4,
+            screen_size: (u16_at(&body, screens + 20), u16_at(&body, screens + 22)),
+        })
+    }
+
+    fn send(&mut self, opcode: u8, data: u8, body: &[u8]) -> Result<u16, ClipboardError> {
+        let length = 4 + body.len();
+        if length > self.max_request {
+            return Err(ClipboardError::InvalidData(
+                "X11 request is too large".to_string(),
+            ));
+        }
+        let mut request = vec![opcode, data];
+        request.extend_from_slice(&((length / 4) as u16).to_le_bytes());
+        request.extend_from_slice(body);
+        self.stream.write_all(&request)?;
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(self.sequence)
+    }
+
+    // Returns None if the deadline passes first.
+    fn read_packet(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> Result<Option<Vec<u8>>, ClipboardError> {
+        loop {
+            if self.buffer.len() >= 32 {
+                // Replies carry a length; errors and events are always 32 bytes.
+                let length = match self.buffer[0] {
+                    1 => 32 + u32_at(&self.buffer, 4) as usize * 4,
+                    _ => 32,
+                };
+                if self.buffer.len() >= length {
+                    return Ok(Some(self.buffer.drain(..length).collect()));
+                }
+            }
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    Some(remaining)
+                }
+                None => None,
+            };
+            self.stream.set_read_timeout(timeout)?;
+            let mut chunk = [0u8; 4096];
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None)
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn reply(&mut self, sequence: u16) -> Result<Vec<u8>, ClipboardError> {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let packet = self.read_packet(Some(deadline))?.ok_or_else(timed_out)?;
+            match packet[0] {
+                0 if u16_at(&packet, 2) == sequence => {
+                    return Err(ClipboardError::InvalidData(format!(
+                        "X11 error {} for request {}",
+                        packet[1], packet[10]
+                    )))
+                }
+                1 if u16_at(&packet, 2) == sequence => return Ok(packet),
+                // Errors for requests that have no reply, e.g. writing to a window that
+                // has just been destroyed, are of no interest.
+                0 | 1 => {}
+                _ => self.events.push_back(packet),
+            }
+        }
+    }
+
+    fn event(&mut self, deadline: Option<Instant>) -> Result<Option<Vec<u8>>, ClipboardError> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+        while let Some(packet) = self.read_packet(deadline)? {
+            if packet[0] > 1 {
+                return Ok(Some(packet));
+            }
+        }
+        Ok(None)
+    }
+
+    fn intern(&mut self, name: &str) -> Result<u32, ClipboardError> {
+        let mut body = (name.len() as u16).to_le_bytes().to_vec();
+        body.extend_from_slice(&[0, 0]);
+        body.extend(padded(name.as_bytes()));
+        let sequence = self.send(INTERN_ATOM, 0, &body)?;
+        Ok(u32_at(&self.reply(sequence)?, 8))
+    }
+
+    // The major opcode of an extension, if the server has it.
+    fn extension(&mut self, name: &str) -> Result<Option<u8>, ClipboardError> {
+        let mut body = (name.len() as u16).to_le_bytes().to_vec();
+        body.extend_from_slice(&[0, 0]);
+        body.extend(padded(name.as_bytes()));
+        let sequence = self.send(QUERY_EXTENSION, 0, &body)?;
+        let reply = self.reply(sequence)?;
+        Ok((reply[8] != 0).then_some(reply[9]))
+    }
+
+    // The pointer's position on the root window and the state of buttons and modifiers.
+    fn query_pointer(&mut self) -> Result<(i32, i32, u16), ClipboardError> {
+        let sequence = self.send(QUERY_POINTER, 0, &words(&[self.root]))?;
+        let reply = self.reply(sequence)?;
+        Ok((
+            u16_at(&reply, 16) as i16 as i32,
+            u16_at(&reply, 18) as i16 as i32,
+            u16_at(&reply, 24),
+        ))
+    }
+
+    fn selection(&mut self, selection: Selection) -> Result<u32, ClipboardError> {
+        match selection {
+            Selection::Clipboard => self.intern("CLIPBOARD"),
+            Selection::Primary => Ok(PRIMARY),
+        }
+    }
+
+    // An invisible window to own selections and receive converted data on.
+    fn create_window(&mut self) -> Result<u32, ClipboardError> {
+        self.next_id += 1;
+        let window = self.id_base | (self.next_id * self.id_step);
+        let mut body = words(&[window, self.root]);
+        // x, y, width, height, border width and class InputOnly.
+        for value in [0u16, 0, 1, 1, 0, 2] {
+            body.extend_from_slice(&value.to_le_bytes());
+        }
+        body.extend(words(&[0, CW_EVENT_MASK, PROPERTY_CHANGE_MASK]));
+        self.send(CREATE_WINDOW, 0, &body)?;
+        Ok(window)
+    }
+
+    fn selection_owner(&mut self, selection: u32) -> Result<u32, ClipboardError> {
+        let sequence = self.send(GET_SELECTION_OWNER, 0, &words(&[selection]))?;
+        Ok(u32_at(&self.reply(sequence)?, 8))
+    }
+
+    // Returns the property's type and value, deleting it if asked to.
+    fn get_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        delete: bool,
+    ) -> Result<(u32, Vec<u8>), ClipboardError> {
+        let body = words(&[window, property, 0, 0, u32::MAX / 4]);
+        let sequence = self.send(GET_PROPERTY, delete as u8, &body)?;
+        let reply = self.reply(sequence)?;
+        let length = u32_at(&reply, 16) as usize * (reply[1] as usize / 8);
+        let value = reply.get(32..32 + length).ok_or_else(|| {
+            ClipboardError::InvalidData("X11 property reply is truncated".to_string())
+        })?;
+        Ok((u32_at(&reply, 8), value.to_vec()))
+    }
+
+    fn change_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        kind: u32,
+        format: u8,
+        value: &[u8],
+    ) -> Result<(), ClipboardError> {
+        let mut body = words(&[window, property, kind]);
+        body.extend_from_slice(&[format, 0, 0, 0]);
+        let units = value.len() / (format as usize / 8);
+        body.extend_from_slice(&(units as u32).to_le_bytes());
+        body.extend(padded(value));
+        self.send(CHANGE_PROPERTY, 0, &body)?;
+        Ok(())
+    }
+
+    // The most data one ChangeProperty request can carry.
+    fn max_property(&self) -> usize {
+        (self.max_request - 24).min(1 << 16)
+    }
+}
+
+// A connection with a window to receive selection data on.
+struct Reader {
+    connection: Connection,
+    window: u32,
+    selection: u32,
+    property: u32,
+    incr: u32,
+}
+
+impl Reader {
+    // Asks the selection's owner for the data as `target`. None means the owner does not
+    // offer that target, or there is no owner.
+    fn convert(&mut self, target: u32) -> Result<Option<Vec<u8>>, ClipboardError> {
+        let body = words(&[
+            self.window,
+            self.selection,
+            target,
+            self.property,
+            CURRENT_TIME,
+        ]);
+        self.connection.send(CONVERT_SELECTION, 0, &body)?;
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let event = self
+                .connection
+                .event(Some(deadline))?
+                .ok_or_else(timed_out)?;
+            if event[0] & 0x7f == SELECTION_NOTIFY && u32_at(&event, 8) == self.window {
+                if u32_at(&event, 20) == NONE {
+                    return Ok(None);
+                }
+                break;
+            }
+        }
+        let (kind, value) = self
+            .connection
+            .get_property(self.window, self.property, true)?;
+        if kind != self.incr {
+            return Ok(Some(value));
+        }
+        // Large data arrives in chunks: the owner writes the next one each time we delete
+        // the property, and ends with an empty one.
+        let mut data = Vec::new();
+        loop {
+            let deadline = Instant::now() + TIMEOUT;
+            let event = self
+                .connection
+                .event(Some(deadline))?
+                .ok_or_else(timed_out)?;
+            if event[0] & 0x7f != PROPERTY_NOTIFY
+                || u32_at(&event, 4) != self.window
+                || u32_at(&event, 8) != self.property
+                || event[16] != PROPERTY_NEW_VALUE
+            {
+                continue;
+            }
+            let (_, chunk) = self
+                .connection
+                .get_property(self.window, self.property, true)?;
+            if chunk.is_empty() {
+                return Ok(Some(data));
+            }
+            data.extend_from_slice(&chunk);
+        }
+    }
+
+    fn convert_named(&mut self, target: &str) -> Result<Option<Vec<u8>>, ClipboardError> {
+        let target = self.connection.intern(target)?;
+        self.convert(target)
+    }
+
+    fn targets(&mut self) -> Result<Vec<u32>, ClipboardError> {
+        let targets = self.convert_named("TARGETS")?.unwrap_or_default();
+        Ok(targets
+            .chunks_exact(4)
+            .map(|atom| u32_at(atom, 0))
+            .collect())
+    }
+}
+
+// Serves the data for a selection we own until another client takes it over.
+struct Owner {
+    connection: Connection,
+    selection: u32,
+    targets: u32,
+    incr: u32,
+    offers: Vec<(u32, Vec<u8>)>,
+    transfers: Vec<Transfer>,
+}
+
+// Data too large for one request, sent in chunks as the requestor reads them.
+struct Transfer {
+    requestor: u32,
+    property: u32,
+    kind: u32,
+    data: Vec<u8>,
+    sent: usize,
+}
+
+impl Owner {
+    fn serve(mut self) -> Result<(), ClipboardError> {
+        let mut cleared = false;
+        // Finish transfers already under way even after losing the selection.
+        while !cleared || !self.transfers.is_empty() {
+            let Some(event) = self.connection.event(None)? else {
+                continue;
+            };
+            match event[0] & 0x7f {
+                SELECTION_CLEAR if u32_at(&event, 12) == self.selection => cleared = true,
+                SELECTION_REQUEST if !cleared => self.answer(&event)?,
+                PROPERTY_NOTIFY if event[16] == PROPERTY_DELETED => {
+                    self.send_chunk(u32_at(&event, 4), u32_at(&event, 8))?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn answer(&mut self, request: &[u8]) -> Result<(), ClipboardError> {
+        let time = u32_at(request, 4);
+        let requestor = u32_at(request, 12);
+        let selection = u32_at(request, 16);
+        let target = u32_at(request, 20);
+        // Clients from before ICCCM 2 leave the property empty and mean the target.
+        let property = match u32_at(request, 24) {
+            NONE => target,
+            property => property,
+        };
+        let offer = self.offers.iter().find(|(atom, _)| *atom == target);
+        let answered = if target == self.targets {
+            let mut atoms = vec![self.targets];
+            atoms.extend(self.offers.iter().map(|(atom, _)| *atom));
+            self.connection
+                .change_property(requestor, property, ATOM, 32, &words(&atoms))?;
+            true
+        } else if let Some((_, data)) = offer {
+            let data = data.clone();
+            if data.len() <= self.connection.max_property() {
+                self.connection
+                    .change_property(requestor, property, target, 8, &data)?;
+            } else {
+                // Watch for the requestor deleting the property, which asks for the next
+                // chunk, before telling it a chunked transfer is coming.
+                let body = words(&[requestor, CW_EVENT_MASK, PROPERTY_CHANGE_MASK]);
+                self.connection.send(CHANGE_WINDOW_ATTRIBUTES, 0, &body)?;
+                let size = (data.len() as u32).to_le_bytes();
+                self.connection
+                    .change_property(requestor, property, self.incr, 32, &size)?;
+                self.transfers.push(Transfer {
+                    requestor,
+                    property,
+                    kind: target,
+                    data,
+                    sent: 0,
+                });
+            }
+            true
+        } else {
+            false
+        };
+
+        let mut notify = [0u8; 32];
+        notify[0] = SELECTION_NOTIFY;
+        let property = if answered { property } else { NONE };
+        notify[4..24].copy_from_slice(&words(&[time, requestor, selection, target, property]));
+        let mut body = words(&[requestor, 0]);
+        body.extend_from_slice(&notify);
+        self.connection.send(SEND_EVENT, 0, &body)?;
+        Ok(())
+    }
+
+    fn send_chunk(&mut self, requestor: u32, property: u32) -> Result<(), ClipboardError> {
+        let Some(index) = self
+            .transfers
+            .iter()
+            .position(|transfer| transfer.requestor == requestor && transfer.property == property)
+        else {
+            return Ok(());
+        };
+        let max = self.connection.max_property();
+        let transfer = &mut self.transfers[index];
+        let end = (transfer.sent + max).min(transfer.data.len());
+        let chunk = transfer.data[transfer.sent..end].to_vec();
+        transfer.sent = end;
+        let kind = transfer.kind;
+        if chunk.is_empty() {
+            self.transfers.remove(index);
+        }
+        self.connection
+            .change_property(requestor, property, kind, 8, &chunk)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct X11Clipboard {
+    display: String,
+    selection: Selection,
+}
+
+impl X11Clipboard {
+    pub(crate) fn new(display: &str, selection: Selection) -> Result<Self, ClipboardError> {
+        parse_display(display)?;
+        Ok(Self {
+            display: display.to_string(),
+            selection,
+        })
+    }
+
+    fn reader(&self) -> Result<Reader, ClipboardError> {
+        let mut connection = Connection::connect(&self.display)?;
+        let window = connection.create_window()?;
+        let selection = connection.selection(self.selection)?;
+        let property = connection.intern("ROBOT_RS_SELECTION")?;
+        let incr = connection.intern("INCR")?;
+        Ok(Reader {
+            connection,
+            window,
+            selection,
+            property,
+            incr,
+        })
+    }
+
+    // Takes ownership of the selection and serves `offers`, pairs of target name and
+    // data, from a background thread until another client takes the selection.
+    fn own(&self, offers: Vec<(&str, Vec<u8>)>) -> Result<(), ClipboardError> {
+        let mut connection = Connection::connect(&self.display)?;
+        let offers = offers
+            .into_iter()
+            .map(|(target, data)| Ok((connection.intern(target)?, data)))
+            .collect::<Result<Vec<_>, ClipboardError>>()?;
+        self.serve(connection, offers)
+    }
+
+    fn serve(
+        &self,
+        mut connection: Connection,
+        offers: Vec<(u32, Vec<u8>)>,
+    ) -> Result<(), ClipboardError> {
+        let window = connection.create_window()?;
+        let selection = connection.selection(self.selection)?;
+        let targets = connection.intern("TARGETS")?;
+        let incr = connection.intern("INCR")?;
+        connection.send(
+            SET_SELECTION_OWNER,
+            0,
+            &words(&[window, selection, CURRENT_TIME]),
+        )?;
+        if connection.selection_owner(selection)? != window {
+            return Err(ClipboardError::Unavailable);
+        }
+        let owner = Owner {
+            connection,
+            selection,
+            targets,
+            incr,
+            offers,
+            transfers: Vec::new(),
+        };
+        thread::Builder::new()
+            .name("robot_rs-x11-selection".to_string())
+            .spawn(move || owner.serve())?;
+        Ok(())
+    }
+
+    pub(crate) fn get_text(&self) -> Result<String, ClipboardError> {
+        let mut reader = self.reader()?;
+        if let Some(text) = reader.convert_named("UTF8_STRING")? {
+            return String::from_utf8(text)
+                .map_err(|_| ClipboardError::InvalidData("Text is not valid UTF-8".to_string()));
+        }
+        // STRING is Latin-1.
+        match reader.convert(STRING)? {
+            Some(text) => Ok(text.iter().map(|&byte| byte as char).collect()),
+            None => Err(ClipboardError::FormatUnavailable),
+        }
+    }
+
+    pub(crate) fn set_text(&self, text: &str) -> Result<(), ClipboardError> {
+        let latin1 = text
+            .chars()
+            .map(|c| u8::try_from(c).unwrap_or(b'?'))
+            .collect();
+        self.own(vec![
+            ("UTF8_STRING", text.as_bytes().to_vec()),
+            ("text/plain;charset=utf-8", text.as_bytes().to_vec()),
+            ("STRING", latin1),
+        ])
+    }
+
+    // Images are read as image/bmp; other formats would need a decoder for each.
+    pub(crate) fn get_image(&self) -> Result<ClipboardImage, ClipboardError> {
+        let mut reader = self.reader()?;
+        match reader.convert_named("image/bmp")? {
+            Some(bmp) => decode_bmp(&bmp),
+            None => Err(ClipboardError::FormatUnavailable),
+        }
+    }
+
+    pub(crate) fn set_image(&self, image: &ClipboardImage) -> Result<(), ClipboardError> {
+        self.own(vec![
+            ("image/png", encode_png(image)?),
+            ("image/bmp", image.to_bmp()?),
+        ])
+    }
+
+    pub(crate) fn get_files(&self) -> Result<Vec<PathBuf>, ClipboardError> {
+        let mut reader = self.reader()?;
+        match reader.convert_named("text/uri-list")? {
+            Some(list) => Ok(parse_uri_list(&String::from_utf8_lossy(&list))),
+            None => Err(ClipboardError::FormatUnavailable),
+        }
+    }
+
+    pub(crate) fn set_files(&self, files: &[&Path]) -> Result<(), ClipboardError> {
+        let uris = files
+            .iter()
+            .map(|file| file_uri(file))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut list = String::new();
+        for uri in &uris {
+            list.push_str(uri);
+            list.push_str("\r\n");
+        }
+        // File managers on GNOME and KDE only paste files given in their own format.
+        let copied = format!("copy\n{}", uris.join("\n"));
+        self.own(vec![
+            ("text/uri-list", list.into_bytes()),
+            ("x-special/gnome-copied-files", copied.into_bytes()),
+        ])
+    }
+
+    pub(crate) fn set_raw(&self, format: u32, bytes: &[u8]) -> Result<(), ClipboardError> {
+        let connection = Connection::connect(&self.display)?;
+        self.serve(connection, vec![(format, bytes.to_vec())])
+    }
+
+    pub(crate) fn set_formats(&self, formats: &[(u32, Vec<u8>)]) -> Result<(), ClipboardError> {
+        let connection = Connection::connect(&self.display)?;
+        self.serve(connection, formats.to_vec())
+    }
+
+    pub(crate) fn clear(&self) -> Result<(), ClipboardError> {
+        let mut connection = Connection::connect(&self.display)?;
+        let selection = connection.selection(self.selection)?;
+        connection.send(
+            SET_SELECTION_OWNER,
+            0,
+            &words(&[NONE, selection, CURRENT_TIME]),
+        )?;
+        // A round trip makes sure the server has handled the request before we return.
+        connection.selection_owner(selection)?;
+        Ok(())
+    }
+
+    pub(crate) fn save(&self) -> Result<ClipboardContents, ClipboardError> {
+        let mut reader = self.reader()?;
+        let targets = reader.targets()?;
+        let mut find = |name: &str| -> Result<Option<u32>, ClipboardError> {
+            let atom = reader.connection.intern(name)?;
+            Ok(targets.contains(&atom).then_some(atom))
+        };
+        let (uri_list, bmp, png, utf8) = (
+            find("text/uri-list")?,
+            find("image/bmp")?,
+            find("image/png")?,
+            find("UTF8_STRING")?,
+        );
+        let string = targets.contains(&STRING).then_some(STRING);
+        let convert = |reader: &mut Reader, target| {
+            reader
+                .convert(target)?
+                .ok_or(ClipboardError::FormatUnavailable)
+        };
+        let contents = if let Some(target) = uri_list {
+            let list = convert(&mut reader, target)?;
+            ClipboardContents::Files(parse_uri_list(&String::from_utf8_lossy(&list)))
+        } else if let Some(target) = bmp.or(png) {
+            let bytes = convert(&mut reader, target)?;
+            match decode_bmp(&bytes) {
+                Ok(image) if Some(target) == bmp => ClipboardContents::Image(image),
+                _ => ClipboardContents::Raw {
+                    format: target,
+                    bytes,
+                },
+            }
+        } else if let Some(target) = utf8.or(string) {
+            let text = convert(&mut reader, target)?;
+            ClipboardContents::Text(if Some(target) == utf8 {
+                String::from_utf8_lossy(&text).into_owned()
+            } else {
+                text.iter().map(|&byte| byte as char).collect()
+            })
+        } else {
+            ClipboardContents::Empty
+        };
+        Ok(contents)
+    }
+}
+
+// Moves the pointer of an X server, e.g. the one WSLg runs, through the XTEST extension;
+// clones share the connection. The server has to allow XTEST for remote clients.
+#[derive(Clone)]
+pub struct X11Input {
+    connection: Arc<Mutex<InputConnection>>,
+}
+
+struct InputConnection {
+    connection: Connection,
+    xtest: u8,
+    // QueryPointer only reports buttons 1 to 5, so these are the later ones we hold.
+    pressed: Vec<u8>,
+}
+
+impl X11Input {
+    pub fn connect(display: &str) -> Result<Self, MouseError> {
+        let mut connection = Connection::connect(display).map_err(input_error)?;
+        let xtest = connection
+            .extension("XTEST")
+            .map_err(input_error)?
+            .ok_or_else(|| {
+                MouseError::BackendUnavailable("X server does not offer XTEST".to_string())
+            })?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(InputConnection {
+                connection,
+                xtest,
+                pressed: Vec::new(),
+            })),
+        })
+    }
+
+    pub fn get_screen_size(&self) -> (i32, i32) {
+        let (width, height) = self.connection().connection.screen_size;
+        (width as i32, height as i32)
+    }
+
+    pub(crate) fn position(&self) -> Result<(i32, i32), MouseError> {
+        let (x, y, _) = self
+            .connection()
+            .connection
+            .query_pointer()
+            .map_err(input_error)?;
+        Ok((x, y))
+    }
+
+    // What the server reports, so presses by the user count as much as our own, except
+    // for back, forward and later buttons, which only the ones we sent.
+    pub(crate) fn is_pressed(&self, button: MouseButton) -> bool {
+        let Ok(number) = x11_button(button) else {
+            return false;
+        };
+        let mut connection = self.connection();
+        if number > 5 {
+            return connection.pressed.contains(&number);
+        }
+        connection
+            .connection
+            .query_pointer()
+            .is_ok_and(|(_, _, state)| state & (BUTTON1_MASK << (number - 1)) != 0)
+    }
+
+    pub(crate) fn send(&self, events: &[InputEvent]) -> Result<(), MouseError> {
+        self.connection().send(events)
+    }
+
+    fn connection(&self) -> MutexGuard<'_, InputConnection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl InputConnection {
+    // Encodes every event before sending any, so that an invalid one sends nothing.
+    fn send(&mut self, events: &[InputEvent]) -> Result<(), MouseError> {
+        let (width, height) = self.connection.screen_size;
+        let mut fakes = Vec::new();
+        for event in events {
+            match *event {
+                InputEvent::MoveTo { x, y } => {
+                    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                        return Err(MouseError::OutOfBounds);
+                    }
+                    fakes.push((MOTION_NOTIFY, 0, x as i16, y as i16));
+                }
+                InputEvent::Button { button, action } => {
+                    let kind = match action {
+                        ButtonAction::Press => BUTTON_PRESS,
+                        ButtonAction::Release => BUTTON_RELEASE,
+                    };
+                    fakes.push((kind, x11_button(button)?, 0, 0));
+                }
+                // Amounts are in wheel units; buttons 4 to 7 only scroll whole notches.
+                InputEvent::Scroll { axis, amount } => {
+                    let wheel = match (axis, amount > 0) {
+                        (ScrollAxis::Vertical, true) => 4,
+                        (ScrollAxis::Vertical, false) => 5,
+                        (ScrollAxis::Horizontal, false) => 6,
+                        (ScrollAxis::Horizontal, true) => 7,
+                    };
+                    let notches = (amount.abs() + WHEEL_DELTA / 2) / WHEEL_DELTA;
+                    for _ in 0..notches.max((amount != 0) as i32) {
+                        fakes.push((BUTTON_PRESS, wheel, 0, 0));
+                        fakes.push((BUTTON_RELEASE, wheel, 0, 0));
+                    }
+                }
+                InputEvent::Key { .. } | InputEvent::Char { .. } => {
+                    return Err(MouseError::BackendUnavailable(
+                        "X11 input only covers the pointer".to_string(),
+                    ))
+                }
+            }
+        }
+        for &(kind, detail, x, y) in &fakes {
+            let mut body = vec![kind, detail, 0, 0];
+            body.extend(words(&[CURRENT_TIME, self.connection.root, 0, 0]));
+            body.extend_from_slice(&x.to_le_bytes());
+            body.extend_from_slice(&y.to_le_bytes());
+            body.extend_from_slice(&[0; 8]);
+            self.connection
+                .send(self.xtest, FAKE_INPUT, &body)
+                .map_err(input_error)?;
+        }
+        for (kind, detail, _, _) in fakes {
+            if kind != MOTION_NOTIFY && detail > 7 {
+                self.pressed.retain(|&pressed| pressed != detail);
+                if kind == BUTTON_PRESS {
+                    self.pressed.push(detail);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Core protocol buttons: 1 left, 2 middle, 3 right, 4 to 7 the wheel, 8 back, 9 forward
+// and any further ones from 10 on.
+fn x11_button(button: MouseButton) -> Result<u8, MouseError> {
+    match button {
+        MouseButton::Left | MouseButton::Other(1) => Ok(1),
+        MouseButton::Middle | MouseButton::Other(3) => Ok(2),
+        MouseButton::Right | MouseButton::Other(2) => Ok(3),
+        MouseButton::Back | MouseButton::Other(4) => Ok(8),
+        MouseButton::Forward | MouseButton::Other(5) => Ok(9),
+        MouseButton::Other(number @ 6..) => number
+            .checked_add(4)
+            .ok_or(MouseError::UnsupportedButton(number)),
+        MouseButton::Other(number) => Err(MouseError::UnsupportedButton(number)),
+    }
+}
+
+fn input_error(err: ClipboardError) -> MouseError {
+    match err {
+        ClipboardError::IoError(err) => MouseError::IoError(err),
+        err => MouseError::BackendUnavailable(err.to_string()),
+    }
+}
+
+fn decode_bmp(bytes: &[u8]) -> Result<ClipboardImage, ClipboardError> {
+    // A BMP file is a DIB behind a 14-byte file header.
+    match bytes.strip_prefix(b"BM") {
+        Some(_) if bytes.len() >= 14 => decode_dib(&bytes[14..]),
+        _ => Err(ClipboardError::InvalidData("Not a BMP image".to_string())),
+    }
+}
+
+fn file_uri(path: &Path) -> Result<String, ClipboardError> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| ClipboardError::InvalidData("File path is not valid UTF-8".to_string()))?;
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    Ok(uri)
+}
+
+fn parse_uri_list(list: &str) -> Vec<PathBuf> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|uri| {
+            // Skip the host, usually empty, up to the path.
+            let rest = uri.strip_prefix("file://")?;
+            let path = &rest[rest.find('/')?..];
+            let mut bytes = Vec::with_capacity(path.len());
+            let mut input = path.bytes();
+            while let Some(byte) = input.next() {
+                let escaped = (byte == b'%')
+                    .then(|| {
+                        let hex = [input.clone().next()?, input.clone().nth(1)?];
+                        u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()
+                    })
+                    .flatten();
+                match escaped {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        input.nth(1);
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            Some(PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()))
+        })
+        .collect()
+}
+
+// PNG is what X11 applications expect images in. The pixels go into stored deflate
+// blocks: nothing is compressed, but no compressor is needed either.
+fn encode_png(image: &ClipboardImage) -> Result<Vec<u8>, ClipboardError> {
+    if image.width == 0 || image.height == 0 {
+        return Err(ClipboardError::InvalidData("Image is empty".to_string()));
+    }
+    if image.rgba.len() != image.width * image.height * 4 {
+        return Err(ClipboardError::InvalidData(
+            "Image buffer does not match its dimensions".to_string(),
+        ));
+    }
+    // Each row starts with its filter type, 0 for none.
+    let mut raw = Vec::with_capacity((image.width * 4 + 1) * image.height);
+    for row in image.rgba.chunks_exact(image.width * 4) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlace.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        png.extend_from_slice(&crc32(kind.iter().chain(data.iter())).to_be_bytes());
+    }
+    Ok(png)
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    const ROOT: u32 = 0x100;
+    const OWNER: u32 = 0x0040_0001;
+
+    fn read_request(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).ok()?;
+        let mut body = vec![0u8; u16_at(&header, 2) as usize * 4 - 4];
+        stream.read_exact(&mut body).ok()?;
+        Some((header[0], body))
+    }
+
+    fn reply(sequence: u16, data: u8, fields: &[u32], extra: &[u8]) -> Vec<u8> {
+        let mut reply = vec![1, data];
+        reply.extend_from_slice(&sequence.to_le_bytes());
+        reply.extend_from_slice(&((extra.len() / 4) as u32).to_le_bytes());
+        reply.extend(words(fields));
+        reply.resize(32, 0);
+        reply.extend_from_slice(extra);
+        reply
+    }
+
+    // A server on the loopback that accepts one client and hands each request to
+    // `handle`, along with its sequence number. Atoms are numbered from 100 by name.
+    fn fake_server(
+        mut handle: impl FnMut(&mut TcpStream, u16, u8, &[u8], u32) + Send + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(port >= 6000);
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut setup = [0u8; 12];
+            stream.read_exact(&mut setup).unwrap();
+            let auth = padded(&vec![0; u16_at(&setup, 6) as usize]).len()
+                + padded(&vec![0; u16_at(&setup, 8) as usize]).len();
+            stream.read_exact(&mut vec![0u8; auth]).unwrap();
+
+            let mut body = vec![0u8; 72];
+            body[4..8].copy_from_slice(&0x0040_0000u32.to_le_bytes());
+            body[8..12].copy_from_slice(&0x001f_ffffu32.to_le_bytes());
+            body[18..20].copy_from_slice(&u16::MAX.to_le_bytes());
+            body[20] = 1;
+            body[32..36].copy_from_slice(&ROOT.to_le_bytes());
+            body[52..54].copy_from_slice(&1024u16.to_le_bytes());
+            body[54..56].copy_from_slice(&768u16.to_le_bytes());
+            let mut accepted = vec![1, 0, 11, 0, 0, 0];
+            accepted.extend_from_slice(&((body.len() / 4) as u16).to_le_bytes());
+            accepted.extend(body);
+            stream.write_all(&accepted).unwrap();
+
+            let mut atoms: Vec<String> = Vec::new();
+            let mut sequence = 0u16;
+            while let Some((opcode, body)) = read_request(&mut stream) {
+                sequence += 1;
+                let mut atom = 0;
+                if opcode == INTERN_ATOM {
+                    let name = String::from_utf8_lossy(&body[4..4 + u16_at(&body, 0) as usize]);
+                    atom = match atoms.iter().position(|known| *known == name) {
+                        Some(index) => index,
+                        None => {
+                            atoms.push(name.into_owned());
+                            atoms.len() - 1
+                        }
+                    } as u32
+                        + 100;
+                    stream.write_all(&reply(sequence, 0, &[atom], &[])).unwrap();
+                }
+                handle(&mut stream, sequence, opcode, &body, atom);
+            }
+        });
+        format!("127.0.0.1:{}", port - 6000)
+    }
+
+    #[test]
+    fn test_pointer_input() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let display = fake_server(move |stream, sequence, opcode, body, _| match opcode {
+            QUERY_EXTENSION => {
+                assert_eq!(&body[4..9], b"XTEST");
+                let reply = reply(sequence, 0, &[1 | 140 << 8], &[]);
+                stream.write_all(&reply).unwrap();
+            }
+            QUERY_POINTER => {
+                let fields = [ROOT, 0, 10 | 20 << 16, 0, BUTTON1_MASK as u32];
+                stream.write_all(&reply(sequence, 1, &fields, &[])).unwrap();
+            }
+            140 => {
+                assert_eq!(u32_at(body, 8), ROOT);
+                let fake = (body[0], body[1], u16_at(body, 20), u16_at(body, 22));
+                sender.send(fake).unwrap();
+            }
+            _ => {}
+        });
+        let input = X11Input::connect(&display).unwrap();
+        assert_eq!(input.get_screen_size(), (1024, 768));
+        assert!(input.is_pressed(MouseButton::Left));
+        assert!(!input.is_pressed(MouseButton::Right));
+
+        input
+            .send(&[
+                InputEvent::MoveTo { x: 5, y: 6 },
+                InputEvent::Button {
+                    button: MouseButton::Right,
+                    action: ButtonAction::Press,
+                },
+                InputEvent::Button {
+                    button: MouseButton::Back,
+                    action: ButtonAction::Press,
+                },
+                InputEvent::Button {
+                    button: MouseButton::Other(6),
+                    action: ButtonAction::Press,
+                },
+                InputEvent::Scroll {
+                    axis: ScrollAxis::Vertical,
+                    amount: -WHEEL_DELTA,
+                },
+            ])
+            .unwrap();
+        assert!(matches!(
+            input.send(&[
+                InputEvent::MoveTo { x: 1, y: 1 },
+                InputEvent::MoveTo { x: 1024, y: 0 },
+            ]),
+            Err(MouseError::OutOfBounds)
+        ));
+        // Requests are handled in order, so the fakes have all arrived once this returns.
+        assert_eq!(input.position().unwrap(), (10, 20));
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                (MOTION_NOTIFY, 0, 5, 6),
+                (BUTTON_PRESS, 3, 0, 0),
+                (BUTTON_PRESS, 8, 0, 0),
+                (BUTTON_PRESS, 10, 0, 0),
+                (BUTTON_PRESS, 5, 0, 0),
+                (BUTTON_RELEASE, 5, 0, 0),
+            ]
+        );
+        assert!(input.is_pressed(MouseButton::Back));
+        assert!(input.is_pressed(MouseButton::Other(6)));
+        assert!(!input.is_pressed(MouseButton::Forward));
+        assert!(matches!(
+            x11_button(MouseButton::Other(0)),
+            Err(MouseError::UnsupportedButton(0))
+        ));
+        assert_eq!(x11_button(MouseButton::Other(5)).unwrap(), 9);
+    }
+
+    #[test]
+    fn test_parse_display() {
+        assert_eq!(
+            parse_display(":0").unwrap(),
+            Display {
+                host: String::new(),
+                number: 0
+            }
+        );
+        assert_eq!(
+            parse_display("remote:12.1").unwrap(),
+            Display {
+                host: "remote".to_string(),
+                number: 12
+            }
+        );
+        assert!(parse_display("remote").is_err());
+        assert!(parse_display(":x").is_err());
+        assert!(parse_display(":60000").is_err());
+        assert!(X11Clipboard::new("nope", Selection::Primary).is_err());
+    }
+
+    #[test]
+    fn test_find_cookie() {
+        let mut entries = Vec::new();
+        for (number, name, data) in [
+            ("1", "MIT-MAGIC-COOKIE-1", &[1u8, 2][..]),
+            ("0", "XDM-AUTHORIZATION-1", &[3]),
+            ("0", "MIT-MAGIC-COOKIE-1", &[4, 5, 6]),
+        ] {
+            entries.extend_from_slice(&256u16.to_be_bytes());
+            for field in [&b"host"[..], number.as_bytes(), name.as_bytes(), data] {
+                entries.extend_from_slice(&(field.len() as u16).to_be_bytes());
+                entries.extend_from_slice(field);
+            }
+        }
+        assert_eq!(find_cookie(&entries, 0), Some(vec![4, 5, 6]));
+        assert_eq!(find_cookie(&entries, 1), Some(vec![1, 2]));
+        assert_eq!(find_cookie(&entries, 2), None);
+        assert_eq!(find_cookie(&entries[..entries.len() - 1], 0), None);
+    }
+
+    #[test]
+    fn test_uri_list_round_trip() {
+        let uri = file_uri(Path::new("/tmp/a file/100%.txt")).unwrap();
+        assert_eq!(uri, "file:///tmp/a%20file/100%25.txt");
+        let list = format!(
+            "# comment\r\n{}\r\nhttp://example.com/\r\nfile://host/x\r\n",
+            uri
+        );
+        assert_eq!(
+            parse_uri_list(&list),
+            vec![PathBuf::from("/tmp/a file/100%.txt"), PathBuf::from("/x")]
+        );
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND".iter()), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let image = ClipboardImage {
+            width: 2,
+            height: 1,
+            rgba: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let png = encode_png(&image).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"));
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        // The filter byte and both pixels, stored without compression.
+        let idat = png.windows(4).position(|chunk| chunk == b"IDAT").unwrap();
+        assert_eq!(&png[idat + 7..idat + 12], &[9, 0, !9, 0xff, 0]);
+        assert_eq!(
+            &png[idat + 11..idat + 20],
+            &[0, 255, 0, 0, 255, 0, 0, 255, 128]
+        );
+
+        let empty = ClipboardImage {
+            width: 0,
+            height: 0,
+            rgba: Vec::new(),
+        };
+        assert!(encode_png(&empty).is_err());
+    }
+
+    #[test]
+    fn test_get_text() {
+        let mut window = 0;
+        let mut target = 0;
+        let display = fake_server(move |stream, sequence, opcode, body, _| match opcode {
+            CREATE_WINDOW => window = u32_at(body, 0),
+            CONVERT_SELECTION => {
+                assert_eq!(u32_at(body, 4), 100, "CLIPBOARD is interned first");
+                target = u32_at(body, 8);
+                let mut notify = vec![SELECTION_NOTIFY, 0];
+                notify.extend_from_slice(&sequence.to_le_bytes());
+                notify.extend(words(&[
+                    0,
+                    window,
+                    u32_at(body, 4),
+                    target,
+                    u32_at(body, 12),
+                ]));
+                notify.resize(32, 0);
+                stream.write_all(&notify).unwrap();
+            }
+            GET_PROPERTY => {
+                assert_eq!(u32_at(body, 0), window);
+                let text = b"h\xc3\xa9llo\0\0\0";
+                let reply = reply(sequence, 8, &[target, 0, 6], text);
+                stream.write_all(&reply).unwrap();
+            }
+            _ => {}
+        });
+        let clipboard = X11Clipboard::new(&display, Selection::Clipboard).unwrap();
+        assert_eq!(clipboard.get_text().unwrap(), "héllo");
+    }
+
+    #[test]
+    fn test_owner_answers_requests() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut atoms = Vec::new();
+        let display = fake_server(move |stream, sequence, opcode, body, atom| match opcode {
+            INTERN_ATOM => atoms.push(atom),
+            SET_SELECTION_OWNER => assert_eq!(u32_at(body, 4), PRIMARY),
+            GET_SELECTION_OWNER => {
+                stream
+                    .write_all(&reply(sequence, 0, &[OWNER], &[]))
+                    .unwrap();
+                // Another client asks for the first offer, UTF8_STRING.
+                let mut request = vec![SELECTION_REQUEST, 0];
+                request.extend_from_slice(&sequence.to_le_bytes());
+                request.extend(words(&[0, OWNER, 0x500, PRIMARY, atoms[0], 7]));
+                request.resize(32, 0);
+                stream.write_all(&request).unwrap();
+            }
+            CHANGE_PROPERTY => {
+                let length = u32_at(body, 16) as usize;
+                sender
+                    .send((opcode, body[..12].to_vec(), body[20..20 + length].to_vec()))
+                    .unwrap();
+            }
+            SEND_EVENT => sender
+                .send((opcode, body[8..40].to_vec(), Vec::new()))
+                .unwrap(),
+            _ => {}
+        });
+        let clipboard = X11Clipboard::new(&display, Selection::Primary).unwrap();
+        clipboard.set_text("hi").unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let (opcode, header, value) = receiver.recv_timeout(timeout).unwrap();
+        assert_eq!(opcode, CHANGE_PROPERTY);
+        assert_eq!(header, words(&[0x500, 7, 100]));
+        assert_eq!(value, b"hi");
+        let (opcode, event, _) = receiver.recv_timeout(timeout).unwrap();
+        assert_eq!(opcode, SEND_EVENT);
+        assert_eq!(event[0], SELECTION_NOTIFY);
+        assert_eq!(&event[8..24], &words(&[0x500, PRIMARY, 100, 7])[..]);
+    }
+
+    #[test]
+    fn test_lost_ownership_is_unavailable() {
+        let display = fake_server(|stream, sequence, opcode, _, _| {
+            if opcode == GET_SELECTION_OWNER {
+                stream
+                    .write_all(&reply(sequence, 0, &[0x0999_0001], &[]))
+                    .unwrap();
+            }
+        });
+        let clipboard = X11Clipboard::new(&display, Selection::Primary).unwrap();
+        assert!(matches!(
+            clipboard.set_text("hi"),
+            Err(ClipboardError::Unavailable)
+        ));
+    }
+}